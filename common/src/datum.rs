@@ -0,0 +1,325 @@
+// Decoders for the Githoney datums, used by the tx builder's checks and previews
+// and by the worker's bounty index
+
+use pallas_codec::minicbor;
+use pallas_primitives::alonzo::{BigInt, Constr, PlutusData};
+use serde::{Deserialize, Serialize};
+
+// Decoded view of the on-chain GithoneyDatum (see `protocol/main.tx3`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GithoneyDatum {
+    pub admin_payment_key: String,
+    pub maintainer_payment_key: String,
+    pub maintainer_stake_key: Option<String>,
+    pub contributor_payment_key: Option<String>,
+    pub contributor_stake_key: Option<String>,
+    pub bounty_reward_fee: u64,
+    // POSIX time in milliseconds
+    pub deadline: u64,
+    pub merged: bool,
+}
+
+// Decodes an inline GithoneyDatum from its original CBOR bytes.
+// Returns None if the datum does not have the expected shape.
+pub fn decode_githoney_datum(cbor: &[u8]) -> Option<GithoneyDatum> {
+    let data: PlutusData = minicbor::decode(cbor).ok()?;
+    let fields = constr_fields(&data, 0)?;
+
+    if fields.len() != 7 {
+        return None;
+    }
+
+    let (maintainer_payment_key, maintainer_stake_key) = address_keys(&fields[1])?;
+
+    let (contributor_payment_key, contributor_stake_key) = match constr_index(as_constr(&fields[2])?)? {
+        0 => {
+            let (payment_key, stake_key) = address_keys(constr_fields(&fields[2], 0)?.first()?)?;
+            (Some(payment_key), stake_key)
+        }
+        1 => (None, None),
+        _ => return None,
+    };
+
+    Some(GithoneyDatum {
+        admin_payment_key: credential_key(&fields[0])?,
+        maintainer_payment_key,
+        maintainer_stake_key,
+        contributor_payment_key,
+        contributor_stake_key,
+        bounty_reward_fee: as_u64(&fields[3])?,
+        deadline: as_u64(&fields[4])?,
+        merged: as_bool(&fields[5])?,
+    })
+}
+
+// Decoded view of the on-chain SettingsDatum (see `protocol/main.tx3`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SettingsDatum {
+    pub githoney_payment_key: String,
+    pub githoney_stake_key: Option<String>,
+    pub bounty_creation_fee: u64,
+    pub bounty_reward_fee: u64,
+}
+
+// Decodes an inline SettingsDatum from its original CBOR bytes.
+// Returns None if the datum does not have the expected shape.
+pub fn decode_settings_datum(cbor: &[u8]) -> Option<SettingsDatum> {
+    let data: PlutusData = minicbor::decode(cbor).ok()?;
+    let fields = constr_fields(&data, 0)?;
+
+    if fields.len() != 3 {
+        return None;
+    }
+
+    let (githoney_payment_key, githoney_stake_key) = address_keys(&fields[0])?;
+
+    Some(SettingsDatum {
+        githoney_payment_key,
+        githoney_stake_key,
+        bounty_creation_fee: as_u64(&fields[1])?,
+        bounty_reward_fee: as_u64(&fields[2])?,
+    })
+}
+
+// Plutus constructor tags: 121..=127 for indexes 0..=6, 1280..=1400 for 7..=127
+// and 102 for the general form carrying the index explicitly.
+fn constr_index(constr: &Constr<PlutusData>) -> Option<u64> {
+    match constr.tag {
+        121..=127 => Some(constr.tag - 121),
+        1280..=1400 => Some(constr.tag - 1280 + 7),
+        102 => constr.any_constructor,
+        _ => None,
+    }
+}
+
+// Constructor index of any Plutus data, e.g. to name a redeemer
+pub fn constructor_index(data: &PlutusData) -> Option<u64> {
+    constr_index(as_constr(data)?)
+}
+
+// Whether two datums hold the same data, however their maps were encoded: a
+// datum rebuilt field by field may use definite lengths where the original
+// used indefinite ones. False if either does not decode.
+pub fn same_datum(a: &[u8], b: &[u8]) -> bool {
+    match (
+        minicbor::decode::<PlutusData>(a),
+        minicbor::decode::<PlutusData>(b),
+    ) {
+        (Ok(a), Ok(b)) => same_data(&a, &b),
+        _ => false,
+    }
+}
+
+fn same_data(a: &PlutusData, b: &PlutusData) -> bool {
+    let same_list = |a: &[PlutusData], b: &[PlutusData]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_data(a, b))
+    };
+
+    match (a, b) {
+        (PlutusData::Constr(a), PlutusData::Constr(b)) => {
+            constr_index(a).is_some() && constr_index(a) == constr_index(b) && same_list(&a.fields, &b.fields)
+        }
+        (PlutusData::Map(a), PlutusData::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((ak, av), (bk, bv))| same_data(ak, bk) && same_data(av, bv))
+        }
+        (PlutusData::Array(a), PlutusData::Array(b)) => same_list(a, b),
+        _ => a == b,
+    }
+}
+
+fn as_constr(data: &PlutusData) -> Option<&Constr<PlutusData>> {
+    match data {
+        PlutusData::Constr(constr) => Some(constr),
+        _ => None,
+    }
+}
+
+fn constr_fields(data: &PlutusData, index: u64) -> Option<&Vec<PlutusData>> {
+    let constr = as_constr(data)?;
+    (constr_index(constr)? == index).then_some(&constr.fields)
+}
+
+fn as_bytes(data: &PlutusData) -> Option<String> {
+    match data {
+        PlutusData::BoundedBytes(bytes) => Some(hex::encode(bytes.as_slice())),
+        _ => None,
+    }
+}
+
+fn as_u64(data: &PlutusData) -> Option<u64> {
+    match data {
+        PlutusData::BigInt(BigInt::Int(int)) => u64::try_from(i128::from(int.0)).ok(),
+        _ => None,
+    }
+}
+
+fn as_bool(data: &PlutusData) -> Option<bool> {
+    match constr_index(as_constr(data)?)? {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+// CardanoCredential::VerificationKey { VerificationKey: Bytes }
+fn credential_key(data: &PlutusData) -> Option<String> {
+    as_bytes(constr_fields(data, 0)?.first()?)
+}
+
+// CardanoAddress { payment_credential, staking_credential } -> (payment key, stake key)
+fn address_keys(data: &PlutusData) -> Option<(String, Option<String>)> {
+    let fields = constr_fields(data, 0)?;
+    let payment_key = credential_key(fields.first()?)?;
+
+    // OptionAddressCardanoCredential::Some { StakeCredWrapper::Inline { CardanoCredential } }
+    let stake_key = constr_fields(fields.get(1)?, 0)
+        .and_then(|some| constr_fields(some.first()?, 0))
+        .and_then(|inline| credential_key(inline.first()?));
+
+    Some((payment_key, stake_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constr(index: u64, fields: Vec<PlutusData>) -> PlutusData {
+        PlutusData::Constr(Constr {
+            tag: 121 + index,
+            any_constructor: None,
+            fields,
+        })
+    }
+
+    fn bytes(hex_bytes: &str) -> PlutusData {
+        PlutusData::BoundedBytes(hex::decode(hex_bytes).unwrap().into())
+    }
+
+    fn int(value: i64) -> PlutusData {
+        PlutusData::BigInt(BigInt::Int(value.into()))
+    }
+
+    fn credential(key: &str) -> PlutusData {
+        constr(0, vec![bytes(key)])
+    }
+
+    fn address(payment: &str, stake: Option<&str>) -> PlutusData {
+        let stake = match stake {
+            Some(key) => constr(0, vec![constr(0, vec![credential(key)])]),
+            None => constr(1, vec![]),
+        };
+        constr(0, vec![credential(payment), stake])
+    }
+
+    fn encode(data: &PlutusData) -> Vec<u8> {
+        minicbor::to_vec(data).unwrap()
+    }
+
+    fn githoney_datum(contributor: PlutusData, merged: bool) -> PlutusData {
+        constr(
+            0,
+            vec![
+                credential("aa"),
+                address("bb", Some("cc")),
+                contributor,
+                int(3),
+                int(1_700_000_000_000),
+                constr(u64::from(merged), vec![]),
+                int(10_000_000),
+            ],
+        )
+    }
+
+    #[test]
+    fn decodes_githoney_datums() {
+        let datum = decode_githoney_datum(&encode(&githoney_datum(constr(1, vec![]), false))).unwrap();
+        assert_eq!(datum.admin_payment_key, "aa");
+        assert_eq!(datum.maintainer_payment_key, "bb");
+        assert_eq!(datum.maintainer_stake_key.as_deref(), Some("cc"));
+        assert_eq!(datum.contributor_payment_key, None);
+        assert_eq!(datum.bounty_reward_fee, 3);
+        assert_eq!(datum.deadline, 1_700_000_000_000);
+        assert!(!datum.merged);
+
+        let assigned = constr(0, vec![address("dd", None)]);
+        let datum = decode_githoney_datum(&encode(&githoney_datum(assigned, true))).unwrap();
+        assert_eq!(datum.contributor_payment_key.as_deref(), Some("dd"));
+        assert_eq!(datum.contributor_stake_key, None);
+        assert!(datum.merged);
+    }
+
+    #[test]
+    fn rejects_malformed_githoney_datums() {
+        // Wrong constructor, missing field, negative fee and garbage bytes
+        let mut wrong_constructor = githoney_datum(constr(1, vec![]), false);
+        if let PlutusData::Constr(datum) = &mut wrong_constructor {
+            datum.tag = 122;
+        }
+        assert!(decode_githoney_datum(&encode(&wrong_constructor)).is_none());
+
+        let PlutusData::Constr(mut short) = githoney_datum(constr(1, vec![]), false) else {
+            unreachable!()
+        };
+        short.fields.pop();
+        assert!(decode_githoney_datum(&encode(&PlutusData::Constr(short))).is_none());
+
+        let PlutusData::Constr(mut negative) = githoney_datum(constr(1, vec![]), false) else {
+            unreachable!()
+        };
+        negative.fields[3] = int(-1);
+        assert!(decode_githoney_datum(&encode(&PlutusData::Constr(negative))).is_none());
+
+        assert!(decode_githoney_datum(&[0xff, 0x00]).is_none());
+    }
+
+    #[test]
+    fn compares_datums_whatever_their_map_encoding() {
+        // Constr 0 [{h'': 1}], with a definite and an indefinite length map
+        let definite = [0xd8, 0x79, 0x9f, 0xa1, 0x40, 0x01, 0xff];
+        let indefinite = [0xd8, 0x79, 0x9f, 0xbf, 0x40, 0x01, 0xff, 0xff];
+        assert_ne!(
+            minicbor::decode::<PlutusData>(&definite).unwrap(),
+            minicbor::decode::<PlutusData>(&indefinite).unwrap()
+        );
+        assert!(same_datum(&definite, &indefinite));
+
+        let other = [0xd8, 0x79, 0x9f, 0xa1, 0x40, 0x02, 0xff];
+        assert!(!same_datum(&definite, &other));
+        assert!(!same_datum(&definite, &[0xff, 0x00]));
+    }
+
+    #[test]
+    fn decodes_settings_datums() {
+        let settings = constr(0, vec![address("aa", None), int(5_000_000), int(2)]);
+        let datum = decode_settings_datum(&encode(&settings)).unwrap();
+        assert_eq!(datum.githoney_payment_key, "aa");
+        assert_eq!(datum.githoney_stake_key, None);
+        assert_eq!(datum.bounty_creation_fee, 5_000_000);
+        assert_eq!(datum.bounty_reward_fee, 2);
+    }
+
+    #[test]
+    fn reads_every_constructor_tag_form() {
+        let general = |index| Constr::<PlutusData> {
+            tag: 102,
+            any_constructor: Some(index),
+            fields: vec![],
+        };
+        let compact = |tag| Constr::<PlutusData> {
+            tag,
+            any_constructor: None,
+            fields: vec![],
+        };
+        assert_eq!(constr_index(&compact(121)), Some(0));
+        assert_eq!(constr_index(&compact(127)), Some(6));
+        assert_eq!(constr_index(&compact(1280)), Some(7));
+        assert_eq!(constr_index(&compact(1400)), Some(127));
+        assert_eq!(constr_index(&general(200)), Some(200));
+        assert_eq!(constr_index(&compact(128)), None);
+    }
+}
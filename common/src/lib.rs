@@ -0,0 +1,6 @@
+// Types and helpers shared by the tx builder server and the Balius worker
+
+pub mod datum;
+pub mod preview;
+pub mod time;
+pub mod types;
@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use pallas_addresses::Address;
+use pallas_primitives::conway::{MintedDatumOption, RedeemerTag};
+use pallas_traverse::{MultiEraInput, MultiEraOutput, MultiEraPolicyAssets, MultiEraTx};
+use serde::Serialize;
+
+use crate::datum::{
+    constructor_index, decode_githoney_datum, decode_settings_datum, GithoneyDatum, SettingsDatum,
+};
+use crate::time::SlotConfig;
+
+// Constructors of `GithoneyContractRedeemers` in `protocol/main.tx3`
+const GITHONEY_REDEEMERS: &[&str] = &["AddRewards", "Assign", "Merge", "Close", "Claim"];
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Asset {
+    pub policy_id: String,
+    pub asset_name: String,
+    // The asset name as text, when it is valid UTF-8
+    pub asset_name_utf8: Option<String>,
+    pub quantity: i128,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum DatumPreview {
+    Hash { hash: String },
+    Githoney(GithoneyDatum),
+    Settings(SettingsDatum),
+    // Inline data of any other shape
+    Raw { cbor: String },
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OutputPreview {
+    pub address: String,
+    pub lovelace: u64,
+    pub assets: Vec<Asset>,
+    pub datum: Option<DatumPreview>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RedeemerPreview {
+    // spend, mint, cert, reward, vote or propose
+    pub purpose: String,
+    pub index: u32,
+    // The input (`txhash#index`) or policy id the redeemer applies to
+    pub target: Option<String>,
+    // Githoney redeemer name; None for spends of inputs not known to be at the script
+    pub name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TimePreview {
+    pub slot: u64,
+    pub time: Option<String>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TxPreview {
+    pub hash: String,
+    pub fee: Option<u64>,
+    pub valid_from: Option<TimePreview>,
+    pub valid_until: Option<TimePreview>,
+    pub inputs: Vec<String>,
+    pub reference_inputs: Vec<String>,
+    pub collateral: Vec<String>,
+    pub outputs: Vec<OutputPreview>,
+    pub mints: Vec<Asset>,
+    // Quantities are the amounts burned
+    pub burns: Vec<Asset>,
+    pub redeemers: Vec<RedeemerPreview>,
+    pub required_signers: Vec<String>,
+}
+
+pub fn input_ref(input: &MultiEraInput) -> String {
+    format!("{}#{}", input.hash(), input.index())
+}
+
+pub fn address_text(address: &Address) -> String {
+    match address {
+        Address::Byron(byron) => byron.to_base58(),
+        _ => address
+            .to_bech32()
+            .unwrap_or_else(|_| hex::encode(address.to_vec())),
+    }
+}
+
+pub fn assets(policies: &[MultiEraPolicyAssets]) -> Vec<Asset> {
+    policies
+        .iter()
+        .flat_map(|policy| {
+            policy.assets().into_iter().map(move |asset| Asset {
+                policy_id: policy.policy().to_string(),
+                asset_name: hex::encode(asset.name()),
+                asset_name_utf8: String::from_utf8(asset.name().to_vec()).ok(),
+                quantity: asset.any_coin(),
+            })
+        })
+        .collect()
+}
+
+fn datum_preview(output: &MultiEraOutput) -> Option<DatumPreview> {
+    let preview = match output.datum()? {
+        MintedDatumOption::Hash(hash) => DatumPreview::Hash {
+            hash: hash.to_string(),
+        },
+        MintedDatumOption::Data(data) => {
+            let cbor = data.0.raw_cbor();
+            if let Some(datum) = decode_githoney_datum(cbor) {
+                DatumPreview::Githoney(datum)
+            } else if let Some(datum) = decode_settings_datum(cbor) {
+                DatumPreview::Settings(datum)
+            } else {
+                DatumPreview::Raw {
+                    cbor: hex::encode(cbor),
+                }
+            }
+        }
+    };
+    Some(preview)
+}
+
+fn output_preview(output: &MultiEraOutput) -> OutputPreview {
+    OutputPreview {
+        address: output
+            .address()
+            .map(|address| address_text(&address))
+            .unwrap_or_default(),
+        lovelace: output.lovelace_amount(),
+        assets: assets(&output.non_ada_assets()),
+        datum: datum_preview(output),
+    }
+}
+
+fn time_preview(slots: &SlotConfig, slot: u64) -> TimePreview {
+    let posix_ms = slots.slot_to_posix_ms(slot);
+    TimePreview {
+        slot,
+        time: DateTime::<Utc>::from_timestamp_millis(posix_ms as i64).map(|time| time.to_rfc3339()),
+    }
+}
+
+// Spend redeemers are indexed into the sorted inputs and mint redeemers into
+// the sorted policies. A spend redeemer is named after the Githoney script's
+// only when its input is one of `script_inputs`.
+fn redeemer_previews(tx: &MultiEraTx, script_inputs: &[String]) -> Vec<RedeemerPreview> {
+    let mut inputs = tx.inputs();
+    inputs.sort_by_key(|input| (*input.hash(), input.index()));
+    let mut policies = tx.mints();
+    policies.sort_by_key(|policy| *policy.policy());
+
+    tx.redeemers()
+        .iter()
+        .map(|redeemer| {
+            let index = redeemer.index();
+            let (purpose, target, name) = match redeemer.tag() {
+                RedeemerTag::Spend => {
+                    let target = inputs.get(index as usize).map(input_ref);
+                    let name = target
+                        .as_ref()
+                        .filter(|input| script_inputs.contains(input))
+                        .and_then(|_| constructor_index(redeemer.data()))
+                        .and_then(|i| GITHONEY_REDEEMERS.get(i as usize))
+                        .map(|name| name.to_string());
+                    ("spend", target, name)
+                }
+                RedeemerTag::Mint => {
+                    let policy = policies.get(index as usize);
+                    let burning = policy
+                        .map(|policy| policy.assets().iter().all(|asset| asset.any_coin() < 0))
+                        .unwrap_or(false);
+                    (
+                        "mint",
+                        policy.map(|policy| policy.policy().to_string()),
+                        Some(if burning { "Burn" } else { "Mint" }.to_string()),
+                    )
+                }
+                RedeemerTag::Cert => ("cert", None, None),
+                RedeemerTag::Reward => ("reward", None, None),
+                RedeemerTag::Vote => ("vote", None, None),
+                RedeemerTag::Propose => ("propose", None, None),
+            };
+
+            RedeemerPreview {
+                purpose: purpose.to_string(),
+                index,
+                target,
+                name,
+            }
+        })
+        .collect()
+}
+
+// Decodes a transaction into a reviewable summary. The transaction body does
+// not say where its inputs sit, so callers look up which of them are locked at
+// the Githoney script and pass them as `script_inputs`.
+pub fn preview_tx(tx: &MultiEraTx, slots: &SlotConfig, script_inputs: &[String]) -> TxPreview {
+    let minted = assets(&tx.mints());
+    let (mints, burns): (Vec<Asset>, Vec<Asset>) =
+        minted.into_iter().partition(|asset| asset.quantity > 0);
+
+    TxPreview {
+        hash: tx.hash().to_string(),
+        fee: tx.fee(),
+        valid_from: tx.validity_start().map(|slot| time_preview(slots, slot)),
+        valid_until: tx.ttl().map(|slot| time_preview(slots, slot)),
+        inputs: tx.inputs().iter().map(input_ref).collect(),
+        reference_inputs: tx.reference_inputs().iter().map(input_ref).collect(),
+        collateral: tx.collateral().iter().map(input_ref).collect(),
+        outputs: tx.outputs().iter().map(output_preview).collect(),
+        mints,
+        burns: burns
+            .into_iter()
+            .map(|asset| Asset {
+                quantity: -asset.quantity,
+                ..asset
+            })
+            .collect(),
+        redeemers: redeemer_previews(tx, script_inputs),
+        required_signers: tx
+            .required_signers()
+            .collect::<Vec<_>>()
+            .iter()
+            .map(|signer| signer.to_string())
+            .collect(),
+    }
+}
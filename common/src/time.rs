@@ -0,0 +1,277 @@
+use chrono::DateTime;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+// Default validity window when only `since` (or nothing) is given
+pub const DEFAULT_VALIDITY_MS: u64 = 2 * 60 * 60 * 1000;
+// Default bounty deadline when `time_limit` is omitted
+pub const DEFAULT_DEADLINE_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+// Shelley-era slot parameters of a network
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotConfig {
+    pub zero_time_ms: u64,
+    pub zero_slot: u64,
+    pub slot_length_ms: u64,
+}
+
+impl SlotConfig {
+    pub fn for_network(network: &str) -> Option<SlotConfig> {
+        match network {
+            "mainnet" => Some(SlotConfig {
+                zero_time_ms: 1596059091000,
+                zero_slot: 4492800,
+                slot_length_ms: 1000,
+            }),
+            "preprod" => Some(SlotConfig {
+                zero_time_ms: 1655769600000,
+                zero_slot: 86400,
+                slot_length_ms: 1000,
+            }),
+            "preview" => Some(SlotConfig {
+                zero_time_ms: 1666656000000,
+                zero_slot: 0,
+                slot_length_ms: 1000,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn slot_to_posix_ms(&self, slot: u64) -> u64 {
+        self.zero_time_ms + slot.saturating_sub(self.zero_slot) * self.slot_length_ms
+    }
+
+    pub fn posix_ms_to_slot(&self, posix_ms: u64) -> u64 {
+        self.zero_slot + posix_ms.saturating_sub(self.zero_time_ms) / self.slot_length_ms
+    }
+}
+
+// A time field that could not be resolved
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeError {
+    pub field: &'static str,
+    pub reason: String,
+}
+
+impl fmt::Display for TimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {}: {}", self.field, self.reason)
+    }
+}
+
+// A user-provided point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeInput {
+    // A bare integer, passed through unchanged (slot or POSIX ms depending on the field)
+    Raw(u64),
+    // An ISO-8601 / RFC 3339 timestamp, as POSIX ms
+    At(u64),
+    // A duration in ms, relative to the field's reference point
+    After(u64),
+}
+
+// Accepts "12345", "2026-11-01T00:00:00Z", "2h", "30 days", "valid for 2h" or "deadline in 30 days"
+pub fn parse_time_input(input: &str) -> Result<TimeInput, String> {
+    let input = input.trim();
+
+    if let Ok(raw) = input.parse::<u64>() {
+        return Ok(TimeInput::Raw(raw));
+    }
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(input) {
+        return u64::try_from(timestamp.timestamp_millis())
+            .map(TimeInput::At)
+            .map_err(|_| format!("{} is before the POSIX epoch", input));
+    }
+
+    parse_duration_ms(input)
+        .map(TimeInput::After)
+        .ok_or_else(|| format!("{} is not a number, ISO-8601 timestamp or duration", input))
+}
+
+fn parse_duration_ms(input: &str) -> Option<u64> {
+    let lower = input.to_lowercase();
+    let text = ["valid for ", "deadline in ", "in "]
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .unwrap_or(&lower)
+        .trim();
+
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+
+    let unit_ms = match unit.trim() {
+        "s" | "sec" | "secs" | "second" | "seconds" => 1000,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60 * 1000,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60 * 1000,
+        "d" | "day" | "days" => 24 * 60 * 60 * 1000,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60 * 1000,
+        _ => return None,
+    };
+
+    amount.checked_mul(unit_ms)
+}
+
+// A time field as sent in a request body: a bare number or any string `parse_time_input` accepts
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(
+        rename = "TimeInput",
+        description = "A slot (or POSIX ms for `time_limit`), an RFC 3339 timestamp, or a duration such as \"2h\", \"30 days\" or \"valid for 2h\""
+    )
+)]
+#[serde(untagged)]
+pub enum TimeValue {
+    Number(u64),
+    Text(String),
+}
+
+impl fmt::Display for TimeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeValue::Number(number) => write!(f, "{}", number),
+            TimeValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+// Time fields accept JSON strings as well as bare numbers
+pub fn de_time_input<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<TimeValue>::deserialize(deserializer)?.map(|value| value.to_string()))
+}
+
+fn field_input(field: &'static str, input: Option<&str>) -> Result<Option<TimeInput>, TimeError> {
+    input
+        .filter(|input| !input.trim().is_empty())
+        .map(parse_time_input)
+        .transpose()
+        .map_err(|reason| TimeError { field, reason })
+}
+
+// Defaults and durations need the current time. The worker takes it from the
+// chain tip, which is unknown until the first block.
+fn require_now(field: &'static str, now_ms: Option<u64>) -> Result<u64, TimeError> {
+    now_ms.ok_or_else(|| TimeError {
+        field,
+        reason: "current time is not known yet, pass an explicit slot or timestamp".to_string(),
+    })
+}
+
+// Resolves `since`/`until` into a validity interval in slots.
+// Durations are relative to now for `since` and to `since` for `until`.
+pub fn resolve_validity(
+    slots: &SlotConfig,
+    now_ms: Option<u64>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<(u64, u64), TimeError> {
+    let since_slot = match field_input("since", since)? {
+        Some(TimeInput::Raw(slot)) => slot,
+        Some(TimeInput::At(posix_ms)) => slots.posix_ms_to_slot(posix_ms),
+        Some(TimeInput::After(duration_ms)) => {
+            slots.posix_ms_to_slot(require_now("since", now_ms)? + duration_ms)
+        }
+        None => slots.posix_ms_to_slot(require_now("since", now_ms)?),
+    };
+    let since_ms = slots.slot_to_posix_ms(since_slot);
+
+    let until_slot = match field_input("until", until)? {
+        Some(TimeInput::Raw(slot)) => slot,
+        Some(TimeInput::At(posix_ms)) => slots.posix_ms_to_slot(posix_ms),
+        Some(TimeInput::After(duration_ms)) => slots.posix_ms_to_slot(since_ms + duration_ms),
+        None => slots.posix_ms_to_slot(since_ms + DEFAULT_VALIDITY_MS),
+    };
+
+    if until_slot <= since_slot {
+        return Err(TimeError {
+            field: "until",
+            reason: format!("slot {} is not after since slot {}", until_slot, since_slot),
+        });
+    }
+
+    Ok((since_slot, until_slot))
+}
+
+// Resolves `time_limit` into the datum deadline in POSIX ms, durations being relative to now
+pub fn resolve_deadline(now_ms: Option<u64>, time_limit: Option<&str>) -> Result<u64, TimeError> {
+    Ok(match field_input("time_limit", time_limit)? {
+        Some(TimeInput::Raw(posix_ms)) | Some(TimeInput::At(posix_ms)) => posix_ms,
+        Some(TimeInput::After(duration_ms)) => require_now("time_limit", now_ms)? + duration_ms,
+        None => require_now("time_limit", now_ms)? + DEFAULT_DEADLINE_MS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREVIEW: SlotConfig = SlotConfig {
+        zero_time_ms: 1666656000000,
+        zero_slot: 0,
+        slot_length_ms: 1000,
+    };
+
+    #[test]
+    fn converts_between_slots_and_posix_time() {
+        let mainnet = SlotConfig::for_network("mainnet").unwrap();
+        assert_eq!(mainnet.slot_to_posix_ms(4492800), 1596059091000);
+        assert_eq!(mainnet.posix_ms_to_slot(1596059091000 + 10_500), 4492810);
+        assert_eq!(mainnet.posix_ms_to_slot(mainnet.slot_to_posix_ms(120_000_000)), 120_000_000);
+
+        // Before the Shelley start both ends clamp to it
+        assert_eq!(mainnet.slot_to_posix_ms(0), 1596059091000);
+        assert_eq!(mainnet.posix_ms_to_slot(0), 4492800);
+        assert_eq!(SlotConfig::for_network("sanchonet"), None);
+    }
+
+    #[test]
+    fn parses_time_inputs() {
+        assert_eq!(parse_time_input("12345"), Ok(TimeInput::Raw(12345)));
+        assert_eq!(
+            parse_time_input("2022-10-25T00:00:10Z"),
+            Ok(TimeInput::At(1666656010000))
+        );
+        assert_eq!(parse_time_input("2h"), Ok(TimeInput::After(2 * 60 * 60 * 1000)));
+        assert_eq!(
+            parse_time_input("deadline in 30 days"),
+            Ok(TimeInput::After(DEFAULT_DEADLINE_MS))
+        );
+        assert_eq!(parse_time_input(" valid for 90 s "), Ok(TimeInput::After(90_000)));
+        assert!(parse_time_input("2 fortnights").is_err());
+        assert!(parse_time_input("1969-12-31T23:59:59Z").is_err());
+    }
+
+    #[test]
+    fn resolves_validity_intervals() {
+        let now = PREVIEW.slot_to_posix_ms(1_000);
+
+        // Defaults to now plus DEFAULT_VALIDITY_MS
+        assert_eq!(resolve_validity(&PREVIEW, Some(now), None, None), Ok((1_000, 8_200)));
+        // `until` durations count from `since`
+        assert_eq!(
+            resolve_validity(&PREVIEW, Some(now), Some("10m"), Some("1h")),
+            Ok((1_600, 5_200))
+        );
+        assert_eq!(
+            resolve_validity(&PREVIEW, None, Some("500"), Some("700")),
+            Ok((500, 700))
+        );
+
+        let empty = resolve_validity(&PREVIEW, Some(now), Some("700"), Some("700")).unwrap_err();
+        assert_eq!(empty.field, "until");
+        let unknown_now = resolve_validity(&PREVIEW, None, None, Some("700")).unwrap_err();
+        assert_eq!(unknown_now.field, "since");
+    }
+
+    #[test]
+    fn resolves_deadlines() {
+        let now = 1_700_000_000_000;
+        assert_eq!(resolve_deadline(Some(now), None), Ok(now + DEFAULT_DEADLINE_MS));
+        assert_eq!(resolve_deadline(Some(now), Some("1d")), Ok(now + 86_400_000));
+        assert_eq!(resolve_deadline(None, Some("1800000000000")), Ok(1_800_000_000_000));
+        assert_eq!(resolve_deadline(None, Some("1d")).unwrap_err().field, "time_limit");
+    }
+}
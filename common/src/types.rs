@@ -0,0 +1,328 @@
+// Validated request argument types. Each one serializes to the string form
+// TRP expects for the matching tx3 type.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+// Cardano asset names are limited to 32 bytes
+pub const MAX_ASSET_NAME_BYTES: usize = 32;
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value).map_err(|e| format!("invalid hex {:?}: {}", value, e))
+}
+
+macro_rules! string_serde {
+    ($type:ty) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                value.parse().map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+// Hex-encoded bytes of exactly N bytes (key hashes, policy ids, tx hashes)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HexBytes<const N: usize>(String);
+
+pub type KeyHash = HexBytes<28>;
+pub type PolicyId = HexBytes<28>;
+pub type TxHash = HexBytes<32>;
+
+impl<const N: usize> HexBytes<N> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const N: usize> FromStr for HexBytes<N> {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = parse_hex(value)?;
+        if bytes.len() != N {
+            return Err(format!("expected {} bytes, got {}", N, bytes.len()));
+        }
+        Ok(HexBytes(value.to_lowercase()))
+    }
+}
+
+impl<const N: usize> fmt::Display for HexBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<const N: usize> Serialize for HexBytes<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for HexBytes<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+// Hex-encoded bytes of any length (scripts, empty policy ids for lovelace)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HexString(String);
+
+impl HexString {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for HexString {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_hex(value)?;
+        Ok(HexString(value.to_lowercase()))
+    }
+}
+
+impl fmt::Display for HexString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+string_serde!(HexString);
+
+// Hex-encoded asset name, at most 32 bytes. Empty for lovelace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssetName(String);
+
+impl AssetName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for AssetName {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = parse_hex(value)?;
+        if bytes.len() > MAX_ASSET_NAME_BYTES {
+            return Err(format!(
+                "asset names are at most {} bytes, got {}",
+                MAX_ASSET_NAME_BYTES,
+                bytes.len()
+            ));
+        }
+        Ok(AssetName(value.to_lowercase()))
+    }
+}
+
+impl fmt::Display for AssetName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+string_serde!(AssetName);
+
+// Asset name of a bounty NFT: like AssetName, but never empty
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BountyId(String);
+
+impl BountyId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for BountyId {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = parse_hex(value)?;
+        if bytes.is_empty() || bytes.len() > MAX_ASSET_NAME_BYTES {
+            return Err(format!(
+                "bounty ids are 1 to {} bytes, got {}",
+                MAX_ASSET_NAME_BYTES,
+                bytes.len()
+            ));
+        }
+        Ok(BountyId(value.to_lowercase()))
+    }
+}
+
+impl fmt::Display for BountyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+string_serde!(BountyId);
+
+// UTxO reference, written as `txhash#index`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UtxoRef {
+    pub tx_hash: TxHash,
+    pub index: u32,
+}
+
+impl FromStr for UtxoRef {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (tx_hash, index) = value
+            .split_once('#')
+            .ok_or_else(|| format!("expected txhash#index, got {:?}", value))?;
+
+        Ok(UtxoRef {
+            tx_hash: tx_hash.parse()?,
+            index: index
+                .parse()
+                .map_err(|e| format!("invalid output index {:?}: {}", index, e))?,
+        })
+    }
+}
+
+impl fmt::Display for UtxoRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.tx_hash, self.index)
+    }
+}
+
+string_serde!(UtxoRef);
+
+// Bech32 Cardano address
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address(String);
+
+impl Address {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Address {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        pallas_addresses::Address::from_bech32(value)
+            .map_err(|e| format!("invalid bech32 address {:?}: {}", value, e))?;
+        Ok(Address(value.to_string()))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+string_serde!(Address);
+
+///// JSON SCHEMAS /////
+#[cfg(feature = "schema")]
+mod schema {
+    use schemars::r#gen::SchemaGenerator;
+    use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation};
+    use schemars::JsonSchema;
+
+    use super::*;
+
+    fn string_schema(description: &str, pattern: Option<String>) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            metadata: Some(Box::new(Metadata {
+                description: Some(description.to_string()),
+                ..Default::default()
+            })),
+            string: Some(Box::new(StringValidation {
+                pattern,
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    impl<const N: usize> JsonSchema for HexBytes<N> {
+        fn schema_name() -> String {
+            format!("HexBytes{}", N)
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            string_schema(
+                &format!("Hex-encoded {} bytes", N),
+                Some(format!("^[0-9a-fA-F]{{{}}}$", N * 2)),
+            )
+        }
+    }
+
+    impl JsonSchema for HexString {
+        fn schema_name() -> String {
+            "HexString".to_string()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            string_schema("Hex-encoded bytes", Some("^([0-9a-fA-F]{2})*$".to_string()))
+        }
+    }
+
+    impl JsonSchema for AssetName {
+        fn schema_name() -> String {
+            "AssetName".to_string()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            string_schema(
+                "Hex-encoded asset name, at most 32 bytes",
+                Some(format!("^([0-9a-fA-F]{{2}}){{0,{}}}$", MAX_ASSET_NAME_BYTES)),
+            )
+        }
+    }
+
+    impl JsonSchema for BountyId {
+        fn schema_name() -> String {
+            "BountyId".to_string()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            string_schema(
+                "Hex-encoded bounty NFT asset name, 1 to 32 bytes",
+                Some(format!("^([0-9a-fA-F]{{2}}){{1,{}}}$", MAX_ASSET_NAME_BYTES)),
+            )
+        }
+    }
+
+    impl JsonSchema for UtxoRef {
+        fn schema_name() -> String {
+            "UtxoRef".to_string()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            string_schema(
+                "UTxO reference written as `txhash#index`",
+                Some("^[0-9a-fA-F]{64}#[0-9]+$".to_string()),
+            )
+        }
+    }
+
+    impl JsonSchema for Address {
+        fn schema_name() -> String {
+            "Address".to_string()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            string_schema("Bech32 Cardano address", None)
+        }
+    }
+}
@@ -130,6 +130,224 @@ impl AddParams {
     }
 }
 
+pub const ADD2_IR: &str = "ab6466656573a1694576616c506172616d6a457870656374466565736a7265666572656e63657381a1694576616c506172616da16b45787065637456616c7565826b73657474696e6773726566675574786f52656666696e7075747383a3646e616d656d73706f6e736f725f696e707574657574786f73a1694576616c506172616da16b457870656374496e707574826d73706f6e736f725f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f7267416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657202a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf46872656465656d6572644e6f6e65a3646e616d656863757272656e7431657574786f73a1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380a3646e616d656863757272656e7432657574786f73a1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380676f75747075747383a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657202a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657205a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16341646482a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657202a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657205a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16341646482a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f72674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826d73706f6e736f725f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f7267416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657202a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf4a1694576616c506172616d6a45787065637446656573a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74686f7074696f6e616cf46876616c6964697479a26573696e6365a1694576616c506172616da16b45787065637456616c7565826573696e636563496e7465756e74696ca1694576616c506172616da16b45787065637456616c75658265756e74696c63496e74656d696e747380656275726e7380656164686f63806a636f6c6c61746572616c81a1657574786f73a1694576616c506172616da16b457870656374496e707574826a636f6c6c61746572616ca56761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f7267416464726573736a6d696e5f616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1664e756d6265721a004c4b4063726566644e6f6e65646d616e79f46a636f6c6c61746572616cf5677369676e657273f6686d6574616461746180";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Add2Params {
+    pub bountyref1: String,
+    pub bountyref2: String,
+    pub rewardamount1: String,
+    pub rewardamount2: String,
+    pub rewardassetname1: String,
+    pub rewardassetname2: String,
+    pub rewardpolicyid1: String,
+    pub rewardpolicyid2: String,
+    pub script: String,
+    pub settingsref: String,
+    pub since: String,
+    pub sponsor: String,
+    pub until: String,
+}
+impl Add2Params {
+    fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut map = serde_json::Map::new();
+
+        map.insert("bountyref1".to_string(), serde_json::json!(&self.bountyref1));
+        map.insert("bountyref2".to_string(), serde_json::json!(&self.bountyref2));
+        map.insert("rewardamount1".to_string(), serde_json::json!(&self.rewardamount1));
+        map.insert("rewardamount2".to_string(), serde_json::json!(&self.rewardamount2));
+        map.insert("rewardassetname1".to_string(), serde_json::json!(&self.rewardassetname1));
+        map.insert("rewardassetname2".to_string(), serde_json::json!(&self.rewardassetname2));
+        map.insert("rewardpolicyid1".to_string(), serde_json::json!(&self.rewardpolicyid1));
+        map.insert("rewardpolicyid2".to_string(), serde_json::json!(&self.rewardpolicyid2));
+        map.insert("script".to_string(), serde_json::json!(&self.script));
+        map.insert("settingsref".to_string(), serde_json::json!(&self.settingsref));
+        map.insert("since".to_string(), serde_json::json!(&self.since));
+        map.insert("sponsor".to_string(), serde_json::json!(&self.sponsor));
+        map.insert("until".to_string(), serde_json::json!(&self.until));
+
+        map.into()
+    }
+}
+
+pub const ADD3_IR: &str = "ab6466656573a1694576616c506172616d6a457870656374466565736a7265666572656e63657381a1694576616c506172616da16b45787065637456616c7565826b73657474696e6773726566675574786f52656666696e7075747384a3646e616d656d73706f6e736f725f696e707574657574786f73a1694576616c506172616da16b457870656374496e707574826d73706f6e736f725f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f7267416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657203a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964336542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653365427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743363496e74a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf46872656465656d6572644e6f6e65a3646e616d656863757272656e7431657574786f73a1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380a3646e616d656863757272656e7432657574786f73a1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380a3646e616d656863757272656e7433657574786f73a1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380676f75747075747384a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657202a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657205a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16341646482a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826863757272656e7431a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656631675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657202a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657205a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16341646482a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826863757272656e7432a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656632675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657202a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657205a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16341646482a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826863757272656e7433a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c7565826a626f756e747972656633675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964336542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653365427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743363496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f72674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826d73706f6e736f725f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f7267416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657203a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964336542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653365427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743363496e74a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf4a1694576616c506172616d6a45787065637446656573a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964316542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653165427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743163496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964326542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653265427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743263496e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c7565826f726577617264706f6c6963796964336542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c7565827072657761726461737365746e616d653365427974657366616d6f756e74a1694576616c506172616da16b45787065637456616c7565826d726577617264616d6f756e743363496e74686f7074696f6e616cf46876616c6964697479a26573696e6365a1694576616c506172616da16b45787065637456616c7565826573696e636563496e7465756e74696ca1694576616c506172616da16b45787065637456616c75658265756e74696c63496e74656d696e747380656275726e7380656164686f63806a636f6c6c61746572616c81a1657574786f73a1694576616c506172616da16b457870656374496e707574826a636f6c6c61746572616ca56761646472657373a1694576616c506172616da16b45787065637456616c7565826773706f6e736f7267416464726573736a6d696e5f616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1664e756d6265721a004c4b4063726566644e6f6e65646d616e79f46a636f6c6c61746572616cf5677369676e657273f6686d6574616461746180";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Add3Params {
+    pub bountyref1: String,
+    pub bountyref2: String,
+    pub bountyref3: String,
+    pub rewardamount1: String,
+    pub rewardamount2: String,
+    pub rewardamount3: String,
+    pub rewardassetname1: String,
+    pub rewardassetname2: String,
+    pub rewardassetname3: String,
+    pub rewardpolicyid1: String,
+    pub rewardpolicyid2: String,
+    pub rewardpolicyid3: String,
+    pub script: String,
+    pub settingsref: String,
+    pub since: String,
+    pub sponsor: String,
+    pub until: String,
+}
+impl Add3Params {
+    fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut map = serde_json::Map::new();
+
+        map.insert("bountyref1".to_string(), serde_json::json!(&self.bountyref1));
+        map.insert("bountyref2".to_string(), serde_json::json!(&self.bountyref2));
+        map.insert("bountyref3".to_string(), serde_json::json!(&self.bountyref3));
+        map.insert("rewardamount1".to_string(), serde_json::json!(&self.rewardamount1));
+        map.insert("rewardamount2".to_string(), serde_json::json!(&self.rewardamount2));
+        map.insert("rewardamount3".to_string(), serde_json::json!(&self.rewardamount3));
+        map.insert("rewardassetname1".to_string(), serde_json::json!(&self.rewardassetname1));
+        map.insert("rewardassetname2".to_string(), serde_json::json!(&self.rewardassetname2));
+        map.insert("rewardassetname3".to_string(), serde_json::json!(&self.rewardassetname3));
+        map.insert("rewardpolicyid1".to_string(), serde_json::json!(&self.rewardpolicyid1));
+        map.insert("rewardpolicyid2".to_string(), serde_json::json!(&self.rewardpolicyid2));
+        map.insert("rewardpolicyid3".to_string(), serde_json::json!(&self.rewardpolicyid3));
+        map.insert("script".to_string(), serde_json::json!(&self.script));
+        map.insert("settingsref".to_string(), serde_json::json!(&self.settingsref));
+        map.insert("since".to_string(), serde_json::json!(&self.since));
+        map.insert("sponsor".to_string(), serde_json::json!(&self.sponsor));
+        map.insert("until".to_string(), serde_json::json!(&self.until));
+
+        map.into()
+    }
+}
+
+pub const CLOSE_BEFORE_CONTRIBUTOR_IR: &str = "ab6466656573a1694576616c506172616d6a457870656374466565736a7265666572656e63657381a1694576616c506172616da16b45787065637456616c7565826b73657474696e6773726566675574786f52656666696e7075747382a3646e616d656b61646d696e5f696e707574657574786f73a1694576616c506172616da16b457870656374496e707574826b61646d696e5f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf46872656465656d6572644e6f6e65a3646e616d656763757272656e74657574786f73a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7203666669656c647380676f75747075747382a46761646472657373a1694576616c506172616da16b45787065637456616c7565826a6d61696e7461696e6572674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c756582716d696e74696e675f706f6c6963795f69646542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c75658269626f756e74795f696465427974657366616d6f756e74a1664e756d62657201686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826b61646d696e5f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf4a1694576616c506172616d6a45787065637446656573686f7074696f6e616cf46876616c6964697479a26573696e6365a1694576616c506172616da16b45787065637456616c7565826573696e636563496e7465756e74696ca1694576616c506172616da16b45787065637456616c75658265756e74696c63496e74656d696e747380656275726e7381a266616d6f756e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c756582716d696e74696e675f706f6c6963795f69646542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c75658269626f756e74795f696465427974657366616d6f756e74a1664e756d626572016872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380656164686f63806a636f6c6c61746572616c81a1657574786f73a1694576616c506172616da16b457870656374496e707574826a636f6c6c61746572616ca56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1664e756d6265721a004c4b4063726566644e6f6e65646d616e79f46a636f6c6c61746572616cf5677369676e657273a1677369676e65727381a1694576616c506172616da16b45787065637456616c7565826561646d696e6741646472657373686d6574616461746180";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseBeforeContributorParams {
+    pub admin: String,
+    pub bounty_id: String,
+    pub bountyref: String,
+    pub maintainer: String,
+    pub minting_policy_id: String,
+    pub settingsref: String,
+    pub since: String,
+    pub until: String,
+}
+impl CloseBeforeContributorParams {
+    fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut map = serde_json::Map::new();
+
+        map.insert("admin".to_string(), serde_json::json!(&self.admin));
+        map.insert("bounty_id".to_string(), serde_json::json!(&self.bounty_id));
+        map.insert("bountyref".to_string(), serde_json::json!(&self.bountyref));
+        map.insert("maintainer".to_string(), serde_json::json!(&self.maintainer));
+        map.insert("minting_policy_id".to_string(), serde_json::json!(&self.minting_policy_id));
+        map.insert("settingsref".to_string(), serde_json::json!(&self.settingsref));
+        map.insert("since".to_string(), serde_json::json!(&self.since));
+        map.insert("until".to_string(), serde_json::json!(&self.until));
+
+        map.into()
+    }
+}
+
+pub const CLOSE_AFTER_CONTRIBUTOR_IR: &str = "ab6466656573a1694576616c506172616d6a457870656374466565736a7265666572656e63657381a1694576616c506172616da16b45787065637456616c7565826b73657474696e6773726566675574786f52656666696e7075747382a3646e616d656b61646d696e5f696e707574657574786f73a1694576616c506172616da16b457870656374496e707574826b61646d696e5f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657202a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf46872656465656d6572644e6f6e65a3646e616d656763757272656e74657574786f73a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7203666669656c647380676f75747075747383a46761646472657373a1694576616c506172616da16b45787065637456616c7565826a6d61696e7461696e6572674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c756582716d696e74696e675f706f6c6963795f69646542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c75658269626f756e74795f696465427974657366616d6f756e74a1664e756d62657201a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c75658273636f6e7472696275746f725f6465706f73697463496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826b636f6e7472696275746f72674164647265737365646174756d644e6f6e6566616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c75658273636f6e7472696275746f725f6465706f73697463496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826b61646d696e5f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657202a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf4a1694576616c506172616d6a45787065637446656573686f7074696f6e616cf46876616c6964697479a26573696e6365a1694576616c506172616da16b45787065637456616c7565826573696e636563496e7465756e74696ca1694576616c506172616da16b45787065637456616c75658265756e74696c63496e74656d696e747380656275726e7381a266616d6f756e74a16641737365747381a366706f6c696379a1694576616c506172616da16b45787065637456616c756582716d696e74696e675f706f6c6963795f69646542797465736a61737365745f6e616d65a1694576616c506172616da16b45787065637456616c75658269626f756e74795f696465427974657366616d6f756e74a1664e756d626572016872656465656d6572a166537472756374a26b636f6e7374727563746f7200666669656c647380656164686f63806a636f6c6c61746572616c81a1657574786f73a1694576616c506172616da16b457870656374496e707574826a636f6c6c61746572616ca56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1664e756d6265721a004c4b4063726566644e6f6e65646d616e79f46a636f6c6c61746572616cf5677369676e657273a1677369676e65727381a1694576616c506172616da16b45787065637456616c7565826561646d696e6741646472657373686d6574616461746180";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseAfterContributorParams {
+    pub admin: String,
+    pub bounty_id: String,
+    pub bountyref: String,
+    pub contributor: String,
+    pub contributor_deposit: String,
+    pub maintainer: String,
+    pub minting_policy_id: String,
+    pub settingsref: String,
+    pub since: String,
+    pub until: String,
+}
+impl CloseAfterContributorParams {
+    fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut map = serde_json::Map::new();
+
+        map.insert("admin".to_string(), serde_json::json!(&self.admin));
+        map.insert("bounty_id".to_string(), serde_json::json!(&self.bounty_id));
+        map.insert("bountyref".to_string(), serde_json::json!(&self.bountyref));
+        map.insert("contributor".to_string(), serde_json::json!(&self.contributor));
+        map.insert("contributor_deposit".to_string(), serde_json::json!(&self.contributor_deposit));
+        map.insert("maintainer".to_string(), serde_json::json!(&self.maintainer));
+        map.insert("minting_policy_id".to_string(), serde_json::json!(&self.minting_policy_id));
+        map.insert("settingsref".to_string(), serde_json::json!(&self.settingsref));
+        map.insert("since".to_string(), serde_json::json!(&self.since));
+        map.insert("until".to_string(), serde_json::json!(&self.until));
+
+        map.into()
+    }
+}
+
+pub const ASSIGN_IR: &str = "ab6466656573a1694576616c506172616d6a457870656374466565736a7265666572656e63657381a1694576616c506172616da16b45787065637456616c7565826b73657474696e6773726566675574786f52656666696e7075747382a3646e616d6571636f6e7472696275746f725f696e707574657574786f73a1694576616c506172616da16b457870656374496e7075748271636f6e7472696275746f725f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826b636f6e7472696275746f7267416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c756582676d696e5f61646163496e74a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf46872656465656d6572644e6f6e65a3646e616d656763757272656e74657574786f73a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7201666669656c647380676f75747075747382a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a166537472756374a26b636f6e7374727563746f7200666669656c647381a166537472756374a26b636f6e7374727563746f7200666669656c647382a166537472756374a26b636f6e7374727563746f7200666669656c647381a1694576616c506172616da16b45787065637456616c75658277636f6e7472696275746f725f7061796d656e745f6b6579654279746573a166537472756374a26b636f6e7374727563746f7200666669656c647381a166537472756374a26b636f6e7374727563746f7200666669656c647381a166537472756374a26b636f6e7374727563746f7200666669656c647381a1694576616c506172616da16b45787065637456616c75658275636f6e7472696275746f725f7374616b655f6b6579654279746573a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657205a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16341646482a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c756582676d696e5f61646163496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826b636f6e7472696275746f72674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e7075748271636f6e7472696275746f725f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826b636f6e7472696275746f7267416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c756582676d696e5f61646163496e74a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf4a1694576616c506172616d6a45787065637446656573a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c756582676d696e5f61646163496e74686f7074696f6e616cf46876616c6964697479a26573696e6365a1694576616c506172616da16b45787065637456616c7565826573696e636563496e7465756e74696ca1694576616c506172616da16b45787065637456616c75658265756e74696c63496e74656d696e747380656275726e7380656164686f63806a636f6c6c61746572616c81a1657574786f73a1694576616c506172616da16b457870656374496e707574826a636f6c6c61746572616ca56761646472657373a1694576616c506172616da16b45787065637456616c7565826b636f6e7472696275746f7267416464726573736a6d696e5f616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1664e756d6265721a004c4b4063726566644e6f6e65646d616e79f46a636f6c6c61746572616cf5677369676e657273f6686d6574616461746180";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignParams {
+    pub bountyref: String,
+    pub contributor: String,
+    pub contributor_payment_key: String,
+    pub contributor_stake_key: String,
+    pub min_ada: String,
+    pub script: String,
+    pub settingsref: String,
+    pub since: String,
+    pub until: String,
+}
+impl AssignParams {
+    fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut map = serde_json::Map::new();
+
+        map.insert("bountyref".to_string(), serde_json::json!(&self.bountyref));
+        map.insert("contributor".to_string(), serde_json::json!(&self.contributor));
+        map.insert("contributor_payment_key".to_string(), serde_json::json!(&self.contributor_payment_key));
+        map.insert("contributor_stake_key".to_string(), serde_json::json!(&self.contributor_stake_key));
+        map.insert("min_ada".to_string(), serde_json::json!(&self.min_ada));
+        map.insert("script".to_string(), serde_json::json!(&self.script));
+        map.insert("settingsref".to_string(), serde_json::json!(&self.settingsref));
+        map.insert("since".to_string(), serde_json::json!(&self.since));
+        map.insert("until".to_string(), serde_json::json!(&self.until));
+
+        map.into()
+    }
+}
+
+pub const MERGE_IR: &str = "ab6466656573a1694576616c506172616d6a457870656374466565736a7265666572656e63657381a1694576616c506172616da16b45787065637456616c7565826b73657474696e6773726566675574786f52656666696e7075747382a3646e616d656b61646d696e5f696e707574657574786f73a1694576616c506172616da16b457870656374496e707574826b61646d696e5f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657203a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf46872656465656d6572644e6f6e65a3646e616d656763757272656e74657574786f73a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf46872656465656d6572a166537472756374a26b636f6e7374727563746f7202666669656c647380676f75747075747384a46761646472657373a1694576616c506172616da16b45787065637456616c75658266736372697074674164647265737365646174756da166537472756374a26b636f6e7374727563746f7200666669656c647387a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657200a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657201a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657202a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657203a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d62657204a164426f6f6cf5a16b4576616c4275696c74496ea16850726f706572747982a16a4576616c436f65726365a169496e746f446174756da1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a1664e756d6265720666616d6f756e74a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826763757272656e74a56761646472657373644e6f6e656a6d696e5f616d6f756e74644e6f6e6563726566a1694576616c506172616da16b45787065637456616c75658269626f756e7479726566675574786f526566646d616e79f46a636f6c6c61746572616cf4a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16341646482a1694576616c506172616da16b45787065637456616c7565826c676974686f6e65795f66656563496e74a1694576616c506172616da16b45787065637456616c756582676d696e5f61646163496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826c676974686f6e657961646472674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16341646482a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c7565826c676974686f6e65795f66656563496e74a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826a6d61696e7461696e6572674164647265737365646174756d644e6f6e6566616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1694576616c506172616da16b45787065637456616c756582676d696e5f61646163496e74686f7074696f6e616cf4a46761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e674164647265737365646174756d644e6f6e6566616d6f756e74a16b4576616c4275696c74496ea16353756282a16b4576616c4275696c74496ea16353756282a16a4576616c436f65726365a16a496e746f417373657473a1694576616c506172616da16b457870656374496e707574826b61646d696e5f696e707574a56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16b4576616c4275696c74496ea16341646482a16b4576616c4275696c74496ea16341646482a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657203a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201a1694576616c506172616d6a4578706563744665657363726566644e6f6e65646d616e79f46a636f6c6c61746572616cf4a1694576616c506172616d6a45787065637446656573a16c4576616c436f6d70696c6572a16e436f6d707574654d696e5574786fa1664e756d62657201686f7074696f6e616cf46876616c6964697479a26573696e6365a1694576616c506172616da16b45787065637456616c7565826573696e636563496e7465756e74696ca1694576616c506172616da16b45787065637456616c75658265756e74696c63496e74656d696e747380656275726e7380656164686f63806a636f6c6c61746572616c81a1657574786f73a1694576616c506172616da16b457870656374496e707574826a636f6c6c61746572616ca56761646472657373a1694576616c506172616da16b45787065637456616c7565826561646d696e67416464726573736a6d696e5f616d6f756e74a16641737365747381a366706f6c696379644e6f6e656a61737365745f6e616d65644e6f6e6566616d6f756e74a1664e756d6265721a004c4b4063726566644e6f6e65646d616e79f46a636f6c6c61746572616cf5677369676e657273a1677369676e65727381a1694576616c506172616da16b45787065637456616c7565826561646d696e6741646472657373686d6574616461746180";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeParams {
+    pub admin: String,
+    pub bountyref: String,
+    pub githoney_fee: String,
+    pub githoneyaddr: String,
+    pub maintainer: String,
+    pub min_ada: String,
+    pub script: String,
+    pub settingsref: String,
+    pub since: String,
+    pub until: String,
+}
+impl MergeParams {
+    fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut map = serde_json::Map::new();
+
+        map.insert("admin".to_string(), serde_json::json!(&self.admin));
+        map.insert("bountyref".to_string(), serde_json::json!(&self.bountyref));
+        map.insert("githoney_fee".to_string(), serde_json::json!(&self.githoney_fee));
+        map.insert("githoneyaddr".to_string(), serde_json::json!(&self.githoneyaddr));
+        map.insert("maintainer".to_string(), serde_json::json!(&self.maintainer));
+        map.insert("min_ada".to_string(), serde_json::json!(&self.min_ada));
+        map.insert("script".to_string(), serde_json::json!(&self.script));
+        map.insert("settingsref".to_string(), serde_json::json!(&self.settingsref));
+        map.insert("since".to_string(), serde_json::json!(&self.since));
+        map.insert("until".to_string(), serde_json::json!(&self.until));
+
+        map.into()
+    }
+}
+
 pub struct Client {
     client: tx3_sdk::trp::Client,
 }
@@ -192,6 +410,84 @@ impl Client {
         }).await
     }
 
+    pub async fn add2_tx(&self, args: Add2Params) -> Result<TxEnvelope, tx3_sdk::trp::Error> {
+        let tir_info = TirEnvelope {
+            content: ADD2_IR.to_string(),
+            encoding: BytesEncoding::Hex,
+            version: "v1beta0".to_string(),
+        };
+
+        self.client.resolve(ResolveParams {
+            tir: tir_info,
+            args: args.to_map(),
+        }).await
+    }
+
+    pub async fn add3_tx(&self, args: Add3Params) -> Result<TxEnvelope, tx3_sdk::trp::Error> {
+        let tir_info = TirEnvelope {
+            content: ADD3_IR.to_string(),
+            encoding: BytesEncoding::Hex,
+            version: "v1beta0".to_string(),
+        };
+
+        self.client.resolve(ResolveParams {
+            tir: tir_info,
+            args: args.to_map(),
+        }).await
+    }
+
+    pub async fn close_before_contributor_tx(&self, args: CloseBeforeContributorParams) -> Result<TxEnvelope, tx3_sdk::trp::Error> {
+        let tir_info = TirEnvelope {
+            content: CLOSE_BEFORE_CONTRIBUTOR_IR.to_string(),
+            encoding: BytesEncoding::Hex,
+            version: "v1beta0".to_string(),
+        };
+
+        self.client.resolve(ResolveParams {
+            tir: tir_info,
+            args: args.to_map(),
+        }).await
+    }
+
+    pub async fn close_after_contributor_tx(&self, args: CloseAfterContributorParams) -> Result<TxEnvelope, tx3_sdk::trp::Error> {
+        let tir_info = TirEnvelope {
+            content: CLOSE_AFTER_CONTRIBUTOR_IR.to_string(),
+            encoding: BytesEncoding::Hex,
+            version: "v1beta0".to_string(),
+        };
+
+        self.client.resolve(ResolveParams {
+            tir: tir_info,
+            args: args.to_map(),
+        }).await
+    }
+
+    pub async fn assign_tx(&self, args: AssignParams) -> Result<TxEnvelope, tx3_sdk::trp::Error> {
+        let tir_info = TirEnvelope {
+            content: ASSIGN_IR.to_string(),
+            encoding: BytesEncoding::Hex,
+            version: "v1beta0".to_string(),
+        };
+
+        self.client.resolve(ResolveParams {
+            tir: tir_info,
+            args: args.to_map(),
+        }).await
+    }
+
+    pub async fn merge_tx(&self, args: MergeParams) -> Result<TxEnvelope, tx3_sdk::trp::Error> {
+        let tir_info = TirEnvelope {
+            content: MERGE_IR.to_string(),
+            encoding: BytesEncoding::Hex,
+            version: "v1beta0".to_string(),
+        };
+
+        self.client.resolve(ResolveParams {
+            tir: tir_info,
+            args: args.to_map(),
+        }).await
+    }
+
     pub async fn submit(&self, params: SubmitParams) -> Result<SubmitResponse, tx3_sdk::trp::Error> {
         self.client.submit(params).await
     }
@@ -0,0 +1,453 @@
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::time::now_posix_ms;
+
+// Request bodies are buffered to verify worker signatures
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+// What a caller may do: build bounty transactions or run admin operations
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Bounty,
+    Admin,
+}
+
+impl Scope {
+    fn parse(value: &str) -> Option<Scope> {
+        match value {
+            "bounty" => Some(Scope::Bounty),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+fn all_scopes() -> Vec<Scope> {
+    vec![Scope::Bounty, Scope::Admin]
+}
+
+fn default_max_skew_secs() -> u64 {
+    600
+}
+
+#[derive(Deserialize)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<Scope>,
+    // Route paths, e.g. `/create-bounty`, the key is limited to within its
+    // scopes; every route of its scopes when unset
+    #[serde(default)]
+    pub routes: Option<Vec<String>>,
+}
+
+// Shared secret with the Balius worker (`tx_builder_secret` in its config)
+#[derive(Deserialize)]
+pub struct WorkerAuthConfig {
+    pub secret: String,
+    #[serde(default = "all_scopes")]
+    pub scopes: Vec<Scope>,
+    #[serde(default)]
+    pub routes: Option<Vec<String>>,
+    // The worker timestamps requests with the chain tip, which may lag behind
+    #[serde(default = "default_max_skew_secs")]
+    pub max_skew_secs: u64,
+}
+
+// HS256 tokens whose `scope` claim lists space-separated scopes
+#[derive(Deserialize)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    pub worker: Option<WorkerAuthConfig>,
+    pub jwt: Option<JwtConfig>,
+}
+
+// The authenticated caller, available to handlers as a request extension
+#[derive(Clone, Debug)]
+pub struct Principal {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    // Route paths the caller is limited to, None for every route of its scopes
+    pub routes: Option<Vec<String>>,
+}
+
+impl Principal {
+    // Whether the caller may use `route`, which requires `scope`
+    fn allows(&self, scope: Scope, route: &str) -> Result<(), String> {
+        if !self.scopes.contains(&scope) {
+            return Err(format!("{} is missing the {:?} scope", self.name, scope));
+        }
+        if self
+            .routes
+            .as_ref()
+            .is_some_and(|routes| !routes.iter().any(|allowed| allowed == route))
+        {
+            return Err(format!("{} may not use {}", self.name, route));
+        }
+        Ok(())
+    }
+}
+
+tokio::task_local! {
+    static CALLER: String;
+}
+
+// Name of the caller of the request being handled. Empty when authentication
+// is disabled, so every unauthenticated caller is the same one.
+pub fn caller() -> String {
+    CALLER.try_with(String::clone).unwrap_or_default()
+}
+
+pub trait Authenticator: Send + Sync {
+    // None when the request carries no credentials for this method
+    fn authenticate(&self, headers: &HeaderMap, body: &[u8]) -> Option<Result<Principal, String>>;
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+///// API KEYS /////
+// `X-Api-Key: <key>`; keys are compared by digest so the comparison time does not leak them
+pub struct ApiKeyAuth {
+    keys: Vec<([u8; 32], Principal)>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(keys: &[ApiKeyConfig]) -> ApiKeyAuth {
+        ApiKeyAuth {
+            keys: keys
+                .iter()
+                .map(|key| {
+                    let principal = Principal {
+                        name: key.name.clone(),
+                        scopes: key.scopes.clone(),
+                        routes: key.routes.clone(),
+                    };
+                    (Sha256::digest(key.key.as_bytes()).into(), principal)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Authenticator for ApiKeyAuth {
+    fn authenticate(&self, headers: &HeaderMap, _body: &[u8]) -> Option<Result<Principal, String>> {
+        let key = header(headers, "X-Api-Key")?;
+        let digest: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+
+        Some(
+            self.keys
+                .iter()
+                .find(|(known, _)| *known == digest)
+                .map(|(_, principal)| principal.clone())
+                .ok_or_else(|| "Unknown API key".to_string()),
+        )
+    }
+}
+
+///// WORKER HMAC /////
+// `X-Worker-Timestamp: <unix secs>`, `X-Worker-Nonce: <nonce>` and
+// `X-Worker-Signature: <hex HMAC-SHA256 of "timestamp.nonce.body">`. A nonce
+// is accepted once, so a captured request cannot be replayed within the window.
+pub struct WorkerHmacAuth {
+    secret: String,
+    scopes: Vec<Scope>,
+    routes: Option<Vec<String>>,
+    max_skew_secs: u64,
+    // Nonces of accepted requests, with the unix time their timestamp expires
+    seen_nonces: Mutex<HashMap<String, u64>>,
+}
+
+impl WorkerHmacAuth {
+    pub fn new(config: &WorkerAuthConfig) -> WorkerHmacAuth {
+        WorkerHmacAuth {
+            secret: config.secret.clone(),
+            scopes: config.scopes.clone(),
+            routes: config.routes.clone(),
+            max_skew_secs: config.max_skew_secs,
+            seen_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn verify(&self, headers: &HeaderMap, body: &[u8], now_secs: u64) -> Result<Principal, &'static str> {
+        let signature = header(headers, "X-Worker-Signature").ok_or("Missing X-Worker-Signature")?;
+        let timestamp = header(headers, "X-Worker-Timestamp").ok_or("Missing X-Worker-Timestamp")?;
+        let nonce = header(headers, "X-Worker-Nonce").ok_or("Missing X-Worker-Nonce")?;
+        let signed_at: u64 = timestamp
+            .parse()
+            .map_err(|_| "Invalid X-Worker-Timestamp")?;
+        if now_secs.abs_diff(signed_at) > self.max_skew_secs {
+            return Err("Worker signature timestamp is outside the allowed window");
+        }
+
+        let signature = hex::decode(signature).map_err(|_| "Invalid X-Worker-Signature")?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).unwrap();
+        mac.update(format!("{}.{}.", timestamp, nonce).as_bytes());
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| "Invalid worker signature")?;
+
+        let mut seen = self.seen_nonces.lock().unwrap();
+        seen.retain(|_, expires| *expires >= now_secs);
+        if seen
+            .insert(nonce.to_string(), signed_at.saturating_add(self.max_skew_secs))
+            .is_some()
+        {
+            return Err("Worker request nonce was already used");
+        }
+
+        Ok(Principal {
+            name: "worker".to_string(),
+            scopes: self.scopes.clone(),
+            routes: self.routes.clone(),
+        })
+    }
+}
+
+impl Authenticator for WorkerHmacAuth {
+    fn authenticate(&self, headers: &HeaderMap, body: &[u8]) -> Option<Result<Principal, String>> {
+        header(headers, "X-Worker-Signature")?;
+        Some(self.verify(headers, body, now_posix_ms() / 1000).map_err(str::to_string))
+    }
+}
+
+///// JWT /////
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    scope: String,
+}
+
+// `Authorization: Bearer <jwt>`
+pub struct JwtAuth {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtAuth {
+    pub fn new(config: &JwtConfig) -> JwtAuth {
+        let mut validation = Validation::new(Algorithm::HS256);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        JwtAuth {
+            key: DecodingKey::from_secret(config.secret.as_bytes()),
+            validation,
+        }
+    }
+}
+
+impl Authenticator for JwtAuth {
+    fn authenticate(&self, headers: &HeaderMap, _body: &[u8]) -> Option<Result<Principal, String>> {
+        let token = header(headers, "Authorization")?.strip_prefix("Bearer ")?;
+
+        Some(
+            decode::<Claims>(token, &self.key, &self.validation)
+                .map(|data| Principal {
+                    name: data.claims.sub,
+                    scopes: data.claims.scope.split_whitespace().filter_map(Scope::parse).collect(),
+                    routes: None,
+                })
+                .map_err(|e| format!("Invalid token: {}", e)),
+        )
+    }
+}
+
+type Authenticators = Vec<Box<dyn Authenticator>>;
+
+// Authenticators from `AUTH_CONFIG`. Leaving the server open takes an explicit
+// `AUTH_DISABLED=true`, for local development; None is returned then.
+fn load_authenticators() -> Result<Option<Authenticators>, String> {
+    let Ok(path) = std::env::var("AUTH_CONFIG") else {
+        if std::env::var("AUTH_DISABLED").as_deref() == Ok("true") {
+            tracing::warn!("AUTH_DISABLED is set, requests are not authenticated");
+            return Ok(None);
+        }
+        return Err("AUTH_CONFIG is not set; set AUTH_DISABLED=true to run without authentication".to_string());
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read AUTH_CONFIG {}: {}", path, e))?;
+    let config: AuthConfig = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid AUTH_CONFIG {}: {}", path, e))?;
+
+    let mut authenticators: Authenticators = Vec::new();
+    if !config.api_keys.is_empty() {
+        authenticators.push(Box::new(ApiKeyAuth::new(&config.api_keys)));
+    }
+    if let Some(worker) = &config.worker {
+        authenticators.push(Box::new(WorkerHmacAuth::new(worker)));
+    }
+    if let Some(jwt) = &config.jwt {
+        authenticators.push(Box::new(JwtAuth::new(jwt)));
+    }
+    if authenticators.is_empty() {
+        return Err(format!("AUTH_CONFIG {} configures no credentials", path));
+    }
+    Ok(Some(authenticators))
+}
+
+static AUTHENTICATORS: Lazy<Result<Option<Authenticators>, String>> =
+    Lazy::new(load_authenticators);
+
+// Checked once at startup, so a missing or broken AUTH_CONFIG stops the server
+pub fn check_config() -> Result<(), String> {
+    AUTHENTICATORS.as_ref().map(|_| ()).map_err(String::clone)
+}
+
+fn reject(status: StatusCode, reason: String) -> Response {
+    tracing::warn!(%status, %reason, "Rejected request");
+    (status, Json(Err::<(), String>(reason))).into_response()
+}
+
+// Route layer admitting only callers holding `scope` and allowed the route
+pub async fn require(State(scope): State<Scope>, req: Request, next: Next) -> Response {
+    let authenticators = match AUTHENTICATORS.as_ref() {
+        Ok(Some(authenticators)) => authenticators,
+        Ok(None) => return next.run(req).await,
+        Err(e) => return reject(StatusCode::INTERNAL_SERVER_ERROR, e.clone()),
+    };
+
+    let (mut parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, format!("Invalid body: {}", e)),
+    };
+
+    let principal = match authenticators
+        .iter()
+        .find_map(|authenticator| authenticator.authenticate(&parts.headers, &body))
+    {
+        Some(Ok(principal)) => principal,
+        Some(Err(reason)) => return reject(StatusCode::UNAUTHORIZED, reason),
+        None => return reject(StatusCode::UNAUTHORIZED, "Missing credentials".to_string()),
+    };
+
+    let route = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    if let Err(reason) = principal.allows(scope, &route) {
+        return reject(StatusCode::FORBIDDEN, reason);
+    }
+
+    tracing::info!(principal = %principal.name, "Authenticated request");
+    let caller = principal.name.clone();
+    parts.extensions.insert(principal);
+    CALLER
+        .scope(caller, next.run(Request::from_parts(parts, Body::from(body))))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn worker_auth() -> WorkerHmacAuth {
+        WorkerHmacAuth::new(&WorkerAuthConfig {
+            secret: "secret".to_string(),
+            scopes: all_scopes(),
+            routes: None,
+            max_skew_secs: 600,
+        })
+    }
+
+    fn signed(timestamp: u64, nonce: &str, body: &[u8]) -> HeaderMap {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(format!("{}.{}.", timestamp, nonce).as_bytes());
+        mac.update(body);
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Worker-Timestamp", HeaderValue::from_str(&timestamp.to_string()).unwrap());
+        headers.insert("X-Worker-Nonce", HeaderValue::from_str(nonce).unwrap());
+        headers.insert("X-Worker-Signature", HeaderValue::from_str(&signature).unwrap());
+        headers
+    }
+
+    #[test]
+    fn accepts_each_worker_nonce_once() {
+        let auth = worker_auth();
+        let body = br#"{"bounty_id":"01"}"#;
+
+        assert!(auth.verify(&signed(NOW, "1", body), body, NOW).is_ok());
+        assert_eq!(
+            auth.verify(&signed(NOW, "1", body), body, NOW + 1).unwrap_err(),
+            "Worker request nonce was already used"
+        );
+        assert!(auth.verify(&signed(NOW, "2", body), body, NOW + 1).is_ok());
+
+        // A nonce is forgotten once its timestamp is too old to be accepted anyway
+        assert!(auth.verify(&signed(NOW, "1", body), body, NOW + 601).is_err());
+        assert!(auth.verify(&signed(NOW + 601, "1", body), body, NOW + 601).is_ok());
+    }
+
+    #[test]
+    fn rejects_forged_worker_requests() {
+        let auth = worker_auth();
+        let body = br#"{"bounty_id":"01"}"#;
+
+        let mut renonced = signed(NOW, "1", body);
+        renonced.insert("X-Worker-Nonce", HeaderValue::from_static("2"));
+        assert!(auth.verify(&renonced, body, NOW).is_err());
+        assert!(auth.verify(&signed(NOW, "3", body), b"{}", NOW).is_err());
+        assert!(auth.verify(&signed(NOW - 601, "4", body), body, NOW).is_err());
+
+        let mut unsigned_nonce = signed(NOW, "5", body);
+        unsigned_nonce.remove("X-Worker-Nonce");
+        assert!(auth.verify(&unsigned_nonce, body, NOW).is_err());
+    }
+
+    #[test]
+    fn api_keys_are_limited_to_their_routes() {
+        let principal = |routes: Option<Vec<String>>| Principal {
+            name: "dashboard".to_string(),
+            scopes: vec![Scope::Bounty],
+            routes,
+        };
+
+        let any_route = principal(None);
+        assert!(any_route.allows(Scope::Bounty, "/submit").is_ok());
+        assert!(any_route.allows(Scope::Admin, "/close-bounty").is_err());
+
+        let limited = principal(Some(vec!["/preview".to_string()]));
+        assert!(limited.allows(Scope::Bounty, "/preview").is_ok());
+        assert_eq!(
+            limited.allows(Scope::Bounty, "/submit").unwrap_err(),
+            "dashboard may not use /submit"
+        );
+    }
+}
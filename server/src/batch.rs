@@ -0,0 +1,477 @@
+use axum::Json;
+use pallas_addresses::Address;
+use pallas_traverse::MultiEraTx;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{ArrayValidation, InstanceType, Schema, SchemaObject, SubschemaValidation};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tx3_sdk::trp::TxEnvelope;
+
+use githoney_common::time::TimeValue;
+use githoney_common::types::{Address as Bech32Address, AssetName, HexString, UtxoRef};
+
+use crate::params::{add2_params, add3_params, AddParams, CreateWithLovelaceParams};
+
+use crate::coin_control::CoinControl;
+use crate::metrics::{self, BuildOutcome};
+use crate::resolve::ogmios_ref;
+use crate::routes::{parse_build_params, parse_timed_params, record_evaluated, reject_built};
+use crate::{evaluate_tx, pending, resolve, tx_check};
+
+// Each bounty is resolved and evaluated in turn, so keep batches short
+const MAX_BATCH_SIZE: usize = 20;
+// Largest multi-bounty add in `main.tx3` (`add3`)
+const MAX_ADD_BATCH_SIZE: usize = 3;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateBountiesRequest {
+    // Same bodies as `/create-bounty`, built in this order
+    #[schemars(schema_with = "create_bounty_bodies", length(min = 1, max = "MAX_BATCH_SIZE"))]
+    pub bounties: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+// The bodies are parsed one by one like `/create-bounty`, so their schema is
+// that route's: the bounty params plus optional coin control
+fn create_bounty_bodies(gen: &mut SchemaGenerator) -> Schema {
+    let body = SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            all_of: Some(vec![
+                gen.subschema_for::<CreateWithLovelaceParams>(),
+                gen.subschema_for::<CoinControl>(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    Schema::Object(SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(Schema::Object(body).into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    })
+}
+
+// Top-ups of several bounties from one sponsor, built as a single `add2` or
+// `add3` transaction
+#[derive(Deserialize, JsonSchema)]
+pub struct AddFundsBatchRequest {
+    pub sponsor: Bech32Address,
+    pub script: Bech32Address,
+    pub settingsref: UtxoRef,
+    pub since: Option<TimeValue>,
+    pub until: Option<TimeValue>,
+    #[serde(flatten)]
+    pub coin_control: CoinControl,
+    #[schemars(length(min = 1, max = "MAX_ADD_BATCH_SIZE"))]
+    pub bounties: Vec<BatchTopUp>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct BatchTopUp {
+    pub bountyref: UtxoRef,
+    pub rewardamount: u64,
+    pub rewardpolicyid: HexString,
+    pub rewardassetname: AssetName,
+}
+
+impl AddFundsBatchRequest {
+    // The `/add-funds` params of each entry, in order
+    fn top_ups(&self) -> Result<Vec<AddParams>, String> {
+        self.coin_control.validate()?;
+        let mut requests: Vec<AddParams> = Vec::with_capacity(self.bounties.len());
+        for (position, top_up) in self.bounties.iter().enumerate() {
+            let with_position = |e: String| format!("bounties[{}]: {}", position, e);
+            if let Some(earlier) = requests
+                .iter()
+                .position(|request| request.bountyref == top_up.bountyref)
+            {
+                return Err(with_position(format!(
+                    "Invalid field `bountyref`: {} is already topped up by bounties[{}]",
+                    top_up.bountyref, earlier
+                )));
+            }
+
+            let body = json!({
+                "sponsor": self.sponsor,
+                "script": self.script,
+                "settingsref": self.settingsref,
+                "since": self.since,
+                "until": self.until,
+                "bountyref": top_up.bountyref,
+                "rewardamount": top_up.rewardamount,
+                "rewardpolicyid": top_up.rewardpolicyid,
+                "rewardassetname": top_up.rewardassetname,
+            });
+            let serde_json::Value::Object(body) = body else {
+                unreachable!()
+            };
+            requests.push(parse_timed_params(body, None).map_err(with_position)?);
+        }
+        Ok(requests)
+    }
+}
+
+// Inputs a built transaction spends and the change it returns to the paying
+// wallet, as an Ogmios UTxO
+struct ChainLink {
+    inputs: Vec<String>,
+    change: Option<Value>,
+}
+
+fn chain_link(tx: &TxEnvelope, wallet: &Address) -> Result<ChainLink, String> {
+    let bytes = hex::decode(&tx.tx).map_err(|e| format!("transaction is not valid hex: {}", e))?;
+    let decoded =
+        MultiEraTx::decode(&bytes).map_err(|e| format!("transaction does not decode: {}", e))?;
+
+    let inputs = decoded
+        .inputs()
+        .iter()
+        .map(|input| format!("{}#{}", input.hash(), input.index()))
+        .collect();
+    let change = decoded
+        .outputs()
+        .iter()
+        .enumerate()
+        .find(|(_, output)| {
+            output
+                .address()
+                .map(|address| address.to_vec() == wallet.to_vec())
+                .unwrap_or(false)
+        })
+        .map(|(index, output)| pending::ogmios_utxo(&tx.hash, index, output));
+
+    Ok(ChainLink { inputs, change })
+}
+
+// Tracks what earlier transactions of the batch spend and the change they
+// leave, so each one spends the previous one's change and can be signed and
+// submitted after it without double-spending
+#[derive(Default)]
+struct UtxoChain {
+    // Input -> position of the transaction spending it
+    spent: HashMap<String, usize>,
+    change: Option<Value>,
+}
+
+impl UtxoChain {
+    // Makes the previous transaction's change an input of the next one. It is
+    // not on chain yet, so it also goes to TRP and the evaluation as an
+    // unconfirmed UTxO (see `unconfirmed`).
+    fn pin_change(&self, mut coin_control: CoinControl) -> Result<CoinControl, String> {
+        if let Some(change) = &self.change {
+            let change: UtxoRef = ogmios_ref(change).parse()?;
+            if !coin_control.inputs.contains(&change) {
+                coin_control.inputs.insert(0, change);
+            }
+        }
+        Ok(coin_control)
+    }
+
+    fn unconfirmed(&self) -> Vec<Value> {
+        self.change.iter().cloned().collect()
+    }
+
+    fn append(&mut self, position: usize, link: ChainLink) -> Result<(), String> {
+        if let Some((input, earlier)) = link
+            .inputs
+            .iter()
+            .find_map(|input| self.spent.get(input).map(|earlier| (input, earlier)))
+        {
+            return Err(format!(
+                "bounties[{}] spends {}, already spent by bounties[{}]",
+                position, input, earlier
+            ));
+        }
+
+        let chained = self
+            .change
+            .as_ref()
+            .is_some_and(|change| link.inputs.contains(&ogmios_ref(change)));
+        tracing::info!(position, chained, "Added transaction to batch chain");
+
+        for input in link.inputs {
+            self.spent.insert(input, position);
+        }
+        self.change = link.change;
+        Ok(())
+    }
+}
+
+// Chains and evaluates a resolved transaction that went through `tx_check`
+async fn finish_one(
+    tx_name: &str,
+    position: usize,
+    tx: TxEnvelope,
+    checked: Result<(), String>,
+    wallet: &Address,
+    chain: &mut UtxoChain,
+) -> Result<TxEnvelope, String> {
+    tracing::info!(position, tx_hash = %tx.hash, cbor = %tx.tx, "Generated transaction");
+
+    let unconfirmed = chain.unconfirmed();
+    let chained = match checked {
+        Ok(()) => chain_link(&tx, wallet).and_then(|link| chain.append(position, link)),
+        Err(e) => Err(format!("Transaction check failed: {}", e)),
+    };
+    if let Err(e) = chained {
+        metrics::record_build(tx_name, BuildOutcome::CheckFailed);
+        return Err(format!("bounties[{}]: {}", position, e));
+    }
+
+    let evaluated = evaluate_tx::evaluate_tx(tx, &unconfirmed).await;
+    metrics::record_build(
+        tx_name,
+        if evaluated.is_ok() {
+            BuildOutcome::Ok
+        } else {
+            BuildOutcome::EvaluationFailed
+        },
+    );
+    evaluated.map_err(|e| format!("bounties[{}]: {}", position, e))
+}
+
+async fn build_create(
+    position: usize,
+    (req, coin_control): (CreateWithLovelaceParams, CoinControl),
+    maintainer: &Address,
+    chain: &mut UtxoChain,
+) -> Result<TxEnvelope, String> {
+    let coin_control = chain
+        .pin_change(coin_control)
+        .map_err(|e| format!("bounties[{}]: {}", position, e))?;
+    let params = protocol::CreateWithLovelaceParams::from(req.clone());
+    let tx = metrics::time_resolve(
+        "create_bounties",
+        resolve::resolve(
+            protocol::CREATE_WITH_LOVELACE_IR,
+            &params,
+            &coin_control,
+            &chain.unconfirmed(),
+        ),
+    )
+    .await
+    .map_err(|e| {
+        metrics::record_build("create_bounties", BuildOutcome::ResolveError);
+        format!("bounties[{}]: Error creating bounty: {}", position, e)
+    })?;
+
+    let checked = tx_check::check_create_bounty(&tx, &req)
+        .await
+        .and_then(|()| coin_control.check(&tx));
+    finish_one("create_bounties", position, tx, checked, maintainer, chain).await
+}
+
+// Resolves the `add` variant matching the number of top-ups
+async fn resolve_top_ups(
+    top_ups: &[AddParams],
+    coin_control: &CoinControl,
+) -> Result<TxEnvelope, String> {
+    match top_ups {
+        [top_up] => {
+            let params = protocol::AddParams::from(top_up.clone());
+            resolve::resolve(protocol::ADD_IR, &params, coin_control, &[]).await
+        }
+        [first, second] => {
+            let params = add2_params([first.clone(), second.clone()]);
+            resolve::resolve(protocol::ADD2_IR, &params, coin_control, &[]).await
+        }
+        [first, second, third] => {
+            let params = add3_params([first.clone(), second.clone(), third.clone()]);
+            resolve::resolve(protocol::ADD3_IR, &params, coin_control, &[]).await
+        }
+        _ => Err(format!(
+            "expected between 1 and {} top-ups, got {}",
+            MAX_ADD_BATCH_SIZE,
+            top_ups.len()
+        )),
+    }
+}
+
+// Builds one create bounty transaction per entry, in order, each spending
+// the maintainer's change from the previous one. The result is meant to be
+// signed and submitted in sequence; if any entry fails, nothing is returned.
+pub async fn create_bounties(
+    Json(body): Json<CreateBountiesRequest>,
+) -> Json<Result<Vec<TxEnvelope>, String>> {
+    if body.bounties.is_empty() || body.bounties.len() > MAX_BATCH_SIZE {
+        metrics::record_build("create_bounties", BuildOutcome::InvalidParams);
+        return Json(Err(format!(
+            "Invalid field `bounties`: expected between 1 and {} entries",
+            MAX_BATCH_SIZE
+        )));
+    }
+
+    let mut requests = Vec::with_capacity(body.bounties.len());
+    for (position, bounty) in body.bounties.into_iter().enumerate() {
+        match parse_build_params::<CreateWithLovelaceParams>(bounty, Some("time_limit")) {
+            Ok(req) => requests.push(req),
+            Err(e) => {
+                tracing::warn!(position, error = %e, "Rejected create bounties request");
+                metrics::record_build("create_bounties", BuildOutcome::InvalidParams);
+                return Json(Err(format!("bounties[{}]: {}", position, e)));
+            }
+        }
+    }
+
+    // The chain runs through a single wallet's change outputs
+    let maintainer = &requests[0].0.maintainer;
+    if let Some(position) = requests
+        .iter()
+        .position(|(req, _)| &req.maintainer != maintainer)
+    {
+        metrics::record_build("create_bounties", BuildOutcome::InvalidParams);
+        return Json(Err(format!(
+            "Invalid field `bounties[{}].maintainer`: every bounty in a batch must have the same maintainer",
+            position
+        )));
+    }
+    let maintainer = match Address::from_bech32(maintainer.as_str()) {
+        Ok(maintainer) => maintainer,
+        Err(e) => return Json(Err(format!("Invalid field `maintainer`: {}", e))),
+    };
+    tracing::info!(count = requests.len(), "Received create bounties request");
+
+    let mut chain = UtxoChain::default();
+    let mut envelopes = Vec::with_capacity(requests.len());
+    for (position, req) in requests.into_iter().enumerate() {
+        match build_create(position, req, &maintainer, &mut chain).await {
+            Ok(tx) => envelopes.push(tx),
+            Err(e) => {
+                tracing::error!(error = %e, "Error building bounty batch");
+                return Json(Err(e));
+            }
+        }
+    }
+
+    Json(Ok(envelopes))
+}
+
+// Tops up several bounties from one sponsor in a single transaction, paying
+// one fee. Shared fields (`sponsor`, `script`, `settingsref`, `since`,
+// `until`, coin control) sit at the top level and each entry of `bounties`
+// carries `bountyref`, `rewardamount`, `rewardpolicyid` and `rewardassetname`.
+pub async fn add_funds_batch(
+    Json(body): Json<AddFundsBatchRequest>,
+) -> Json<Result<TxEnvelope, String>> {
+    if body.bounties.is_empty() || body.bounties.len() > MAX_ADD_BATCH_SIZE {
+        metrics::record_build("add_funds_batch", BuildOutcome::InvalidParams);
+        return Json(Err(format!(
+            "Invalid field `bounties`: expected between 1 and {} entries",
+            MAX_ADD_BATCH_SIZE
+        )));
+    }
+
+    let top_ups = match body.top_ups() {
+        Ok(top_ups) => top_ups,
+        Err(e) => {
+            tracing::warn!(error = %e, "Rejected add funds batch request");
+            metrics::record_build("add_funds_batch", BuildOutcome::InvalidParams);
+            return Json(Err(e));
+        }
+    };
+    tracing::info!(count = top_ups.len(), "Received add funds batch request");
+
+    let resolved = resolve_top_ups(&top_ups, &body.coin_control);
+    match metrics::time_resolve("add_funds_batch", resolved).await {
+        Ok(tx) => {
+            tracing::info!(tx_hash = %tx.hash, cbor = %tx.tx, "Generated transaction");
+            let checked = tx_check::check_add_funds_batch(&tx, &top_ups).await;
+            if let Err(e) = checked.and_then(|()| body.coin_control.check(&tx)) {
+                return reject_built("add_funds_batch", &tx, e);
+            }
+            let evaluated_tx_or_err = evaluate_tx::evaluate_tx(tx, &[]).await;
+            record_evaluated("add_funds_batch", &evaluated_tx_or_err);
+            Json(evaluated_tx_or_err)
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, "Error adding funds batch");
+            metrics::record_build("add_funds_batch", BuildOutcome::ResolveError);
+            Json(Err(format!("Error adding funds: {}", e)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(tx_hash: &str, index: u64) -> Value {
+        json!({
+            "transaction": { "id": tx_hash },
+            "index": index,
+            "address": "addr_test1wr5nj776ygd7d7vwcw3f9fknj4axx0wzy67y3qu8dqtscys7w9q65",
+            "value": { "ada": { "lovelace": 5_000_000 } }
+        })
+    }
+
+    #[test]
+    fn next_transaction_spends_previous_change() {
+        let first = "aa".repeat(32);
+        let mut chain = UtxoChain::default();
+        assert!(chain.pin_change(CoinControl::default()).unwrap().inputs.is_empty());
+
+        let link = ChainLink {
+            inputs: vec![format!("{}#0", "11".repeat(32))],
+            change: Some(change(&first, 1)),
+        };
+        chain.append(0, link).unwrap();
+
+        let coin_control = chain.pin_change(CoinControl::default()).unwrap();
+        assert_eq!(coin_control.inputs, vec![format!("{}#1", first).parse().unwrap()]);
+        assert_eq!(chain.unconfirmed(), vec![change(&first, 1)]);
+
+        // Spending the first transaction's input again is a double spend
+        let link = ChainLink {
+            inputs: vec![format!("{}#0", "11".repeat(32))],
+            change: None,
+        };
+        assert!(chain.append(1, link).unwrap_err().contains("already spent by bounties[0]"));
+    }
+
+    fn top_up(index: u32) -> AddParams {
+        AddParams {
+            bountyref: format!("{}#{}", "aa".repeat(32), index).parse().unwrap(),
+            rewardamount: 1_000_000,
+            rewardassetname: "".parse().unwrap(),
+            rewardpolicyid: "".parse().unwrap(),
+            script: "addr_test1wr5nj776ygd7d7vwcw3f9fknj4axx0wzy67y3qu8dqtscys7w9q65".parse().unwrap(),
+            settingsref: format!("{}#0", "bb".repeat(32)).parse().unwrap(),
+            since: 1,
+            sponsor: "addr_test1wr5nj776ygd7d7vwcw3f9fknj4axx0wzy67y3qu8dqtscys7w9q65".parse().unwrap(),
+            until: 2,
+        }
+    }
+
+    fn param_names<P: serde::Serialize>(params: &P) -> Vec<String> {
+        let serde_json::Value::Object(params) = serde_json::to_value(params).unwrap() else {
+            unreachable!()
+        };
+        params.keys().cloned().collect()
+    }
+
+    #[test]
+    fn top_ups_fill_every_param_of_their_add_variant() {
+        use tx3_tir::reduce::Apply;
+
+        let cases = [
+            (protocol::ADD2_IR, param_names(&add2_params([top_up(0), top_up(1)]))),
+            (
+                protocol::ADD3_IR,
+                param_names(&add3_params([top_up(0), top_up(1), top_up(2)])),
+            ),
+        ];
+        for (ir, names) in cases {
+            let tx = tx3_tir::encoding::from_bytes(
+                &hex::decode(ir).unwrap(),
+                tx3_tir::encoding::TirVersion::V1Beta0,
+            )
+            .unwrap();
+            let expected: Vec<String> = tx.params().into_keys().collect();
+            assert_eq!(names, expected);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use axum::Json;
+use pallas_crypto::hash::Hasher;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Canonical bounty id for a GitHub issue: blake2b-256 of `owner/repo#issue`,
+// hex-encoded so it can be used directly as the bounty NFT asset name.
+// Must stay in sync with the worker's `bounty_id::derive_bounty_id`.
+pub fn derive_bounty_id(repo: &str, issue: u64) -> String {
+    let canonical = format!("{}#{}", repo.trim().to_lowercase(), issue);
+    hex::encode(Hasher::<256>::hash(canonical.as_bytes()).as_ref())
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct DeriveBountyIdRequest {
+    pub repo: String,
+    pub issue: u64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DeriveBountyIdResponse {
+    pub bounty_id: String,
+}
+
+pub async fn derive_bounty_id_route(
+    Json(req): Json<DeriveBountyIdRequest>,
+) -> Json<DeriveBountyIdResponse> {
+    Json(DeriveBountyIdResponse {
+        bounty_id: derive_bounty_id(&req.repo, req.issue),
+    })
+}
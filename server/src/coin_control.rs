@@ -0,0 +1,210 @@
+use pallas_traverse::MultiEraTx;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tx3_sdk::trp::TxEnvelope;
+
+use githoney_common::types::UtxoRef;
+
+use crate::preview::input_ref;
+
+// Request fields choosing which wallet UTxOs a transaction may spend
+const FIELDS: [&str; 3] = ["inputs", "exclude_inputs", "collateral"];
+
+// Optional coin control accepted by every build route, next to the protocol
+// params. It is passed to TRP as constraints on the wallet and collateral
+// queries (see `resolve`); the built transaction is still checked against it
+// and withheld if it does not comply.
+#[derive(Deserialize, JsonSchema, Default, Clone, Debug)]
+pub struct CoinControl {
+    // UTxOs the transaction must spend
+    #[serde(default)]
+    pub inputs: Vec<UtxoRef>,
+    // UTxOs the transaction must not spend, e.g. ones holding NFTs or stake
+    #[serde(default)]
+    pub exclude_inputs: Vec<UtxoRef>,
+    // UTxOs the transaction may use as collateral; any when empty
+    #[serde(default)]
+    pub collateral: Vec<UtxoRef>,
+}
+
+// Removes the coin control fields from a request body, so the rest parses
+// into the protocol params as before
+pub fn take(body: &mut Map<String, Value>) -> Result<CoinControl, String> {
+    let mut fields = Map::new();
+    for field in FIELDS {
+        if let Some(value) = body.remove(field) {
+            fields.insert(field.to_string(), value);
+        }
+    }
+
+    let coin_control: CoinControl = serde_path_to_error::deserialize(Value::Object(fields))
+        .map_err(|e| format!("Invalid field `{}`: {}", e.path(), e.inner()))?;
+    coin_control.validate()?;
+    Ok(coin_control)
+}
+
+impl CoinControl {
+    pub fn validate(&self) -> Result<(), String> {
+        match self
+            .inputs
+            .iter()
+            .find(|input| self.exclude_inputs.contains(input))
+        {
+            Some(input) => Err(format!(
+                "Invalid field `exclude_inputs`: {} is also listed in `inputs`",
+                input
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.exclude_inputs.is_empty() && self.collateral.is_empty()
+    }
+
+    pub fn check(&self, tx: &TxEnvelope) -> Result<(), String> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let bytes =
+            hex::decode(&tx.tx).map_err(|e| format!("transaction is not valid hex: {}", e))?;
+        let decoded = MultiEraTx::decode(&bytes)
+            .map_err(|e| format!("transaction does not decode: {}", e))?;
+        let inputs: Vec<String> = decoded.inputs().iter().map(input_ref).collect();
+        let collateral: Vec<String> = decoded.collateral().iter().map(input_ref).collect();
+
+        if let Some(missing) = self
+            .inputs
+            .iter()
+            .map(UtxoRef::to_string)
+            .find(|input| !inputs.contains(input))
+        {
+            return Err(format!("requested input {} is not spent", missing));
+        }
+
+        if let Some(excluded) = self
+            .exclude_inputs
+            .iter()
+            .map(UtxoRef::to_string)
+            .find(|input| inputs.contains(input))
+        {
+            return Err(format!("excluded input {} is spent", excluded));
+        }
+
+        if !self.collateral.is_empty() {
+            let allowed: Vec<String> = self.collateral.iter().map(UtxoRef::to_string).collect();
+            if let Some(other) = collateral.iter().find(|input| !allowed.contains(input)) {
+                return Err(format!(
+                    "collateral {} is not one of the requested UTxOs",
+                    other
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallas_codec::minicbor;
+
+    fn utxo_ref(byte: &str, index: u32) -> UtxoRef {
+        format!("{}#{}", byte.repeat(32), index).parse().unwrap()
+    }
+
+    // A transaction spending `inputs` with `collateral`, and no outputs
+    fn tx(inputs: &[UtxoRef], collateral: &[UtxoRef]) -> TxEnvelope {
+        let mut encoder = minicbor::Encoder::new(Vec::new());
+        let refs = |encoder: &mut minicbor::Encoder<Vec<u8>>, refs: &[UtxoRef]| {
+            encoder.array(refs.len() as u64).unwrap();
+            for utxo_ref in refs {
+                encoder
+                    .array(2)
+                    .unwrap()
+                    .bytes(&hex::decode(utxo_ref.tx_hash.as_str()).unwrap())
+                    .unwrap()
+                    .u32(utxo_ref.index)
+                    .unwrap();
+            }
+        };
+        encoder.array(4).unwrap().map(4).unwrap();
+        encoder.u8(0).unwrap();
+        refs(&mut encoder, inputs);
+        encoder.u8(1).unwrap().array(0).unwrap();
+        encoder.u8(2).unwrap().u64(200_000).unwrap();
+        encoder.u8(13).unwrap();
+        refs(&mut encoder, collateral);
+        encoder.map(0).unwrap().bool(true).unwrap().null().unwrap();
+
+        TxEnvelope {
+            tx: hex::encode(encoder.into_writer()),
+            hash: String::new(),
+        }
+    }
+
+    fn body(fields: Value) -> Map<String, Value> {
+        let Value::Object(body) = fields else {
+            unreachable!()
+        };
+        body
+    }
+
+    #[test]
+    fn takes_coin_control_out_of_the_body() {
+        let mut request = body(serde_json::json!({
+            "sponsor": "addr_test1...",
+            "inputs": [utxo_ref("aa", 0).to_string()],
+        }));
+        let coin_control = take(&mut request).unwrap();
+        assert_eq!(coin_control.inputs, vec![utxo_ref("aa", 0)]);
+        assert!(request.contains_key("sponsor"));
+        assert!(!request.contains_key("inputs"));
+
+        let mut overlapping = body(serde_json::json!({
+            "inputs": [utxo_ref("aa", 0).to_string()],
+            "exclude_inputs": [utxo_ref("aa", 0).to_string()],
+        }));
+        assert!(take(&mut overlapping).unwrap_err().contains("also listed in `inputs`"));
+
+        let mut invalid = body(serde_json::json!({ "collateral": ["aa#0"] }));
+        assert!(take(&mut invalid).unwrap_err().starts_with("Invalid field `collateral"));
+    }
+
+    #[test]
+    fn checks_inputs_and_collateral() {
+        let built = tx(&[utxo_ref("aa", 0), utxo_ref("bb", 1)], &[utxo_ref("cc", 0)]);
+        assert_eq!(CoinControl::default().check(&built), Ok(()));
+
+        let complying = CoinControl {
+            inputs: vec![utxo_ref("aa", 0)],
+            exclude_inputs: vec![utxo_ref("dd", 0)],
+            collateral: vec![utxo_ref("cc", 0)],
+        };
+        assert_eq!(complying.check(&built), Ok(()));
+
+        let missing = CoinControl {
+            inputs: vec![utxo_ref("dd", 0)],
+            ..Default::default()
+        };
+        assert!(missing.check(&built).unwrap_err().contains("is not spent"));
+
+        let excluded = CoinControl {
+            exclude_inputs: vec![utxo_ref("bb", 1)],
+            ..Default::default()
+        };
+        assert!(excluded.check(&built).unwrap_err().contains("is spent"));
+
+        let other_collateral = CoinControl {
+            collateral: vec![utxo_ref("dd", 0)],
+            ..Default::default()
+        };
+        assert!(other_collateral
+            .check(&built)
+            .unwrap_err()
+            .contains("is not one of the requested UTxOs"));
+    }
+}
@@ -1,22 +1,114 @@
+use pallas_traverse::MultiEraTx;
 use reqwest::Client;
+use std::time::Instant;
 use tx3_sdk::trp::TxEnvelope;
 
+use crate::preview::input_ref;
+use crate::resolve::ogmios_ref;
+use crate::{auth, metrics, pending, request_id};
+
+// Per-transaction `maxTxExecutionUnits` of the protocol parameters
+const DEFAULT_MAX_TX_MEMORY: u64 = 14_000_000;
+const DEFAULT_MAX_TX_CPU: u64 = 10_000_000_000;
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+// Sums the redeemer budgets of a successful evaluation and rejects
+// transactions the ledger would refuse for exceeding the per-tx limit
+fn check_budget(response: &str) -> Result<(), String> {
+    let redeemers = serde_json::from_str::<serde_json::Value>(response)
+        .ok()
+        .and_then(|response| response["result"].as_array().cloned())
+        .unwrap_or_default();
+
+    let (memory, cpu) = redeemers
+        .iter()
+        .fold((0u64, 0u64), |(memory, cpu), redeemer| {
+            (
+                memory + redeemer["budget"]["memory"].as_u64().unwrap_or_default(),
+                cpu + redeemer["budget"]["cpu"].as_u64().unwrap_or_default(),
+            )
+        });
+
+    let max_memory = env_u64("MAX_TX_EX_MEMORY", DEFAULT_MAX_TX_MEMORY);
+    let max_cpu = env_u64("MAX_TX_EX_CPU", DEFAULT_MAX_TX_CPU);
+    if memory > max_memory || cpu > max_cpu {
+        return Err(format!(
+            "Transaction exceeds the execution budget: memory {} of {}, cpu {} of {}",
+            memory, max_memory, cpu, max_cpu
+        ));
+    }
+
+    tracing::info!(memory, cpu, "Transaction execution budget");
+    Ok(())
+}
+
+// Refuses transactions spending what a pending transaction already spends,
+// and returns the pending or `unconfirmed` outputs they spend or reference
+fn pending_inputs(
+    base16_cbor: &str,
+    unconfirmed: &[serde_json::Value],
+) -> Result<Vec<serde_json::Value>, String> {
+    let Ok(bytes) = hex::decode(base16_cbor) else {
+        return Ok(vec![]);
+    };
+    let Ok(tx) = MultiEraTx::decode(&bytes) else {
+        return Ok(vec![]);
+    };
+
+    let caller = auth::caller();
+    let inputs: Vec<String> = tx.inputs().iter().map(input_ref).collect();
+    if let Some((input, pending_tx)) = pending::spent_by_pending(&inputs, &caller) {
+        return Err(format!(
+            "Input {} is already spent by pending transaction {}, wait for it to confirm or expire",
+            input, pending_tx
+        ));
+    }
+
+    let mut refs = inputs;
+    refs.extend(tx.reference_inputs().iter().map(input_ref));
+    refs.extend(tx.collateral().iter().map(input_ref));
+    let mut additional_utxo = pending::additional_utxos(&refs, &caller);
+    additional_utxo.extend(
+        unconfirmed
+            .iter()
+            .filter(|utxo| refs.contains(&ogmios_ref(utxo)))
+            .cloned(),
+    );
+    Ok(additional_utxo)
+}
+
 async fn ogmios_evaluate(
     client: Client,
     url: &str,
     ogmios_api_key: &str,
     base16_cbor: &str,
+    additional_utxo: Vec<serde_json::Value>,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut params = serde_json::json!({ "transaction": { "cbor": base16_cbor } });
+    if !additional_utxo.is_empty() {
+        params["additionalUtxo"] = serde_json::Value::Array(additional_utxo);
+    }
     let body = serde_json::json!({
         "jsonrpc": "2.0",
         "method": "evaluateTransaction",
-        "params": { "transaction": { "cbor": base16_cbor } }
+        "params": params
     });
 
-    let resp_text = client
+    let mut request = client
         .post(url)
         .header("Content-Type", "application/json")
-        .header("dmtr-api-key", ogmios_api_key)
+        .header("dmtr-api-key", ogmios_api_key);
+    if let Some(id) = request_id::header_value() {
+        request = request.header(request_id::REQUEST_ID_HEADER, id);
+    }
+
+    let resp_text = request
         .body(serde_json::to_vec(&body)?)
         .send()
         .await?
@@ -26,21 +118,43 @@ async fn ogmios_evaluate(
     Ok(resp_text)
 }
 
-pub async fn evaluate_tx(tx: TxEnvelope) -> Result<TxEnvelope, String> {
+// `unconfirmed` holds outputs of transactions that are not submitted yet,
+// such as earlier links of a batch chain. A transaction that evaluates is
+// recorded as built for the caller, who may then submit it through `/submit`.
+pub async fn evaluate_tx(
+    tx: TxEnvelope,
+    unconfirmed: &[serde_json::Value],
+) -> Result<TxEnvelope, String> {
     let evaluate_url = std::env::var("OGMIOS_ENDPOINT").unwrap();
     let ogmios_api_key = std::env::var("DMTR_API_KEY_OGMIOS").unwrap();
     let client = reqwest::Client::new();
+    let additional_utxo = pending_inputs(&tx.tx, unconfirmed)?;
+    let started = Instant::now();
 
-    match ogmios_evaluate(client, &evaluate_url, &ogmios_api_key, &tx.tx).await {
+    match ogmios_evaluate(
+        client,
+        &evaluate_url,
+        &ogmios_api_key,
+        &tx.tx,
+        additional_utxo,
+    )
+    .await
+    {
         Ok(response) => {
-            println!("Transaction evaluated successfully: {}", response);
+            tracing::info!(response = %response, "Transaction evaluated");
             if response.contains("Some of the scripts failed") {
+                metrics::observe_evaluation(started, "script_failure");
+                metrics::record_script_failures(&response);
                 return Err(response);
             }
+            metrics::observe_evaluation(started, "ok");
+            check_budget(&response)?;
+            pending::record_built(&tx, &auth::caller());
             Ok(tx)
         }
         Err(e) => {
-            println!("Error evaluating transaction: {:?}", e);
+            metrics::observe_evaluation(started, "error");
+            tracing::error!(error = ?e, "Error evaluating transaction");
             Err(format!("Error evaluating transaction: {:?}", e))
         }
     }
@@ -0,0 +1,715 @@
+use axum::{body::Bytes, http::HeaderMap, http::StatusCode, Json};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use githoney_common::datum::{decode_githoney_datum, GithoneyDatum};
+use githoney_common::types::{Address, BountyId, KeyHash, PolicyId, UtxoRef};
+
+use crate::coin_control::CoinControl;
+use crate::params::{AssignParams, CreateWithLovelaceParams, MergeParams};
+
+use crate::bounty_id::derive_bounty_id;
+use crate::{evaluate_tx, resolve, time, tx_check};
+
+// Per-repository bounty settings for the maintainer behind it
+#[derive(Deserialize, Clone)]
+pub struct RepoConfig {
+    pub maintainer: Address,
+    pub maintainer_payment_key: KeyHash,
+    pub maintainer_stake_key: KeyHash,
+    pub reward_amount: u64,
+    pub min_ada: u64,
+    pub bounty_creation_fee: u64,
+    pub bounty_rewards_fee: u64,
+    pub deadline_days: u64,
+}
+
+// Wallet the bridge assigns a GitHub user's pull requests to
+#[derive(Deserialize, Clone)]
+pub struct ContributorConfig {
+    pub address: Address,
+    pub payment_key: KeyHash,
+    pub stake_key: KeyHash,
+}
+
+#[derive(Deserialize)]
+pub struct BridgeConfig {
+    pub webhook_secret: String,
+    pub bounty_label: String,
+    // Protocol values the Balius worker normally fills in from its config
+    pub admin: Address,
+    pub admin_payment_key: KeyHash,
+    pub githoneyaddr: Address,
+    pub script: Address,
+    pub settings_ref: UtxoRef,
+    pub minting_policy_id: PolicyId,
+    // Keyed by `owner/repo`
+    pub repos: HashMap<String, RepoConfig>,
+    // Keyed by GitHub login; pull requests of other users are not assigned
+    #[serde(default)]
+    pub contributors: HashMap<String, ContributorConfig>,
+}
+
+fn load_bridge_config() -> Result<Option<BridgeConfig>, String> {
+    let Ok(path) = std::env::var("GITHUB_BRIDGE_CONFIG") else {
+        return Ok(None);
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read GITHUB_BRIDGE_CONFIG {}: {}", path, e))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| format!("Invalid GITHUB_BRIDGE_CONFIG {}: {}", path, e))
+}
+
+// Without GITHUB_BRIDGE_CONFIG the bridge is off; a config that cannot be
+// loaded stops the server at startup, see `check_config`
+static BRIDGE_CONFIG: Lazy<Result<Option<BridgeConfig>, String>> = Lazy::new(load_bridge_config);
+
+pub fn check_config() -> Result<(), String> {
+    BRIDGE_CONFIG.as_ref().map(|_| ()).map_err(String::clone)
+}
+
+fn bridge_config() -> Option<&'static BridgeConfig> {
+    BRIDGE_CONFIG.as_ref().ok().and_then(Option::as_ref)
+}
+
+// The bounty's `bounty_reward_fee` is in basis points
+const REWARD_FEE_DENOMINATOR: u64 = 10_000;
+
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BridgeAction {
+    Create,
+    Assign,
+    Merge,
+}
+
+// Pending actions are dropped after a week, oldest first beyond the cap
+const MAX_PENDING_ACTIONS: usize = 500;
+const PENDING_ACTION_TTL_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+// GitHub redelivers on timeouts and manual retries; remember this many delivery ids
+const MAX_SEEN_DELIVERIES: usize = 2048;
+
+// A protocol action derived from a GitHub event, waiting for its signer: the
+// contributor for an assignment, the maintainer otherwise
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+pub struct PendingAction {
+    pub delivery_id: String,
+    // POSIX time in milliseconds the delivery was received
+    pub received_ms: u64,
+    pub repo: String,
+    pub issue: u64,
+    pub action: BridgeAction,
+    pub bounty_id: String,
+    pub maintainer_payment_key: KeyHash,
+    pub contributor: Option<String>,
+    pub tx_hash: Option<String>,
+    pub tx: Option<String>,
+    pub error: Option<String>,
+}
+
+static PENDING_ACTIONS: Lazy<Mutex<VecDeque<PendingAction>>> =
+    Lazy::new(|| Mutex::new(VecDeque::new()));
+
+// Delivery ids already handled or being handled, oldest first
+static SEEN_DELIVERIES: Lazy<Mutex<VecDeque<String>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+// GitHub's closing keywords followed by an issue reference, e.g. "Fixes #12"
+static CLOSING_KEYWORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:close[sd]?|fix(?:e[sd])?|resolve[sd]?):?\s+#(\d+)\b").unwrap()
+});
+
+fn prune_actions(actions: &mut VecDeque<PendingAction>, now_ms: u64) {
+    actions.retain(|action| action.received_ms + PENDING_ACTION_TTL_MS > now_ms);
+    while actions.len() > MAX_PENDING_ACTIONS {
+        actions.pop_front();
+    }
+}
+
+// Records a delivery id, returning false if it was already seen
+fn first_delivery(delivery_id: &str) -> bool {
+    let mut seen = SEEN_DELIVERIES.lock().unwrap();
+    if seen.iter().any(|seen| seen == delivery_id) {
+        return false;
+    }
+    seen.push_back(delivery_id.to_string());
+    if seen.len() > MAX_SEEN_DELIVERIES {
+        seen.pop_front();
+    }
+    true
+}
+
+// Lets GitHub redeliver an event that could not be handled
+fn forget_delivery(delivery_id: &str) {
+    SEEN_DELIVERIES
+        .lock()
+        .unwrap()
+        .retain(|seen| seen != delivery_id);
+}
+
+///// GITHUB WEBHOOK PAYLOADS /////
+#[derive(Deserialize, Debug)]
+struct Repository {
+    full_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Label {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct User {
+    login: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Issue {
+    number: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct IssuesEvent {
+    action: String,
+    issue: Issue,
+    label: Option<Label>,
+    repository: Repository,
+}
+
+#[derive(Deserialize, Debug)]
+struct PullRequest {
+    body: Option<String>,
+    merged: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PullRequestEvent {
+    action: String,
+    pull_request: PullRequest,
+    assignee: Option<User>,
+    repository: Repository,
+}
+
+// Checks the `X-Hub-Signature-256: sha256=<hex>` header against the raw body
+fn verify_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let Some(signature) = headers
+        .get("X-Hub-Signature-256")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("sha256="))
+        .and_then(|value| hex::decode(value).ok())
+    else {
+        return false;
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn header(headers: &HeaderMap, name: &str) -> String {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+// Finds the issue a PR resolves from closing keywords in its body ("Fixes #12")
+fn linked_issue(body: &str) -> Option<u64> {
+    CLOSING_KEYWORD
+        .captures_iter(body)
+        .find_map(|captures| captures[1].parse().ok())
+}
+
+// Validity interval of a transaction built now
+fn validity(now: u64) -> Result<(u64, u64), String> {
+    time::resolve_validity(&time::network_slots()?, Some(now), None, None).map_err(|e| e.to_string())
+}
+
+// The UTxO holding a bounty's NFT at the script
+struct BountyUtxo {
+    utxo_ref: UtxoRef,
+    lovelace: u64,
+    datum: GithoneyDatum,
+}
+
+async fn find_bounty(config: &BridgeConfig, bounty_id: &BountyId) -> Result<BountyUtxo, String> {
+    let utxos = resolve::query_utxos(json!({ "addresses": [config.script.as_str()] })).await?;
+    let utxo = utxos
+        .iter()
+        .find(|utxo| {
+            utxo["value"][config.minting_policy_id.as_str()][bounty_id.as_str()].as_u64() == Some(1)
+        })
+        .ok_or_else(|| format!("No UTxO at the script holds bounty {}", bounty_id))?;
+
+    let datum = utxo["datum"]
+        .as_str()
+        .and_then(|datum| hex::decode(datum).ok())
+        .and_then(|cbor| decode_githoney_datum(&cbor))
+        .ok_or_else(|| format!("Bounty {} has no valid GithoneyDatum", bounty_id))?;
+    Ok(BountyUtxo {
+        utxo_ref: resolve::ogmios_ref(utxo).parse()?,
+        lovelace: utxo["value"]["ada"]["lovelace"].as_u64().unwrap_or_default(),
+        datum,
+    })
+}
+
+// GitHoney's fee on merging: `bounty_reward_fee` basis points of the reward,
+// which is the bounty's lovelace less the maintainer's and the contributor's
+// `min_ada` deposits
+fn reward_fee(bounty: &BountyUtxo, min_ada: u64) -> Result<u64, String> {
+    let reward = bounty
+        .lovelace
+        .checked_sub(min_ada.saturating_mul(2))
+        .ok_or_else(|| format!("Bounty holds {} lovelace, less than its deposits", bounty.lovelace))?;
+    let fee = u128::from(reward) * u128::from(bounty.datum.bounty_reward_fee)
+        / u128::from(REWARD_FEE_DENOMINATOR);
+    u64::try_from(fee).map_err(|_| "Reward fee is too large".to_string())
+}
+
+async fn build_create_tx(
+    config: &BridgeConfig,
+    repo: &RepoConfig,
+    bounty_id: &BountyId,
+) -> Result<tx3_sdk::trp::TxEnvelope, String> {
+    let now = time::now_posix_ms();
+    let (since, until) = validity(now)?;
+
+    let params = CreateWithLovelaceParams {
+        admin_payment_key: config.admin_payment_key.clone(),
+        bounty_creation_fee: repo.bounty_creation_fee,
+        bounty_id: bounty_id.clone(),
+        bounty_rewards_fee: repo.bounty_rewards_fee,
+        githoneyaddr: config.githoneyaddr.clone(),
+        maintainer: repo.maintainer.clone(),
+        maintainer_payment_key: repo.maintainer_payment_key.clone(),
+        maintainer_stake_key: repo.maintainer_stake_key.clone(),
+        min_ada: repo.min_ada,
+        minting_policy_id: config.minting_policy_id.clone(),
+        reward_amount: repo.reward_amount,
+        script: config.script.clone(),
+        settings_ref: config.settings_ref.clone(),
+        since,
+        time_limit: now + repo.deadline_days * 24 * 60 * 60 * 1000,
+        until,
+    };
+
+    let protocol_params = protocol::CreateWithLovelaceParams::from(params.clone());
+    let tx = resolve::resolve(
+        protocol::CREATE_WITH_LOVELACE_IR,
+        &protocol_params,
+        &CoinControl::default(),
+        &[],
+    )
+        .await
+        .map_err(|e| format!("Error creating bounty: {}", e))?;
+    tx_check::check_create_bounty(&tx, &params)
+        .await
+        .map_err(|e| format!("Transaction check failed: {}", e))?;
+    evaluate_tx::evaluate_tx(tx, &[]).await
+}
+
+async fn build_assign_tx(
+    config: &BridgeConfig,
+    repo: &RepoConfig,
+    bounty_id: &BountyId,
+    login: Option<&str>,
+) -> Result<tx3_sdk::trp::TxEnvelope, String> {
+    let login = login.ok_or("The pull request has no assignee")?;
+    let contributor = config
+        .contributors
+        .get(login)
+        .ok_or_else(|| format!("No wallet is configured for GitHub user {}", login))?;
+    let bounty = find_bounty(config, bounty_id).await?;
+    if bounty.datum.contributor_payment_key.is_some() {
+        return Err(format!("Bounty {} is already assigned", bounty_id));
+    }
+    let (since, until) = validity(time::now_posix_ms())?;
+
+    let params = AssignParams {
+        bountyref: bounty.utxo_ref,
+        contributor: contributor.address.clone(),
+        contributor_payment_key: contributor.payment_key.clone(),
+        contributor_stake_key: contributor.stake_key.clone(),
+        min_ada: repo.min_ada,
+        script: config.script.clone(),
+        settingsref: config.settings_ref.clone(),
+        since,
+        until,
+    };
+
+    let protocol_params = protocol::AssignParams::from(params.clone());
+    let tx = resolve::resolve(protocol::ASSIGN_IR, &protocol_params, &CoinControl::default(), &[])
+        .await
+        .map_err(|e| format!("Error assigning bounty: {}", e))?;
+    tx_check::check_assign_bounty(&tx, &params, bounty_id)
+        .map_err(|e| format!("Transaction check failed: {}", e))?;
+    evaluate_tx::evaluate_tx(tx, &[]).await
+}
+
+async fn build_merge_tx(
+    config: &BridgeConfig,
+    repo: &RepoConfig,
+    bounty_id: &BountyId,
+) -> Result<tx3_sdk::trp::TxEnvelope, String> {
+    let bounty = find_bounty(config, bounty_id).await?;
+    if bounty.datum.contributor_payment_key.is_none() {
+        return Err(format!("Bounty {} has no contributor to merge", bounty_id));
+    }
+    if bounty.datum.merged {
+        return Err(format!("Bounty {} is already merged", bounty_id));
+    }
+    let (since, until) = validity(time::now_posix_ms())?;
+
+    let params = MergeParams {
+        admin: config.admin.clone(),
+        bountyref: bounty.utxo_ref.clone(),
+        githoney_fee: reward_fee(&bounty, repo.min_ada)?,
+        githoneyaddr: config.githoneyaddr.clone(),
+        maintainer: repo.maintainer.clone(),
+        min_ada: repo.min_ada,
+        script: config.script.clone(),
+        settingsref: config.settings_ref.clone(),
+        since,
+        until,
+    };
+
+    let protocol_params = protocol::MergeParams::from(params.clone());
+    let tx = resolve::resolve(protocol::MERGE_IR, &protocol_params, &CoinControl::default(), &[])
+        .await
+        .map_err(|e| format!("Error merging bounty: {}", e))?;
+    tx_check::check_merge_bounty(&tx, &params, bounty_id)
+        .map_err(|e| format!("Transaction check failed: {}", e))?;
+    evaluate_tx::evaluate_tx(tx, &[]).await
+}
+
+// What the bridge reads from a GitHub event it acts on
+#[derive(Debug, PartialEq, Eq)]
+struct BridgeEvent {
+    repo: String,
+    issue: u64,
+    action: BridgeAction,
+    contributor: Option<String>,
+}
+
+// Maps a verified GitHub event onto a protocol action, if it is one the bridge handles
+fn parse_event(event: &str, body: &[u8], bounty_label: &str) -> Result<Option<BridgeEvent>, String> {
+    match event {
+        "issues" => {
+            let payload: IssuesEvent = serde_json::from_slice(body)
+                .map_err(|e| format!("Invalid issues payload: {}", e))?;
+            let labeled_bounty = payload.action == "labeled"
+                && payload
+                    .label
+                    .is_some_and(|label| label.name == bounty_label);
+            if !labeled_bounty {
+                return Ok(None);
+            }
+            Ok(Some(BridgeEvent {
+                repo: payload.repository.full_name,
+                issue: payload.issue.number,
+                action: BridgeAction::Create,
+                contributor: None,
+            }))
+        }
+        "pull_request" => {
+            let payload: PullRequestEvent = serde_json::from_slice(body)
+                .map_err(|e| format!("Invalid pull_request payload: {}", e))?;
+            let action = match payload.action.as_str() {
+                "assigned" => BridgeAction::Assign,
+                "closed" if payload.pull_request.merged == Some(true) => BridgeAction::Merge,
+                _ => return Ok(None),
+            };
+            let Some(issue) = payload.pull_request.body.as_deref().and_then(linked_issue) else {
+                return Ok(None);
+            };
+            Ok(Some(BridgeEvent {
+                repo: payload.repository.full_name,
+                issue,
+                action,
+                contributor: payload.assignee.map(|user| user.login),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn handle_event(
+    config: &BridgeConfig,
+    event: &str,
+    delivery_id: String,
+    body: &[u8],
+) -> Result<Option<PendingAction>, String> {
+    let Some(BridgeEvent {
+        repo: repo_name,
+        issue,
+        action,
+        contributor,
+    }) = parse_event(event, body, &config.bounty_label)?
+    else {
+        return Ok(None);
+    };
+
+    let Some(repo) = config.repos.get(&repo_name) else {
+        tracing::info!(event, repo = %repo_name, "Ignoring event for unconfigured repo");
+        return Ok(None);
+    };
+
+    let bounty_id: BountyId = derive_bounty_id(&repo_name, issue).parse()?;
+    let mut pending = PendingAction {
+        delivery_id,
+        received_ms: time::now_posix_ms(),
+        repo: repo_name,
+        issue,
+        action,
+        bounty_id: bounty_id.to_string(),
+        maintainer_payment_key: repo.maintainer_payment_key.clone(),
+        contributor,
+        tx_hash: None,
+        tx: None,
+        error: None,
+    };
+
+    let built = match action {
+        BridgeAction::Create => build_create_tx(config, repo, &bounty_id).await,
+        BridgeAction::Assign => {
+            build_assign_tx(config, repo, &bounty_id, pending.contributor.as_deref()).await
+        }
+        BridgeAction::Merge => build_merge_tx(config, repo, &bounty_id).await,
+    };
+    match built {
+        Ok(tx) => {
+            pending.tx_hash = Some(tx.hash);
+            pending.tx = Some(tx.tx);
+        }
+        Err(e) => pending.error = Some(e),
+    }
+
+    let mut actions = PENDING_ACTIONS.lock().unwrap();
+    actions.push_back(pending.clone());
+    prune_actions(&mut actions, pending.received_ms);
+    Ok(Some(pending))
+}
+
+pub async fn github_webhook(
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<Result<Option<PendingAction>, String>>) {
+    let Some(config) = bridge_config() else {
+        tracing::warn!("GITHUB_BRIDGE_CONFIG is not set, rejecting GitHub webhook");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(Err("The GitHub bridge is not configured".to_string())),
+        );
+    };
+    if !verify_signature(&config.webhook_secret, &headers, &body) {
+        tracing::warn!("Rejected GitHub webhook with invalid signature");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(Err("Invalid webhook signature".to_string())),
+        );
+    }
+
+    let event = header(&headers, "X-GitHub-Event");
+    let delivery_id = header(&headers, "X-GitHub-Delivery");
+    tracing::info!(%event, %delivery_id, "Received GitHub webhook");
+
+    if delivery_id.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(Err("Missing X-GitHub-Delivery header".to_string())),
+        );
+    }
+    if !first_delivery(&delivery_id) {
+        tracing::info!(%delivery_id, "Ignoring redelivered GitHub webhook");
+        return (StatusCode::OK, Json(Ok(None)));
+    }
+
+    match handle_event(config, &event, delivery_id.clone(), &body).await {
+        Ok(pending) => (StatusCode::OK, Json(Ok(pending))),
+        Err(e) => {
+            tracing::error!(error = %e, "Error handling GitHub webhook");
+            forget_delivery(&delivery_id);
+            (StatusCode::BAD_REQUEST, Json(Err(e)))
+        }
+    }
+}
+
+pub async fn pending_actions() -> Json<Vec<PendingAction>> {
+    let mut actions = PENDING_ACTIONS.lock().unwrap();
+    prune_actions(&mut actions, time::now_posix_ms());
+    Json(actions.iter().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const LABEL: &str = "githoney";
+
+    fn fixture(name: &str) -> &'static [u8] {
+        match name {
+            "issues_labeled" => include_bytes!("fixtures/github/issues_labeled.json"),
+            "issues_labeled_other" => include_bytes!("fixtures/github/issues_labeled_other.json"),
+            "pull_request_assigned" => include_bytes!("fixtures/github/pull_request_assigned.json"),
+            "pull_request_closed_merged" => {
+                include_bytes!("fixtures/github/pull_request_closed_merged.json")
+            }
+            "pull_request_closed_unmerged" => {
+                include_bytes!("fixtures/github/pull_request_closed_unmerged.json")
+            }
+            _ => unreachable!("unknown fixture {}", name),
+        }
+    }
+
+    fn signed(signature: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Hub-Signature-256", HeaderValue::from_str(signature).unwrap());
+        headers
+    }
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn verifies_github_documented_signature() {
+        // Example from GitHub's "Validating webhook deliveries" docs
+        let headers = signed("sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17");
+        assert!(verify_signature("It's a Secret to Everybody", &headers, b"Hello, World!"));
+    }
+
+    #[test]
+    fn rejects_bad_signatures() {
+        let body = fixture("issues_labeled");
+        let signature = sign("secret", body);
+
+        assert!(verify_signature("secret", &signed(&signature), body));
+        assert!(!verify_signature("other", &signed(&signature), body));
+        assert!(!verify_signature("secret", &signed(&signature), fixture("issues_labeled_other")));
+        assert!(!verify_signature("secret", &signed(signature.trim_start_matches("sha256=")), body));
+        assert!(!verify_signature("secret", &HeaderMap::new(), body));
+    }
+
+    #[test]
+    fn maps_bounty_label_to_create() {
+        let event = parse_event("issues", fixture("issues_labeled"), LABEL).unwrap();
+        assert_eq!(
+            event,
+            Some(BridgeEvent {
+                repo: "octo-org/octo-repo".to_string(),
+                issue: 42,
+                action: BridgeAction::Create,
+                contributor: None,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_other_labels_and_events() {
+        assert_eq!(parse_event("issues", fixture("issues_labeled_other"), LABEL), Ok(None));
+        assert_eq!(parse_event("push", fixture("issues_labeled"), LABEL), Ok(None));
+        assert!(parse_event("pull_request", fixture("issues_labeled"), LABEL).is_err());
+    }
+
+    #[test]
+    fn maps_pull_requests_to_assign_and_merge() {
+        let assigned = parse_event("pull_request", fixture("pull_request_assigned"), LABEL).unwrap();
+        assert_eq!(
+            assigned,
+            Some(BridgeEvent {
+                repo: "octo-org/octo-repo".to_string(),
+                issue: 42,
+                action: BridgeAction::Assign,
+                contributor: Some("hubot".to_string()),
+            })
+        );
+
+        let merged =
+            parse_event("pull_request", fixture("pull_request_closed_merged"), LABEL).unwrap();
+        assert_eq!(merged.map(|event| (event.issue, event.action)), Some((42, BridgeAction::Merge)));
+
+        let unmerged = parse_event("pull_request", fixture("pull_request_closed_unmerged"), LABEL);
+        assert_eq!(unmerged, Ok(None));
+    }
+
+    #[test]
+    fn linked_issue_needs_a_closing_keyword() {
+        assert_eq!(linked_issue("Fixes #12"), Some(12));
+        assert_eq!(linked_issue("this closes: #7 for good"), Some(7));
+        assert_eq!(linked_issue("Resolved #3"), Some(3));
+        assert_eq!(linked_issue("prefix #1"), None);
+        assert_eq!(linked_issue("suffix #1"), None);
+        assert_eq!(linked_issue("see #12, unrelated to fixtures #4"), None);
+        assert_eq!(linked_issue("fix #12abc"), None);
+    }
+
+    #[test]
+    fn reward_fee_leaves_out_the_deposits() {
+        let bounty = |lovelace: u64| BountyUtxo {
+            utxo_ref: format!("{}#0", "aa".repeat(32)).parse().unwrap(),
+            lovelace,
+            datum: GithoneyDatum {
+                admin_payment_key: "00".repeat(28),
+                maintainer_payment_key: "11".repeat(28),
+                maintainer_stake_key: None,
+                contributor_payment_key: Some("22".repeat(28)),
+                contributor_stake_key: None,
+                bounty_reward_fee: 250,
+                deadline: 0,
+                merged: false,
+            },
+        };
+
+        // 2.5% of the 100 ADA left after two 2 ADA deposits
+        assert_eq!(reward_fee(&bounty(104_000_000), 2_000_000), Ok(2_500_000));
+        assert_eq!(reward_fee(&bounty(4_000_000), 2_000_000), Ok(0));
+        assert!(reward_fee(&bounty(3_000_000), 2_000_000).is_err());
+    }
+
+    #[test]
+    fn deduplicates_deliveries() {
+        let delivery = "72d3162e-cc78-11e3-81ab-4c9367dc0958";
+        assert!(first_delivery(delivery));
+        assert!(!first_delivery(delivery));
+        forget_delivery(delivery);
+        assert!(first_delivery(delivery));
+    }
+
+    #[test]
+    fn prunes_expired_and_excess_actions() {
+        let action = |received_ms: u64| PendingAction {
+            delivery_id: received_ms.to_string(),
+            received_ms,
+            repo: "octo-org/octo-repo".to_string(),
+            issue: 42,
+            action: BridgeAction::Create,
+            bounty_id: "00".to_string(),
+            maintainer_payment_key: "00".repeat(28).parse().unwrap(),
+            contributor: None,
+            tx_hash: None,
+            tx: None,
+            error: None,
+        };
+
+        let now = PENDING_ACTION_TTL_MS * 2;
+        let mut actions: VecDeque<_> = (0..MAX_PENDING_ACTIONS as u64 + 10)
+            .map(|i| action(now - PENDING_ACTION_TTL_MS / 2 + i))
+            .collect();
+        actions.push_front(action(now - PENDING_ACTION_TTL_MS));
+
+        prune_actions(&mut actions, now);
+        assert_eq!(actions.len(), MAX_PENDING_ACTIONS);
+        assert_eq!(actions[0].received_ms, now - PENDING_ACTION_TTL_MS / 2 + 10);
+    }
+}
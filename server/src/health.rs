@@ -0,0 +1,169 @@
+use axum::{http::StatusCode, Json};
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::tx_check;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, JsonSchema)]
+pub struct Check {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(detail: impl Into<String>) -> Check {
+        Check {
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(detail: impl Into<String>) -> Check {
+        Check {
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    pub trp: Check,
+    pub ogmios: Check,
+    pub settings_utxo: Check,
+}
+
+pub(crate) fn env(name: &str) -> Result<String, String> {
+    std::env::var(name).map_err(|_| format!("{} is not set", name))
+}
+
+pub(crate) async fn json_rpc(
+    client: &Client,
+    url: &str,
+    api_key: &str,
+    method: &str,
+    params: Value,
+) -> Result<(StatusCode, Value), String> {
+    let response = client
+        .post(url)
+        .timeout(PROBE_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header("dmtr-api-key", api_key)
+        .body(json!({ "jsonrpc": "2.0", "method": method, "params": params }).to_string())
+        .send()
+        .await
+        .map_err(|e| format!("unreachable: {}", e))?;
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let body = serde_json::from_str(&text).unwrap_or(Value::Null);
+    Ok((status, body))
+}
+
+// TRP answers unknown JSON-RPC methods with a JSON-RPC error, so any
+// successful HTTP response means the endpoint and API key are usable
+async fn check_trp(client: &Client) -> Check {
+    let probe = async {
+        let url = env("TRP_ENDPOINT")?;
+        let api_key = env("DMTR_API_KEY")?;
+        let (status, _) = json_rpc(client, &url, &api_key, "health", json!({})).await?;
+        if status.is_success() {
+            Ok(format!("reachable ({})", status))
+        } else {
+            Err(format!("responded with {}", status))
+        }
+    };
+
+    match probe.await {
+        Ok(detail) => Check::pass(detail),
+        Err(e) => Check::fail(e),
+    }
+}
+
+async fn check_ogmios(client: &Client) -> Check {
+    let probe = async {
+        let url = env("OGMIOS_ENDPOINT")?;
+        let api_key = env("DMTR_API_KEY_OGMIOS")?;
+        let (status, body) = json_rpc(client, &url, &api_key, "queryNetwork/tip", json!({})).await?;
+        match body.get("result") {
+            Some(tip) => Ok(format!("tip at slot {}", tip["slot"])),
+            None => Err(format!("responded with {}: {}", status, body)),
+        }
+    };
+
+    match probe.await {
+        Ok(detail) => Check::pass(detail),
+        Err(e) => Check::fail(e),
+    }
+}
+
+// The settings UTxO is referenced by every bounty transaction, so the
+// server cannot build anything once it has been spent. Without a valid
+// `SETTINGS_REF` the server is not ready either.
+async fn check_settings_utxo(client: &Client) -> Check {
+    let probe = async {
+        let settings_ref = &tx_check::config()?.settings_ref;
+        let url = env("OGMIOS_ENDPOINT")?;
+        let api_key = env("DMTR_API_KEY_OGMIOS")?;
+        let params = json!({
+            "outputReferences": [{
+                "transaction": { "id": settings_ref.tx_hash.as_str() },
+                "index": settings_ref.index
+            }]
+        });
+        let (status, body) =
+            json_rpc(client, &url, &api_key, "queryLedgerState/utxo", params).await?;
+
+        match body.get("result").and_then(Value::as_array) {
+            Some(utxos) if !utxos.is_empty() => Ok(format!("{} is unspent", settings_ref)),
+            Some(_) => Err(format!("{} is spent or does not exist", settings_ref)),
+            None => Err(format!("responded with {}: {}", status, body)),
+        }
+    };
+
+    match probe.await {
+        Ok(detail) => Check::pass(detail),
+        Err(e) => Check::fail(e),
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct Health {
+    pub status: &'static str,
+}
+
+pub async fn healthz() -> Json<Health> {
+    Json(Health { status: "ok" })
+}
+
+pub async fn readyz() -> (StatusCode, Json<Readiness>) {
+    let client = Client::new();
+    let (trp, ogmios, settings_utxo) = tokio::join!(
+        check_trp(&client),
+        check_ogmios(&client),
+        check_settings_utxo(&client)
+    );
+
+    let ready = trp.ok && ogmios.ok && settings_utxo.ok;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(Readiness {
+            ready,
+            trp,
+            ogmios,
+            settings_utxo,
+        }),
+    )
+}
@@ -0,0 +1,269 @@
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::auth::Principal;
+use crate::time::now_posix_ms;
+
+// Bodies are buffered to compare retries with the original request
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+
+enum Outcome {
+    // The first request with this key is still being built
+    InFlight,
+    Done {
+        status: StatusCode,
+        content_type: Option<HeaderValue>,
+        body: Vec<u8>,
+    },
+}
+
+struct Entry {
+    body_hash: [u8; 32],
+    expires_ms: u64,
+    outcome: Outcome,
+}
+
+static ENTRIES: Lazy<Mutex<HashMap<String, Entry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// How long a key is remembered, from `IDEMPOTENCY_TTL_SECS`
+static TTL_MS: Lazy<u64> = Lazy::new(|| {
+    std::env::var("IDEMPOTENCY_TTL_SECS")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .unwrap_or(DEFAULT_TTL_SECS)
+        * 1000
+});
+
+// Frees an in-flight key unless the response was cached, including when the
+// request future is dropped because the client went away
+struct InFlightGuard {
+    cache_key: Option<String>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if let Some(cache_key) = self.cache_key.take() {
+            ENTRIES.lock().unwrap().remove(&cache_key);
+        }
+    }
+}
+
+fn conflict(reason: &str) -> Response {
+    tracing::warn!(%reason, "Rejected idempotent request");
+    (StatusCode::CONFLICT, Json(Err::<(), String>(reason.to_string()))).into_response()
+}
+
+fn replay(status: StatusCode, content_type: Option<HeaderValue>, body: Vec<u8>) -> Response {
+    let mut response = (status, body).into_response();
+    if let Some(content_type) = content_type {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert("Idempotent-Replayed", HeaderValue::from_static("true"));
+    response
+}
+
+// Route layer replaying the first response for requests that repeat an
+// `Idempotency-Key`, so retries return the same transaction instead of
+// building a new one that spends different UTxOs.
+pub async fn idempotent(req: Request, next: Next) -> Response {
+    let Some(key) = req
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+    else {
+        return next.run(req).await;
+    };
+
+    // Keys are scoped to the caller and route so clients cannot collide
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let caller = req
+        .extensions()
+        .get::<Principal>()
+        .map(|principal| principal.name.clone())
+        .unwrap_or_default();
+    let cache_key = format!("{}:{}:{}", caller, route, key);
+
+    let (parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(Err::<(), String>(format!("Invalid body: {}", e))))
+                .into_response()
+        }
+    };
+    let body_hash: [u8; 32] = Sha256::digest(&body).into();
+    let now = now_posix_ms();
+
+    {
+        let mut entries = ENTRIES.lock().unwrap();
+        entries.retain(|_, entry| entry.expires_ms > now);
+
+        match entries.get(&cache_key) {
+            Some(entry) if entry.body_hash != body_hash => {
+                return conflict("Idempotency-Key was already used with a different request body");
+            }
+            Some(Entry {
+                outcome: Outcome::InFlight,
+                ..
+            }) => {
+                return conflict("A request with this Idempotency-Key is still being processed");
+            }
+            Some(Entry {
+                outcome:
+                    Outcome::Done {
+                        status,
+                        content_type,
+                        body,
+                    },
+                ..
+            }) => {
+                tracing::info!(idempotency_key = %key, "Replaying cached response");
+                return replay(*status, content_type.clone(), body.clone());
+            }
+            None => {
+                entries.insert(
+                    cache_key.clone(),
+                    Entry {
+                        body_hash,
+                        expires_ms: now + *TTL_MS,
+                        outcome: Outcome::InFlight,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut guard = InFlightGuard {
+        cache_key: Some(cache_key.clone()),
+    };
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Only built results (including build errors) are cached; anything else
+    // frees the key so the client can retry
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(Err::<(), String>(format!("Failed to read response: {}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(entry) = ENTRIES.lock().unwrap().get_mut(&cache_key) {
+        entry.outcome = Outcome::Done {
+            status: parts.status,
+            content_type: parts.headers.get(header::CONTENT_TYPE).cloned(),
+            body: body.to_vec(),
+        };
+    }
+    guard.cache_key = None;
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::Service;
+
+    static BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+    // Counts builds, failing with 503 when the body asks to
+    async fn build(body: String) -> Response {
+        let count = BUILDS.fetch_add(1, Ordering::SeqCst);
+        if body == "unavailable" {
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+        Json(Ok::<usize, String>(count)).into_response()
+    }
+
+    async fn send(key: &str, body: &'static str) -> (StatusCode, bool, String) {
+        let mut app = Router::new()
+            .route("/build", post(build))
+            .route_layer(middleware::from_fn(idempotent));
+        let request = Request::post("/build")
+            .header("Idempotency-Key", key)
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.call(request).await.unwrap();
+        let status = response.status();
+        let replayed = response.headers().contains_key("Idempotent-Replayed");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn replays_the_first_response() {
+        let (status, replayed, first) = send("replay", "a").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!replayed);
+
+        let (status, replayed, retry) = send("replay", "a").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(replayed);
+        assert_eq!(retry, first);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_reused_key_with_another_body() {
+        send("conflict", "a").await;
+        let (status, _, body) = send("conflict", "b").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body.contains("different request body"));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_key_still_in_flight() {
+        let body_hash: [u8; 32] = Sha256::digest(b"a").into();
+        ENTRIES.lock().unwrap().insert(
+            ":/build:in-flight".to_string(),
+            Entry {
+                body_hash,
+                expires_ms: u64::MAX,
+                outcome: Outcome::InFlight,
+            },
+        );
+        let (status, _, body) = send("in-flight", "a").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body.contains("still being processed"));
+    }
+
+    #[tokio::test]
+    async fn failed_requests_free_the_key() {
+        let (status, _, _) = send("retry", "unavailable").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!ENTRIES.lock().unwrap().contains_key(":/build:retry"));
+
+        let (status, replayed, _) = send("retry", "unavailable").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!replayed);
+    }
+}
@@ -1,20 +1,64 @@
 use axum::Router;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
-use dotenvy;
+use tracing_subscriber::EnvFilter;
 
 mod routes;
 mod evaluate_tx;
+mod bounty_id;
+mod github;
+mod time;
+mod openapi;
+mod request_id;
+mod metrics;
+mod health;
+mod auth;
+mod rate_limit;
+mod idempotency;
+mod tx_check;
+mod preview;
+mod batch;
+mod pending;
+mod coin_control;
+mod params;
+mod resolve;
 
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let checked = time::network_slots()
+        .and(tx_check::config().map(|_| ()))
+        .and(auth::check_config())
+        .and(github::check_config())
+        .and(rate_limit::check_config());
+    if let Err(e) = checked {
+        tracing::error!(error = %e, "Invalid configuration");
+        std::process::exit(1);
+    }
+
     let app: Router = routes::router();
+    rate_limit::spawn_snapshots();
+    pending::spawn_confirmations();
 
     let listener = TcpListener::bind("127.0.0.1:8080")
         .await
         .unwrap();
 
-    println!("Server listening on http://127.0.0.1:8080");
+    tracing::info!("Server listening on http://127.0.0.1:8080");
 
-    axum::serve(listener, app).await.unwrap();
+    // Peer addresses are needed for per-IP rate limits
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
\ No newline at end of file
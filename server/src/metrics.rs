@@ -0,0 +1,224 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, Encoder,
+    HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+};
+use std::future::Future;
+use std::time::Instant;
+
+static HTTP_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "http_requests_total",
+        "HTTP requests handled, by route and status",
+        &["route", "status"]
+    )
+    .unwrap()
+});
+
+static HTTP_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "http_request_duration_seconds",
+        "HTTP request latency, by route and status",
+        &["route", "status"]
+    )
+    .unwrap()
+});
+
+static HTTP_IN_FLIGHT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "http_requests_in_flight",
+        "HTTP requests currently being handled, by route",
+        &["route"]
+    )
+    .unwrap()
+});
+
+static TX_BUILDS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "tx_builds_total",
+        "Transaction build requests, by transaction and outcome",
+        &["tx", "outcome"]
+    )
+    .unwrap()
+});
+
+static TRP_RESOLVE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "trp_resolve_duration_seconds",
+        "TRP resolve latency, by transaction and outcome",
+        &["tx", "outcome"]
+    )
+    .unwrap()
+});
+
+static OGMIOS_EVALUATE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "ogmios_evaluate_duration_seconds",
+        "Ogmios evaluateTransaction latency, by outcome",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+static SCRIPT_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "script_failures_total",
+        "Scripts rejected by Ogmios evaluation, by redeemer purpose and index",
+        &["redeemer"]
+    )
+    .unwrap()
+});
+
+// Outcome of a transaction build request, as reported in `tx_builds_total`
+#[derive(Clone, Copy, Debug)]
+pub enum BuildOutcome {
+    Ok,
+    InvalidParams,
+    ResolveError,
+    CheckFailed,
+    EvaluationFailed,
+}
+
+impl BuildOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            BuildOutcome::Ok => "ok",
+            BuildOutcome::InvalidParams => "invalid_params",
+            BuildOutcome::ResolveError => "resolve_error",
+            BuildOutcome::CheckFailed => "check_failed",
+            BuildOutcome::EvaluationFailed => "evaluation_failed",
+        }
+    }
+}
+
+pub fn record_build(tx: &str, outcome: BuildOutcome) {
+    TX_BUILDS.with_label_values(&[tx, outcome.label()]).inc();
+}
+
+fn outcome_label<T, E>(result: &Result<T, E>) -> &'static str {
+    match result {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    }
+}
+
+// Times a TRP resolve call for `tx`
+pub async fn time_resolve<T, E>(tx: &str, resolve: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let started = Instant::now();
+    let result = resolve.await;
+    TRP_RESOLVE_DURATION
+        .with_label_values(&[tx, outcome_label(&result)])
+        .observe(started.elapsed().as_secs_f64());
+    result
+}
+
+// Records how long an Ogmios evaluation took: "ok", "script_failure" or "error"
+pub fn observe_evaluation(started: Instant, outcome: &str) {
+    OGMIOS_EVALUATE_DURATION
+        .with_label_values(&[outcome])
+        .observe(started.elapsed().as_secs_f64());
+}
+
+// Counts the failing redeemers of an Ogmios `evaluateTransaction` error response
+pub fn record_script_failures(response: &str) {
+    let failures = serde_json::from_str::<serde_json::Value>(response)
+        .ok()
+        .and_then(|response| response["error"]["data"].as_array().cloned())
+        .unwrap_or_default();
+
+    if failures.is_empty() {
+        SCRIPT_FAILURES.with_label_values(&["unknown"]).inc();
+        return;
+    }
+
+    for failure in failures {
+        let validator = &failure["validator"];
+        let redeemer = match (validator["purpose"].as_str(), validator["index"].as_u64()) {
+            (Some(purpose), Some(index)) => format!("{}:{}", purpose, index),
+            _ => "unknown".to_string(),
+        };
+        SCRIPT_FAILURES.with_label_values(&[&redeemer]).inc();
+    }
+}
+
+// Decrements the in-flight gauge when dropped, so a cancelled request
+// (client gone, handler panic) is not counted as in flight forever
+struct InFlight(IntGauge);
+
+impl InFlight {
+    fn start(gauge: IntGauge) -> InFlight {
+        gauge.inc();
+        InFlight(gauge)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+// Records latency, status and in-flight count for every matched route
+pub async fn track(req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let in_flight = InFlight::start(HTTP_IN_FLIGHT.with_label_values(&[&route]));
+    let started = Instant::now();
+
+    let response = next.run(req).await;
+
+    drop(in_flight);
+    let status = response.status().as_u16().to_string();
+    HTTP_REQUESTS.with_label_values(&[&route, &status]).inc();
+    HTTP_DURATION
+        .with_label_values(&[&route, &status])
+        .observe(started.elapsed().as_secs_f64());
+
+    response
+}
+
+pub async fn metrics_route() -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+
+    ([(header::CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancelled_requests_leave_in_flight() {
+        let gauge = IntGauge::new("test_in_flight", "Test gauge").unwrap();
+
+        let request = tokio::spawn({
+            let gauge = gauge.clone();
+            async move {
+                let _in_flight = InFlight::start(gauge);
+                std::future::pending::<()>().await
+            }
+        });
+        while gauge.get() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        request.abort();
+        let _ = request.await;
+        assert_eq!(gauge.get(), 0);
+    }
+}
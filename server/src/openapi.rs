@@ -0,0 +1,469 @@
+use axum::Json;
+use once_cell::sync::Lazy;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::params::{AddParams, CloseParams, CreateWithLovelaceParams, DeployParams};
+
+use crate::batch::{AddFundsBatchRequest, CreateBountiesRequest};
+use crate::bounty_id::{DeriveBountyIdRequest, DeriveBountyIdResponse};
+use crate::coin_control::CoinControl;
+use crate::github::PendingAction;
+use crate::health::{Health, Readiness};
+use crate::pending::{SubmitRequest, SubmittedResponse, WebhookEvent};
+use crate::preview::{PreviewRequest, TxPreview};
+
+// `tx3_sdk::trp::TxEnvelope` as serialized in responses. The SDK type has no
+// schema, so this mirrors it; a test keeps the two in sync.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "TxEnvelope")]
+struct TxEnvelopeSchema {
+    // Hex-encoded transaction hash
+    hash: String,
+    // Hex-encoded unsigned transaction CBOR
+    tx: String,
+}
+
+// `Err` as serialized by the `Json<Result<_, String>>` the middleware and
+// routes answer with. Validation errors read "Invalid field `path`: reason".
+#[derive(Serialize, JsonSchema)]
+struct ErrorResponse {
+    #[serde(rename = "Err")]
+    err: String,
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+// Registers `T` in the generator and returns a reference to it
+fn component<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    serde_json::to_value(gen.subschema_for::<T>()).unwrap()
+}
+
+// Adds the optional coin control fields to a request schema
+fn with_coin_control(gen: &mut SchemaGenerator, request: Value) -> Value {
+    json!({ "allOf": [request, component::<CoinControl>(gen)] })
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+// Any one of the supported credentials; see `auth.rs` for the required scopes
+fn security() -> Value {
+    json!([{ "apiKey": [] }, { "bearer": [] }, { "workerSignature": [] }])
+}
+
+// An operation behind `auth::require`, `rate_limit::limit` and `idempotency::idempotent`
+fn authed(summary: &str, request: Option<Value>, description: &str, response: Value) -> Value {
+    let mut operation = json!({
+        "summary": summary,
+        "parameters": [{
+            "name": "Idempotency-Key",
+            "in": "header",
+            "required": false,
+            "description": "Repeats with the same key and body replay the first response",
+            "schema": { "type": "string" }
+        }],
+        "responses": {
+            "200": { "description": description, "content": json_content(response) },
+            "400": { "$ref": "#/components/responses/MalformedBody" },
+            "401": { "$ref": "#/components/responses/Unauthorized" },
+            "403": { "$ref": "#/components/responses/Forbidden" },
+            "409": {
+                "description": "The Idempotency-Key was reused with a different body or is still in flight",
+                "content": json_content(schema_ref("ErrorResponse"))
+            },
+            "415": { "$ref": "#/components/responses/MalformedBody" },
+            "422": { "$ref": "#/components/responses/MalformedBody" },
+            "429": { "$ref": "#/components/responses/TooManyRequests" }
+        },
+        "security": security()
+    });
+    if let Some(request) = request {
+        operation["requestBody"] = json!({ "required": true, "content": json_content(request) });
+    }
+    operation
+}
+
+fn tx_route(gen: &mut SchemaGenerator, summary: &str, request: Value) -> Value {
+    let response = component::<Result<TxEnvelopeSchema, String>>(gen);
+    json!({
+        "post": authed(
+            summary,
+            Some(request),
+            "The unsigned transaction, or the reason it could not be built",
+            response
+        )
+    })
+}
+
+// Like `tx_route`, but responding with an ordered list of transactions
+fn batch_route(gen: &mut SchemaGenerator, summary: &str, request: Value) -> Value {
+    let response = component::<Result<Vec<TxEnvelopeSchema>, String>>(gen);
+    json!({
+        "post": authed(
+            summary,
+            Some(request),
+            "The unsigned transactions in submission order, or the reason the batch could not be built",
+            response
+        )
+    })
+}
+
+// A webhook authenticated by a signature header instead of `auth::require`
+fn webhook_route(summary: &str, headers: &[(&str, &str)], request: Value, response: Value) -> Value {
+    let parameters: Vec<Value> = headers
+        .iter()
+        .map(|(name, description)| {
+            json!({
+                "name": name,
+                "in": "header",
+                "required": true,
+                "description": description,
+                "schema": { "type": "string" }
+            })
+        })
+        .collect();
+    json!({
+        "post": {
+            "summary": summary,
+            "parameters": parameters,
+            "requestBody": { "required": true, "content": json_content(request) },
+            "responses": {
+                "200": { "description": "The delivery was handled", "content": json_content(response.clone()) },
+                "400": { "description": "The delivery could not be parsed", "content": json_content(response.clone()) },
+                "401": { "description": "The signature is missing or invalid", "content": json_content(response.clone()) },
+                "503": { "description": "Deliveries are not configured on this server", "content": json_content(response) }
+            }
+        }
+    })
+}
+
+fn build_spec() -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+
+    let deploy = component::<DeployParams>(&mut gen);
+    let deploy = with_coin_control(&mut gen, deploy);
+    let create = component::<CreateWithLovelaceParams>(&mut gen);
+    let create = with_coin_control(&mut gen, create);
+    let add = component::<AddParams>(&mut gen);
+    let add = with_coin_control(&mut gen, add);
+    let close = component::<CloseParams>(&mut gen);
+    let close = with_coin_control(&mut gen, close);
+    let create_batch = component::<CreateBountiesRequest>(&mut gen);
+    let add_batch = component::<AddFundsBatchRequest>(&mut gen);
+    let derive_request = component::<DeriveBountyIdRequest>(&mut gen);
+    let derive_response = component::<DeriveBountyIdResponse>(&mut gen);
+    let preview_request = component::<PreviewRequest>(&mut gen);
+    let preview_response = component::<Result<TxPreview, String>>(&mut gen);
+    let submit_request = component::<SubmitRequest>(&mut gen);
+    let submitted_response = component::<Result<SubmittedResponse, String>>(&mut gen);
+    let pending_actions = component::<Vec<PendingAction>>(&mut gen);
+    let github_response = component::<Result<Option<PendingAction>, String>>(&mut gen);
+    let worker_event = component::<WebhookEvent>(&mut gen);
+    let worker_response = component::<Result<bool, String>>(&mut gen);
+    let health = component::<Health>(&mut gen);
+    let readiness = component::<Readiness>(&mut gen);
+    gen.subschema_for::<ErrorResponse>();
+
+    let paths = json!({
+        "/deploy-settings": tx_route(&mut gen, "Build the settings deployment transaction", deploy),
+        "/create-bounty": tx_route(&mut gen, "Build a bounty creation transaction", create),
+        "/add-funds": tx_route(&mut gen, "Build a transaction adding funds to a bounty", add),
+        "/close-bounty": tx_route(
+            &mut gen,
+            "Build the admin transaction closing a bounty whose deadline passed unmerged",
+            close
+        ),
+        "/create-bounties": batch_route(
+            &mut gen,
+            "Build a chain of bounty creation transactions, to be signed and submitted in order",
+            create_batch
+        ),
+        "/add-funds-batch": tx_route(
+            &mut gen,
+            "Build a single transaction adding funds to up to 3 bounties from one sponsor",
+            add_batch
+        ),
+        "/derive-bounty-id": {
+            "post": authed(
+                "Derive the canonical bounty id of a GitHub issue",
+                Some(derive_request),
+                "The hex-encoded bounty id",
+                derive_response
+            )
+        },
+        "/preview": {
+            "post": authed(
+                "Decode a built transaction into a reviewable summary",
+                Some(preview_request),
+                "The decoded transaction, or the reason it could not be decoded",
+                preview_response
+            )
+        },
+        "/submit": {
+            "post": authed(
+                "Submit a signed transaction built here for the caller, so follow-up builds can be evaluated against its outputs until it confirms",
+                Some(submit_request),
+                "The outputs kept until confirmation, or the reason the transaction was not submitted",
+                submitted_response
+            )
+        },
+        "/github-webhook/pending": {
+            "get": authed(
+                "List bounty actions derived from GitHub events, waiting for the maintainer to sign them",
+                None,
+                "Pending actions, oldest first",
+                pending_actions
+            )
+        },
+        "/github-webhook": webhook_route(
+            "Receive a GitHub `issues` or `pull_request` delivery",
+            &[
+                ("X-Hub-Signature-256", "\"sha256=\" + hex HMAC-SHA256 of the body with the bridge webhook secret"),
+                ("X-GitHub-Event", "The GitHub event name"),
+                ("X-GitHub-Delivery", "The GitHub delivery id; redeliveries are acknowledged and ignored")
+            ],
+            json!({ "type": "object", "description": "A GitHub webhook payload" }),
+            github_response
+        ),
+        "/tx-confirmed": webhook_route(
+            "Receive the worker's `tx-confirmed` webhook and drop the transaction from the pending overlay",
+            &[(
+                "X-Githoney-Signature",
+                "\"t=<unix secs>,v1=<hex HMAC-SHA256 of t + \".\" + body>\" with the worker's webhook secret"
+            )],
+            worker_event,
+            worker_response
+        ),
+        "/openapi.json": {
+            "get": {
+                "summary": "This document",
+                "responses": {
+                    "200": { "description": "The OpenAPI 3.0 spec", "content": json_content(json!({ "type": "object" })) }
+                }
+            }
+        },
+        "/metrics": {
+            "get": {
+                "summary": "Prometheus metrics",
+                "responses": {
+                    "200": {
+                        "description": "Metrics in the Prometheus text format",
+                        "content": { "text/plain": { "schema": { "type": "string" } } }
+                    }
+                }
+            }
+        },
+        "/healthz": {
+            "get": {
+                "summary": "Liveness probe",
+                "responses": {
+                    "200": { "description": "The server is running", "content": json_content(health) }
+                }
+            }
+        },
+        "/readyz": {
+            "get": {
+                "summary": "Readiness probe, checking TRP, Ogmios and the settings UTxO",
+                "responses": {
+                    "200": { "description": "Every check passed", "content": json_content(readiness.clone()) },
+                    "503": { "description": "At least one check failed", "content": json_content(readiness) }
+                }
+            }
+        }
+    });
+
+    // Same post-processing as `into_root_schema_for`, e.g. no siblings next to `$ref`
+    let mut definitions = gen.take_definitions();
+    for visitor in gen.visitors_mut() {
+        for schema in definitions.values_mut() {
+            visitor.visit_schema(schema);
+        }
+    }
+    let schemas: serde_json::Map<String, Value> = definitions
+        .into_iter()
+        .map(|(name, schema)| (name, serde_json::to_value(schema).unwrap()))
+        .collect();
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Githoney tx builder",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "responses": {
+                "MalformedBody": {
+                    "description": "The body is not JSON or does not match the expected shape",
+                    "content": { "text/plain": { "schema": { "type": "string" } } }
+                },
+                "Unauthorized": {
+                    "description": "Missing or invalid credentials",
+                    "content": json_content(schema_ref("ErrorResponse"))
+                },
+                "Forbidden": {
+                    "description": "The credentials lack the scope this route requires",
+                    "content": json_content(schema_ref("ErrorResponse"))
+                },
+                "TooManyRequests": {
+                    "description": "The caller's rate limit for this route is exhausted",
+                    "headers": {
+                        "Retry-After": {
+                            "description": "Seconds until a request will be accepted",
+                            "schema": { "type": "integer" }
+                        }
+                    },
+                    "content": json_content(schema_ref("ErrorResponse"))
+                }
+            },
+            "securitySchemes": {
+                "apiKey": { "type": "apiKey", "in": "header", "name": "X-Api-Key" },
+                "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "workerSignature": {
+                    "type": "apiKey",
+                    "in": "header",
+                    "name": "X-Worker-Signature",
+                    "description": "Hex HMAC-SHA256 of `X-Worker-Timestamp` + \".\" + `X-Worker-Nonce` + \".\" + body; each nonce is accepted once"
+                }
+            }
+        }
+    })
+}
+
+static SPEC: Lazy<Value> = Lazy::new(build_spec);
+
+pub async fn openapi_spec() -> Json<Value> {
+    Json(SPEC.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes;
+
+    fn schemas() -> &'static serde_json::Map<String, Value> {
+        SPEC["components"]["schemas"].as_object().unwrap()
+    }
+
+    // `T` and everything it references, generated on their own
+    fn assert_component<T: JsonSchema>() {
+        let mut root = SchemaSettings::openapi3()
+            .into_generator()
+            .into_root_schema_for::<T>();
+        // Only a root schema gets a title
+        root.schema.metadata().title = None;
+        let mut expected = vec![(T::schema_name(), serde_json::to_value(&root.schema).unwrap())];
+        for (name, schema) in root.definitions {
+            expected.push((name, serde_json::to_value(schema).unwrap()));
+        }
+
+        for (name, schema) in expected {
+            assert_eq!(
+                schemas().get(&name),
+                Some(&schema),
+                "component {} differs from its Rust type",
+                name
+            );
+        }
+    }
+
+    fn refs<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    found.push(target);
+                }
+                map.values().for_each(|value| refs(value, found));
+            }
+            Value::Array(values) => values.iter().for_each(|value| refs(value, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_route_is_documented() {
+        let paths = SPEC["paths"].as_object().unwrap();
+        for path in routes::paths() {
+            assert!(paths.contains_key(path), "{} is missing from the spec", path);
+        }
+        assert_eq!(paths.len(), routes::paths().len(), "the spec documents a path the router does not serve");
+    }
+
+    #[test]
+    fn request_components_match_their_types() {
+        assert_component::<DeployParams>();
+        assert_component::<CreateWithLovelaceParams>();
+        assert_component::<AddParams>();
+        assert_component::<CloseParams>();
+        assert_component::<CoinControl>();
+        assert_component::<CreateBountiesRequest>();
+        assert_component::<AddFundsBatchRequest>();
+        assert_component::<DeriveBountyIdRequest>();
+        assert_component::<PreviewRequest>();
+        assert_component::<SubmitRequest>();
+        assert_component::<WebhookEvent>();
+    }
+
+    #[test]
+    fn response_components_match_their_types() {
+        assert_component::<Result<TxEnvelopeSchema, String>>();
+        assert_component::<Result<Vec<TxEnvelopeSchema>, String>>();
+        assert_component::<DeriveBountyIdResponse>();
+        assert_component::<Result<TxPreview, String>>();
+        assert_component::<Result<SubmittedResponse, String>>();
+        assert_component::<Result<Option<PendingAction>, String>>();
+        assert_component::<Result<bool, String>>();
+        assert_component::<Health>();
+        assert_component::<Readiness>();
+        assert_component::<ErrorResponse>();
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let mut found = Vec::new();
+        refs(&SPEC, &mut found);
+        for target in found {
+            let resolved = target
+                .strip_prefix("#/")
+                .unwrap()
+                .split('/')
+                .try_fold(&*SPEC, |value, key| value.get(key));
+            assert!(resolved.is_some(), "{} does not resolve", target);
+        }
+    }
+
+    #[test]
+    fn tx_envelope_schema_matches_the_sdk() {
+        let envelope = tx3_sdk::trp::TxEnvelope {
+            hash: "ab".to_string(),
+            tx: "cd".to_string(),
+        };
+        let mirror = TxEnvelopeSchema {
+            hash: "ab".to_string(),
+            tx: "cd".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&envelope).unwrap(),
+            serde_json::to_value(&mirror).unwrap()
+        );
+    }
+
+    #[test]
+    fn error_response_matches_err() {
+        let err = Json(Err::<(), String>("Invalid field `tx`: odd length".to_string()));
+        let response = ErrorResponse {
+            err: "Invalid field `tx`: odd length".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&err.0).unwrap(),
+            serde_json::to_value(&response).unwrap()
+        );
+    }
+}
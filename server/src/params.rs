@@ -0,0 +1,375 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use githoney_common::time::TimeValue;
+use githoney_common::types::{Address, AssetName, BountyId, HexString, KeyHash, PolicyId, UtxoRef};
+
+// Validated request bodies for the build routes. The generated protocol client
+// takes every argument as a string, so these are parsed first and converted
+// with `into()` right before resolving.
+//
+// Time fields are parsed after `time::normalize_request_times` has turned them
+// into numbers, but their schema is the optional `TimeInput` a request may send.
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeployParams {
+    pub creation_fee: u64,
+    pub githoney_payment_credential: KeyHash,
+    pub githoney_script: HexString,
+    pub githoney_staking_credential: KeyHash,
+    pub githoneyaddr: Address,
+    pub reward_fee: u64,
+    pub script: Address,
+    pub script_version: u64,
+    pub settings_minting_policy: HexString,
+    pub settings_minting_version: u64,
+    pub settings_policy_id: PolicyId,
+    pub settings_token_name: AssetName,
+    pub utxo_ref: UtxoRef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateWithLovelaceParams {
+    pub admin_payment_key: KeyHash,
+    pub bounty_creation_fee: u64,
+    pub bounty_id: BountyId,
+    pub bounty_rewards_fee: u64,
+    pub githoneyaddr: Address,
+    pub maintainer: Address,
+    pub maintainer_payment_key: KeyHash,
+    pub maintainer_stake_key: KeyHash,
+    pub min_ada: u64,
+    pub minting_policy_id: PolicyId,
+    pub reward_amount: u64,
+    pub script: Address,
+    pub settings_ref: UtxoRef,
+    #[schemars(with = "Option<TimeValue>")]
+    pub since: u64,
+    #[schemars(with = "Option<TimeValue>")]
+    pub time_limit: u64,
+    #[schemars(with = "Option<TimeValue>")]
+    pub until: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AddParams {
+    pub bountyref: UtxoRef,
+    pub rewardamount: u64,
+    pub rewardassetname: AssetName,
+    pub rewardpolicyid: HexString,
+    pub script: Address,
+    pub settingsref: UtxoRef,
+    #[schemars(with = "Option<TimeValue>")]
+    pub since: u64,
+    pub sponsor: Address,
+    #[schemars(with = "Option<TimeValue>")]
+    pub until: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CloseParams {
+    pub admin: Address,
+    pub bounty_id: BountyId,
+    pub bountyref: UtxoRef,
+    // Set once a contributor is assigned, selecting `closeAfterContributor`
+    #[serde(default)]
+    pub contributor: Option<ContributorRefund>,
+    pub maintainer: Address,
+    pub minting_policy_id: PolicyId,
+    pub settingsref: UtxoRef,
+    #[schemars(with = "Option<TimeValue>")]
+    pub since: u64,
+    #[schemars(with = "Option<TimeValue>")]
+    pub until: u64,
+}
+
+// The contributor of an assigned bounty and the lovelace it deposited on
+// assignment, which closing the bounty pays back
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContributorRefund {
+    pub address: Address,
+    pub deposit: u64,
+}
+
+// Parameters of the transactions the GitHub bridge builds for pull request
+// events; these have no route of their own
+
+#[derive(Debug, Clone)]
+pub struct AssignParams {
+    pub bountyref: UtxoRef,
+    pub contributor: Address,
+    pub contributor_payment_key: KeyHash,
+    pub contributor_stake_key: KeyHash,
+    pub min_ada: u64,
+    pub script: Address,
+    pub settingsref: UtxoRef,
+    pub since: u64,
+    pub until: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeParams {
+    pub admin: Address,
+    pub bountyref: UtxoRef,
+    pub githoney_fee: u64,
+    pub githoneyaddr: Address,
+    pub maintainer: Address,
+    pub min_ada: u64,
+    pub script: Address,
+    pub settingsref: UtxoRef,
+    pub since: u64,
+    pub until: u64,
+}
+
+impl From<DeployParams> for protocol::DeployParams {
+    fn from(params: DeployParams) -> Self {
+        protocol::DeployParams {
+            creation_fee: params.creation_fee.to_string(),
+            githoney_payment_credential: params.githoney_payment_credential.to_string(),
+            githoney_script: params.githoney_script.to_string(),
+            githoney_staking_credential: params.githoney_staking_credential.to_string(),
+            githoneyaddr: params.githoneyaddr.to_string(),
+            reward_fee: params.reward_fee.to_string(),
+            script: params.script.to_string(),
+            script_version: params.script_version.to_string(),
+            settings_minting_policy: params.settings_minting_policy.to_string(),
+            settings_minting_version: params.settings_minting_version.to_string(),
+            settings_policy_id: params.settings_policy_id.to_string(),
+            settings_token_name: params.settings_token_name.to_string(),
+            utxo_ref: params.utxo_ref.to_string(),
+        }
+    }
+}
+
+impl From<CreateWithLovelaceParams> for protocol::CreateWithLovelaceParams {
+    fn from(params: CreateWithLovelaceParams) -> Self {
+        protocol::CreateWithLovelaceParams {
+            admin_payment_key: params.admin_payment_key.to_string(),
+            bounty_creation_fee: params.bounty_creation_fee.to_string(),
+            bounty_id: params.bounty_id.to_string(),
+            bounty_rewards_fee: params.bounty_rewards_fee.to_string(),
+            githoneyaddr: params.githoneyaddr.to_string(),
+            maintainer: params.maintainer.to_string(),
+            maintainer_payment_key: params.maintainer_payment_key.to_string(),
+            maintainer_stake_key: params.maintainer_stake_key.to_string(),
+            min_ada: params.min_ada.to_string(),
+            minting_policy_id: params.minting_policy_id.to_string(),
+            reward_amount: params.reward_amount.to_string(),
+            script: params.script.to_string(),
+            settings_ref: params.settings_ref.to_string(),
+            since: params.since.to_string(),
+            time_limit: params.time_limit.to_string(),
+            until: params.until.to_string(),
+        }
+    }
+}
+
+impl From<AddParams> for protocol::AddParams {
+    fn from(params: AddParams) -> Self {
+        protocol::AddParams {
+            bountyref: params.bountyref.to_string(),
+            rewardamount: params.rewardamount.to_string(),
+            rewardassetname: params.rewardassetname.to_string(),
+            rewardpolicyid: params.rewardpolicyid.to_string(),
+            script: params.script.to_string(),
+            settingsref: params.settingsref.to_string(),
+            since: params.since.to_string(),
+            sponsor: params.sponsor.to_string(),
+            until: params.until.to_string(),
+        }
+    }
+}
+
+// Top-ups of a batch share `script`, `settingsref`, `since`, `until` and
+// `sponsor`, so these come from the first one
+pub fn add2_params([first, second]: [AddParams; 2]) -> protocol::Add2Params {
+    protocol::Add2Params {
+        bountyref1: first.bountyref.to_string(),
+        bountyref2: second.bountyref.to_string(),
+        rewardamount1: first.rewardamount.to_string(),
+        rewardamount2: second.rewardamount.to_string(),
+        rewardassetname1: first.rewardassetname.to_string(),
+        rewardassetname2: second.rewardassetname.to_string(),
+        rewardpolicyid1: first.rewardpolicyid.to_string(),
+        rewardpolicyid2: second.rewardpolicyid.to_string(),
+        script: first.script.to_string(),
+        settingsref: first.settingsref.to_string(),
+        since: first.since.to_string(),
+        sponsor: first.sponsor.to_string(),
+        until: first.until.to_string(),
+    }
+}
+
+pub fn add3_params([first, second, third]: [AddParams; 3]) -> protocol::Add3Params {
+    protocol::Add3Params {
+        bountyref1: first.bountyref.to_string(),
+        bountyref2: second.bountyref.to_string(),
+        bountyref3: third.bountyref.to_string(),
+        rewardamount1: first.rewardamount.to_string(),
+        rewardamount2: second.rewardamount.to_string(),
+        rewardamount3: third.rewardamount.to_string(),
+        rewardassetname1: first.rewardassetname.to_string(),
+        rewardassetname2: second.rewardassetname.to_string(),
+        rewardassetname3: third.rewardassetname.to_string(),
+        rewardpolicyid1: first.rewardpolicyid.to_string(),
+        rewardpolicyid2: second.rewardpolicyid.to_string(),
+        rewardpolicyid3: third.rewardpolicyid.to_string(),
+        script: first.script.to_string(),
+        settingsref: first.settingsref.to_string(),
+        since: first.since.to_string(),
+        sponsor: first.sponsor.to_string(),
+        until: first.until.to_string(),
+    }
+}
+
+// A close transaction, in the variant matching the bounty's assignment
+pub enum CloseTx {
+    BeforeContributor(protocol::CloseBeforeContributorParams),
+    AfterContributor(protocol::CloseAfterContributorParams),
+}
+
+impl From<CloseParams> for CloseTx {
+    fn from(params: CloseParams) -> Self {
+        match params.contributor {
+            None => CloseTx::BeforeContributor(protocol::CloseBeforeContributorParams {
+                admin: params.admin.to_string(),
+                bounty_id: params.bounty_id.to_string(),
+                bountyref: params.bountyref.to_string(),
+                maintainer: params.maintainer.to_string(),
+                minting_policy_id: params.minting_policy_id.to_string(),
+                settingsref: params.settingsref.to_string(),
+                since: params.since.to_string(),
+                until: params.until.to_string(),
+            }),
+            Some(contributor) => CloseTx::AfterContributor(protocol::CloseAfterContributorParams {
+                admin: params.admin.to_string(),
+                bounty_id: params.bounty_id.to_string(),
+                bountyref: params.bountyref.to_string(),
+                contributor: contributor.address.to_string(),
+                contributor_deposit: contributor.deposit.to_string(),
+                maintainer: params.maintainer.to_string(),
+                minting_policy_id: params.minting_policy_id.to_string(),
+                settingsref: params.settingsref.to_string(),
+                since: params.since.to_string(),
+                until: params.until.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<AssignParams> for protocol::AssignParams {
+    fn from(params: AssignParams) -> Self {
+        protocol::AssignParams {
+            bountyref: params.bountyref.to_string(),
+            contributor: params.contributor.to_string(),
+            contributor_payment_key: params.contributor_payment_key.to_string(),
+            contributor_stake_key: params.contributor_stake_key.to_string(),
+            min_ada: params.min_ada.to_string(),
+            script: params.script.to_string(),
+            settingsref: params.settingsref.to_string(),
+            since: params.since.to_string(),
+            until: params.until.to_string(),
+        }
+    }
+}
+
+impl From<MergeParams> for protocol::MergeParams {
+    fn from(params: MergeParams) -> Self {
+        protocol::MergeParams {
+            admin: params.admin.to_string(),
+            bountyref: params.bountyref.to_string(),
+            githoney_fee: params.githoney_fee.to_string(),
+            githoneyaddr: params.githoneyaddr.to_string(),
+            maintainer: params.maintainer.to_string(),
+            min_ada: params.min_ada.to_string(),
+            script: params.script.to_string(),
+            settingsref: params.settingsref.to_string(),
+            since: params.since.to_string(),
+            until: params.until.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tx3_tir::encoding::{from_bytes, TirVersion};
+    use tx3_tir::reduce::Apply;
+
+    fn param_names<P: Serialize>(params: &P) -> Vec<String> {
+        let serde_json::Value::Object(params) = serde_json::to_value(params).unwrap() else {
+            unreachable!()
+        };
+        params.keys().cloned().collect()
+    }
+
+    fn ir_param_names(ir: &str) -> Vec<String> {
+        let tx = from_bytes(&hex::decode(ir).unwrap(), TirVersion::V1Beta0).unwrap();
+        tx.params().into_keys().collect()
+    }
+
+    #[test]
+    fn close_variant_follows_the_contributor() {
+        let address: Address = "addr_test1wr5nj776ygd7d7vwcw3f9fknj4axx0wzy67y3qu8dqtscys7w9q65".parse().unwrap();
+        let params = CloseParams {
+            admin: address.clone(),
+            bounty_id: "01".parse().unwrap(),
+            bountyref: format!("{}#0", "aa".repeat(32)).parse().unwrap(),
+            contributor: None,
+            maintainer: address.clone(),
+            minting_policy_id: "bb".repeat(28).parse().unwrap(),
+            settingsref: format!("{}#0", "cc".repeat(32)).parse().unwrap(),
+            since: 1,
+            until: 2,
+        };
+
+        let CloseTx::BeforeContributor(before) = CloseTx::from(params.clone()) else {
+            panic!("an unassigned bounty closes before contributor");
+        };
+        assert_eq!(param_names(&before), ir_param_names(protocol::CLOSE_BEFORE_CONTRIBUTOR_IR));
+
+        let assigned = CloseParams {
+            contributor: Some(ContributorRefund { address, deposit: 2_000_000 }),
+            ..params
+        };
+        let CloseTx::AfterContributor(after) = CloseTx::from(assigned) else {
+            panic!("an assigned bounty closes after contributor");
+        };
+        assert_eq!(after.contributor_deposit, "2000000");
+        assert_eq!(param_names(&after), ir_param_names(protocol::CLOSE_AFTER_CONTRIBUTOR_IR));
+    }
+
+    #[test]
+    fn bridge_params_match_the_protocol() {
+        let address: Address = "addr_test1wr5nj776ygd7d7vwcw3f9fknj4axx0wzy67y3qu8dqtscys7w9q65".parse().unwrap();
+        let bountyref: UtxoRef = format!("{}#0", "aa".repeat(32)).parse().unwrap();
+        let settingsref: UtxoRef = format!("{}#0", "cc".repeat(32)).parse().unwrap();
+
+        let assign = protocol::AssignParams::from(AssignParams {
+            bountyref: bountyref.clone(),
+            contributor: address.clone(),
+            contributor_payment_key: "dd".repeat(28).parse().unwrap(),
+            contributor_stake_key: "ee".repeat(28).parse().unwrap(),
+            min_ada: 2_000_000,
+            script: address.clone(),
+            settingsref: settingsref.clone(),
+            since: 1,
+            until: 2,
+        });
+        assert_eq!(param_names(&assign), ir_param_names(protocol::ASSIGN_IR));
+
+        let merge = protocol::MergeParams::from(MergeParams {
+            admin: address.clone(),
+            bountyref,
+            githoney_fee: 100_000,
+            githoneyaddr: address.clone(),
+            maintainer: address.clone(),
+            min_ada: 2_000_000,
+            script: address,
+            settingsref,
+            since: 1,
+            until: 2,
+        });
+        assert_eq!(param_names(&merge), ir_param_names(protocol::MERGE_IR));
+    }
+}
@@ -0,0 +1,577 @@
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use pallas_primitives::conway::MintedDatumOption;
+use pallas_traverse::{MultiEraOutput, MultiEraTx};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use reqwest::Client;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tx3_sdk::trp::TxEnvelope;
+
+use crate::auth;
+use crate::health::{env, json_rpc};
+use crate::preview::{address_text, assets, input_ref};
+use crate::resolve::{ogmios_ref, query_utxos};
+use crate::time::{network_slots, now_posix_ms};
+
+// Unconfirmed outputs of transactions that were built here and submitted
+// through `/submit`. TRP only sees confirmed UTxOs, so this overlay is handed
+// to resolution (see `resolve`) and evaluation for transactions that spend
+// them, and used to refuse transactions that spend inputs a pending one
+// already consumes. Entries belong to the caller that submitted them and are
+// only ever offered back to that caller.
+
+// Kept when the transaction has no TTL and is never seen confirming
+const DEFAULT_PENDING_TTL_SECS: u64 = 60 * 60;
+
+// How often pending transactions are checked against the ledger
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+struct PendingTx {
+    // Caller that built and submitted it
+    owner: String,
+    // `txhash#index` of every input it spends
+    spent: Vec<String>,
+    // Outputs in the Ogmios UTxO format, keyed by `txhash#index`
+    outputs: Vec<(String, Value)>,
+    expires_ms: u64,
+}
+
+// A transaction built here, by hash, which only its owner may submit
+struct BuiltTx {
+    owner: String,
+    expires_ms: u64,
+}
+
+static BUILT: Lazy<Mutex<HashMap<String, BuiltTx>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static PENDING: Lazy<Mutex<HashMap<String, PendingTx>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Worker webhooks are timestamped with the chain tip, which may lag behind
+const DEFAULT_WEBHOOK_MAX_SKEW_SECS: u64 = 600;
+
+static WEBHOOK_MAX_SKEW_SECS: Lazy<u64> = Lazy::new(|| {
+    std::env::var("WORKER_WEBHOOK_MAX_SKEW_SECS")
+        .ok()
+        .and_then(|skew| skew.parse().ok())
+        .unwrap_or(DEFAULT_WEBHOOK_MAX_SKEW_SECS)
+});
+
+static PENDING_TTL_MS: Lazy<u64> = Lazy::new(|| {
+    std::env::var("PENDING_TX_TTL_SECS")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .unwrap_or(DEFAULT_PENDING_TTL_SECS)
+        * 1000
+});
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct SubmitRequest {
+    // Hex-encoded signed transaction CBOR, built by this server for the caller
+    pub tx: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SubmittedResponse {
+    pub hash: String,
+    // `txhash#index` of the outputs follow-up transactions may spend
+    pub outputs: Vec<String>,
+    // POSIX time in milliseconds after which the outputs are dropped
+    pub expires_ms: u64,
+}
+
+// Ogmios `additionalUtxo` entry for an unconfirmed output
+pub(crate) fn ogmios_utxo(tx_hash: &str, index: usize, output: &MultiEraOutput) -> Value {
+    let mut ogmios_value = Map::new();
+    ogmios_value.insert("ada".to_string(), json!({ "lovelace": output.lovelace_amount() }));
+    for asset in assets(&output.non_ada_assets()) {
+        ogmios_value
+            .entry(asset.policy_id)
+            .or_insert_with(|| json!({}))[asset.asset_name] = json!(asset.quantity);
+    }
+
+    let mut utxo = json!({
+        "transaction": { "id": tx_hash },
+        "index": index,
+        "address": output
+            .address()
+            .map(|address| address_text(&address))
+            .unwrap_or_default(),
+        "value": ogmios_value
+    });
+    match output.datum() {
+        Some(MintedDatumOption::Hash(hash)) => utxo["datumHash"] = json!(hash.to_string()),
+        Some(MintedDatumOption::Data(data)) => {
+            utxo["datum"] = json!(hex::encode(data.0.raw_cbor()))
+        }
+        None => {}
+    }
+    utxo
+}
+
+fn prune(pending: &mut HashMap<String, PendingTx>) {
+    let now = now_posix_ms();
+    pending.retain(|hash, tx| {
+        let live = tx.expires_ms > now;
+        if !live {
+            tracing::info!(tx_hash = %hash, "Dropped expired pending transaction");
+        }
+        live
+    });
+}
+
+// POSIX time in milliseconds at which `tx` can no longer be included
+fn expiry(tx: &MultiEraTx) -> Result<u64, String> {
+    match tx.ttl() {
+        Some(slot) => Ok(network_slots()?.slot_to_posix_ms(slot)),
+        None => Ok(now_posix_ms() + *PENDING_TTL_MS),
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<MultiEraTx<'_>, String> {
+    MultiEraTx::decode(bytes).map_err(|e| format!("Invalid transaction: {}", e))
+}
+
+// Records a built transaction for `owner`, the only caller allowed to submit it
+pub fn record_built(tx: &TxEnvelope, owner: &str) {
+    let bytes = hex::decode(&tx.tx).unwrap_or_default();
+    let expires_ms = decode(&bytes)
+        .and_then(|decoded| expiry(&decoded))
+        .unwrap_or_else(|_| now_posix_ms() + *PENDING_TTL_MS);
+
+    let mut built = BUILT.lock().unwrap();
+    let now = now_posix_ms();
+    built.retain(|_, tx| tx.expires_ms > now);
+    built.insert(
+        tx.hash.clone(),
+        BuiltTx {
+            owner: owner.to_string(),
+            expires_ms,
+        },
+    );
+}
+
+// Checks that `cbor_hex` is a transaction built here for `owner` that can
+// still be included, and returns the overlay entry it would make
+fn accept(cbor_hex: &str, owner: &str) -> Result<(String, PendingTx), String> {
+    let bytes = hex::decode(cbor_hex).map_err(|e| format!("Invalid field `tx`: {}", e))?;
+    let tx = decode(&bytes)?;
+    let hash = tx.hash().to_string();
+
+    let built_for_owner = BUILT
+        .lock()
+        .unwrap()
+        .get(&hash)
+        .is_some_and(|built| built.owner == owner);
+    if !built_for_owner {
+        return Err(format!("Transaction {} was not built here for this caller", hash));
+    }
+
+    let expires_ms = expiry(&tx)?;
+    if expires_ms <= now_posix_ms() {
+        return Err(format!("Transaction {} has already expired", hash));
+    }
+
+    let outputs = tx
+        .outputs()
+        .iter()
+        .enumerate()
+        .map(|(index, output)| {
+            (
+                format!("{}#{}", hash, index),
+                ogmios_utxo(&hash, index, output),
+            )
+        })
+        .collect();
+    let pending = PendingTx {
+        owner: owner.to_string(),
+        spent: tx.inputs().iter().map(input_ref).collect(),
+        outputs,
+        expires_ms,
+    };
+    Ok((hash, pending))
+}
+
+// Adds an accepted transaction to the overlay. Its outputs stay there until
+// it is seen confirming or its validity interval ends.
+fn record_pending(hash: String, tx: PendingTx) -> SubmittedResponse {
+    let response = SubmittedResponse {
+        hash: hash.clone(),
+        outputs: tx
+            .outputs
+            .iter()
+            .map(|(utxo_ref, _)| utxo_ref.clone())
+            .collect(),
+        expires_ms: tx.expires_ms,
+    };
+
+    let mut pending = PENDING.lock().unwrap();
+    prune(&mut pending);
+    pending.insert(hash.clone(), tx);
+    tracing::info!(tx_hash = %hash, pending = pending.len(), "Recorded pending transaction");
+
+    response
+}
+
+async fn ogmios_submit(cbor_hex: &str) -> Result<(), String> {
+    let url = env("OGMIOS_ENDPOINT")?;
+    let api_key = env("DMTR_API_KEY_OGMIOS")?;
+    let params = json!({ "transaction": { "cbor": cbor_hex } });
+    let (status, body) = json_rpc(&Client::new(), &url, &api_key, "submitTransaction", params).await?;
+    match body.get("result") {
+        Some(_) => Ok(()),
+        None => Err(format!("Ogmios rejected the transaction with {}: {}", status, body)),
+    }
+}
+
+// Submits a transaction built here for the caller and records its outputs
+pub async fn submit(cbor_hex: &str) -> Result<SubmittedResponse, String> {
+    let (hash, tx) = accept(cbor_hex, &auth::caller())?;
+    ogmios_submit(cbor_hex).await?;
+    Ok(record_pending(hash, tx))
+}
+
+pub fn confirm(tx_hash: &str) -> bool {
+    let mut pending = PENDING.lock().unwrap();
+    prune(&mut pending);
+    pending.remove(tx_hash).is_some()
+}
+
+// Unconfirmed outputs of `owner` among `refs`, for Ogmios evaluation
+pub fn additional_utxos(refs: &[String], owner: &str) -> Vec<Value> {
+    let mut pending = PENDING.lock().unwrap();
+    prune(&mut pending);
+    pending
+        .values()
+        .filter(|tx| tx.owner == owner)
+        .flat_map(|tx| tx.outputs.iter())
+        .filter(|(utxo_ref, _)| refs.contains(utxo_ref))
+        .map(|(_, utxo)| utxo.clone())
+        .collect()
+}
+
+// The pending transaction of `owner` that already spends one of `inputs`, if any
+pub fn spent_by_pending(inputs: &[String], owner: &str) -> Option<(String, String)> {
+    let mut pending = PENDING.lock().unwrap();
+    prune(&mut pending);
+    pending
+        .iter()
+        .filter(|(_, tx)| tx.owner == owner)
+        .find_map(|(hash, tx)| {
+            inputs
+                .iter()
+                .find(|input| tx.spent.contains(input))
+                .map(|input| (input.clone(), hash.clone()))
+        })
+}
+
+pub async fn submit_route(Json(req): Json<SubmitRequest>) -> Json<Result<SubmittedResponse, String>> {
+    let submitted = submit(&req.tx).await;
+    if let Err(e) = &submitted {
+        tracing::warn!(error = %e, "Rejected transaction submission");
+    }
+    Json(submitted)
+}
+
+///// CONFIRMATION POLLING /////
+// Inputs of the pending transactions that are not outputs of other pending
+// transactions, so the ledger can tell whether they are still unspent
+fn confirmed_inputs() -> Vec<String> {
+    let mut pending = PENDING.lock().unwrap();
+    prune(&mut pending);
+    let unconfirmed: HashSet<&String> = pending
+        .values()
+        .flat_map(|tx| tx.outputs.iter().map(|(utxo_ref, _)| utxo_ref))
+        .collect();
+    pending
+        .values()
+        .flat_map(|tx| tx.spent.iter())
+        .filter(|input| !unconfirmed.contains(input))
+        .cloned()
+        .collect()
+}
+
+// Drops pending transactions none of whose inputs is still unspent: either
+// they confirmed or another transaction spent their inputs first. Inputs that
+// are outputs of other pending transactions count as unspent. Returns the
+// dropped hashes.
+fn settle(unspent: &HashSet<String>) -> Vec<String> {
+    let mut pending = PENDING.lock().unwrap();
+    let unconfirmed: HashSet<String> = pending
+        .values()
+        .flat_map(|tx| tx.outputs.iter().map(|(utxo_ref, _)| utxo_ref.clone()))
+        .collect();
+
+    let settled: Vec<String> = pending
+        .iter()
+        .filter(|(_, tx)| {
+            !tx.spent
+                .iter()
+                .any(|input| unspent.contains(input) || unconfirmed.contains(input))
+        })
+        .map(|(hash, _)| hash.clone())
+        .collect();
+    for hash in &settled {
+        pending.remove(hash);
+        tracing::info!(tx_hash = %hash, "Pending transaction settled on chain");
+    }
+    settled
+}
+
+async fn poll_confirmations() -> Result<(), String> {
+    let inputs = confirmed_inputs();
+    if inputs.is_empty() {
+        return Ok(());
+    }
+
+    let output_references: Vec<Value> = inputs
+        .iter()
+        .filter_map(|input| input.split_once('#'))
+        .filter_map(|(tx_hash, index)| {
+            let index: u64 = index.parse().ok()?;
+            Some(json!({ "transaction": { "id": tx_hash }, "index": index }))
+        })
+        .collect();
+    let unspent = query_utxos(json!({ "outputReferences": output_references }))
+        .await?
+        .iter()
+        .map(ogmios_ref)
+        .collect();
+    settle(&unspent);
+    Ok(())
+}
+
+// Evicts confirmed transactions without waiting for the worker's webhook,
+// which only covers transactions submitted through the worker
+pub fn spawn_confirmations() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(CONFIRMATION_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = poll_confirmations().await {
+                tracing::warn!(error = %e, "Failed to poll pending transactions");
+            }
+        }
+    });
+}
+
+///// CONFIRMATION WEBHOOK /////
+#[derive(Deserialize)]
+struct ConfirmedData {
+    tx_hash: String,
+}
+
+// A worker webhook delivery; `tx-confirmed` carries `{ "tx_hash": ... }` in `data`
+#[derive(Deserialize, JsonSchema)]
+pub struct WebhookEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    data: Value,
+}
+
+// Checks `X-Githoney-Signature: t=<unix secs>,v1=<hex HMAC-SHA256 of "t.body">`
+// made with the worker's `webhook_secret`. A `t` further than `max_skew_secs`
+// from `now_secs` is refused, so a captured delivery cannot be replayed later.
+fn verify_signature(
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now_secs: u64,
+    max_skew_secs: u64,
+) -> Result<(), &'static str> {
+    let signature = headers
+        .get("X-Githoney-Signature")
+        .and_then(|value| value.to_str().ok())
+        .ok_or("Missing signature")?;
+
+    let mut timestamp = None;
+    let mut v1 = None;
+    for part in signature.split(',') {
+        match part.split_once('=') {
+            Some(("t", value)) => timestamp = Some(value),
+            Some(("v1", value)) => v1 = hex::decode(value).ok(),
+            _ => {}
+        }
+    }
+    let (Some(timestamp), Some(v1)) = (timestamp, v1) else {
+        return Err("Invalid signature");
+    };
+
+    let signed_at: u64 = timestamp.parse().map_err(|_| "Invalid signature timestamp")?;
+    if now_secs.abs_diff(signed_at) > max_skew_secs {
+        return Err("Signature timestamp is outside the allowed window");
+    }
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&v1).map_err(|_| "Invalid signature")
+}
+
+// Receives the worker's `tx-confirmed` webhook and drops the confirmed
+// transaction from the overlay. Other event types are acknowledged and ignored.
+pub async fn tx_confirmed_webhook(
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, Json<Result<bool, String>>) {
+    let Ok(secret) = std::env::var("WORKER_WEBHOOK_SECRET") else {
+        tracing::warn!("WORKER_WEBHOOK_SECRET is not set, rejecting worker webhook");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(Err("Worker webhooks are not configured".to_string())),
+        );
+    };
+    let verified = verify_signature(
+        &secret,
+        &headers,
+        &body,
+        now_posix_ms() / 1000,
+        *WEBHOOK_MAX_SKEW_SECS,
+    );
+    if let Err(e) = verified {
+        tracing::warn!(error = e, "Rejected worker webhook");
+        return (StatusCode::UNAUTHORIZED, Json(Err(e.to_string())));
+    }
+
+    let event: WebhookEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(Err(format!("Invalid event: {}", e))),
+            )
+        }
+    };
+    if event.event_type != "tx-confirmed" {
+        return (StatusCode::OK, Json(Ok(false)));
+    }
+
+    match serde_json::from_value::<ConfirmedData>(event.data) {
+        Ok(data) => {
+            let dropped = confirm(&data.tx_hash);
+            tracing::info!(tx_hash = %data.tx_hash, dropped, "Transaction confirmed");
+            (StatusCode::OK, Json(Ok(dropped)))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(Err(format!("Invalid event data: {}", e))),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn signed(secret: &str, timestamp: u64, body: &[u8]) -> HeaderMap {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(body);
+        let signature = format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Githoney-Signature", HeaderValue::from_str(&signature).unwrap());
+        headers
+    }
+
+    // Minimal transaction spending `input#0` into one output, without a TTL
+    fn tx_spending(input: &str) -> TxEnvelope {
+        let tx = format!(
+            "84a30081825820{}00018182581d60{}1a001e8480021a00030d40a0f5f6",
+            input,
+            "22".repeat(28)
+        );
+        let hash = MultiEraTx::decode(&hex::decode(&tx).unwrap()).unwrap().hash().to_string();
+        TxEnvelope { hash, tx }
+    }
+
+    #[test]
+    fn only_the_builder_may_submit() {
+        let tx = tx_spending(&"31".repeat(32));
+        assert!(accept(&tx.tx, "alice").is_err());
+
+        record_built(&tx, "alice");
+        assert!(accept(&tx.tx, "bob").is_err());
+        assert!(accept(&tx.tx, "alice").is_ok());
+    }
+
+    fn submitted(tx: &TxEnvelope, owner: &str) {
+        record_built(tx, owner);
+        let (hash, pending) = accept(&tx.tx, owner).unwrap();
+        let response = record_pending(hash, pending);
+        assert_eq!(response.outputs, vec![format!("{}#0", tx.hash)]);
+    }
+
+    // Both eviction paths in one test, since polling sees the whole overlay
+    #[test]
+    fn records_confirms_and_evicts() {
+        let polled_input = format!("{}#0", "41".repeat(32));
+        let polled = tx_spending(&"41".repeat(32));
+        let polled_output = format!("{}#0", polled.hash);
+        let confirmed_input = format!("{}#0", "51".repeat(32));
+        let confirmed = tx_spending(&"51".repeat(32));
+        let confirmed_output = format!("{}#0", confirmed.hash);
+        submitted(&polled, "alice");
+        submitted(&confirmed, "alice");
+        // A follow-up spending the confirmed one's output before it is on chain
+        let follow_up = tx_spending(&confirmed.hash);
+        submitted(&follow_up, "alice");
+
+        // Only the submitter sees its outputs and spent inputs
+        assert_eq!(additional_utxos(std::slice::from_ref(&polled_output), "alice").len(), 1);
+        assert!(additional_utxos(std::slice::from_ref(&polled_output), "bob").is_empty());
+        assert!(spent_by_pending(std::slice::from_ref(&polled_input), "alice").is_some());
+        assert!(spent_by_pending(std::slice::from_ref(&polled_input), "bob").is_none());
+
+        // Polling evicts a transaction once the ledger has spent its inputs,
+        // and keeps the ones whose inputs are unspent or not on chain yet
+        let inputs = confirmed_inputs();
+        assert!(inputs.contains(&polled_input) && inputs.contains(&confirmed_input));
+        assert!(!inputs.contains(&confirmed_output));
+        let settled = settle(&HashSet::from([confirmed_input.clone()]));
+        assert!(settled.contains(&polled.hash));
+        assert!(!settled.contains(&confirmed.hash) && !settled.contains(&follow_up.hash));
+        assert!(additional_utxos(std::slice::from_ref(&polled_output), "alice").is_empty());
+        assert!(spent_by_pending(std::slice::from_ref(&polled_input), "alice").is_none());
+        assert!(!confirm(&polled.hash));
+
+        // The worker's confirmation webhook evicts it right away
+        assert_eq!(additional_utxos(std::slice::from_ref(&confirmed_output), "alice").len(), 1);
+        assert!(confirm(&confirmed.hash));
+        assert!(additional_utxos(std::slice::from_ref(&confirmed_output), "alice").is_empty());
+        assert!(confirm(&follow_up.hash));
+    }
+
+    #[test]
+    fn accepts_fresh_signatures() {
+        let body = br#"{"type":"tx-confirmed","data":{}}"#;
+        assert!(verify_signature("secret", &signed("secret", NOW, body), body, NOW, 600).is_ok());
+        assert!(verify_signature("secret", &signed("secret", NOW - 600, body), body, NOW, 600).is_ok());
+        assert!(verify_signature("secret", &signed("secret", NOW + 600, body), body, NOW, 600).is_ok());
+    }
+
+    #[test]
+    fn rejects_stale_or_forged_signatures() {
+        let body = br#"{"type":"tx-confirmed","data":{}}"#;
+        let stale = signed("secret", NOW - 601, body);
+        assert!(verify_signature("secret", &stale, body, NOW, 600).is_err());
+        let future = signed("secret", NOW + 601, body);
+        assert!(verify_signature("secret", &future, body, NOW, 600).is_err());
+        assert!(verify_signature("other", &signed("secret", NOW, body), body, NOW, 600).is_err());
+        assert!(verify_signature("secret", &signed("secret", NOW, body), b"{}", NOW, 600).is_err());
+        assert!(verify_signature("secret", &HeaderMap::new(), body, NOW, 600).is_err());
+    }
+}
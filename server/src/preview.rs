@@ -0,0 +1,80 @@
+use axum::Json;
+use pallas_traverse::MultiEraTx;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+pub use githoney_common::preview::{address_text, assets, input_ref, TxPreview};
+
+use crate::health::{env, json_rpc};
+use crate::time::network_slots;
+use crate::{auth, pending, tx_check};
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct PreviewRequest {
+    // Hex-encoded transaction CBOR, as returned in `TxEnvelope.tx`
+    pub tx: String,
+}
+
+// `txhash#index` of the UTxOs among `inputs` locked at the Githoney script,
+// looked up in the pending overlay first and then through Ogmios
+async fn script_inputs(inputs: &[String]) -> Result<Vec<String>, String> {
+    let script = address_text(&tx_check::config()?.script);
+
+    let mut utxos = pending::additional_utxos(inputs, &auth::caller());
+    let output_references: Vec<Value> = inputs
+        .iter()
+        .filter_map(|input| input.split_once('#'))
+        .filter_map(|(tx_hash, index)| {
+            let index: u64 = index.parse().ok()?;
+            Some(json!({ "transaction": { "id": tx_hash }, "index": index }))
+        })
+        .collect();
+
+    let url = env("OGMIOS_ENDPOINT")?;
+    let api_key = env("DMTR_API_KEY_OGMIOS")?;
+    let params = json!({ "outputReferences": output_references });
+    let (status, body) =
+        json_rpc(&Client::new(), &url, &api_key, "queryLedgerState/utxo", params).await?;
+    match body.get("result").and_then(Value::as_array) {
+        Some(confirmed) => utxos.extend(confirmed.iter().cloned()),
+        None => return Err(format!("Ogmios responded with {}: {}", status, body)),
+    }
+
+    Ok(utxos
+        .iter()
+        .filter(|utxo| utxo["address"].as_str() == Some(script.as_str()))
+        .filter_map(|utxo| {
+            Some(format!(
+                "{}#{}",
+                utxo["transaction"]["id"].as_str()?,
+                utxo["index"].as_u64()?
+            ))
+        })
+        .collect())
+}
+
+// Decodes a built transaction into a reviewable summary. Spend redeemers are
+// left unnamed when the spent inputs cannot be looked up.
+pub async fn preview_tx(cbor_hex: &str) -> Result<TxPreview, String> {
+    let bytes = hex::decode(cbor_hex).map_err(|e| format!("Invalid field `tx`: {}", e))?;
+    let tx = MultiEraTx::decode(&bytes).map_err(|e| format!("Invalid transaction: {}", e))?;
+    let slots = network_slots()?;
+
+    let inputs: Vec<String> = tx.inputs().iter().map(input_ref).collect();
+    let script_inputs = script_inputs(&inputs).await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Could not look up spent inputs for preview");
+        Vec::new()
+    });
+
+    Ok(githoney_common::preview::preview_tx(&tx, &slots, &script_inputs))
+}
+
+pub async fn preview_route(Json(req): Json<PreviewRequest>) -> Json<Result<TxPreview, String>> {
+    let preview = preview_tx(&req.tx).await;
+    if let Err(e) = &preview {
+        tracing::warn!(error = %e, "Rejected preview request");
+    }
+    Json(preview)
+}
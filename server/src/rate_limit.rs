@@ -0,0 +1,472 @@
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::auth::Principal;
+use crate::time::now_posix_ms;
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+// How often buckets that have refilled completely are dropped
+const PRUNE_INTERVAL_MS: u64 = 60 * 1000;
+
+// A bucket holding up to `capacity` requests, refilled at `refill_per_sec`
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct BucketConfig {
+    pub capacity: f64,
+    pub refill_per_sec: f64,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+pub struct RouteLimits {
+    pub per_key: Option<BucketConfig>,
+    pub per_ip: Option<BucketConfig>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct RateLimitConfig {
+    // Keyed by route path, e.g. `/create-bounty`
+    #[serde(default)]
+    pub routes: HashMap<String, RouteLimits>,
+    // Limits for routes not listed above
+    #[serde(default)]
+    pub default: RouteLimits,
+    // Bucket state is snapshotted here so limits survive restarts
+    pub persist_path: Option<String>,
+    // Reverse proxies whose `Forwarded` / `X-Forwarded-For` headers are
+    // believed. The server listens on 127.0.0.1, so behind a local proxy
+    // every peer is that proxy and per-IP limits need it listed here.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_ms: u64,
+    // When the bucket is full again, after which it equals a fresh one
+    #[serde(default)]
+    full_ms: u64,
+}
+
+pub trait BucketStore: Send + Sync {
+    // Takes one token from each bucket of `buckets`, keyed by name, or none at
+    // all if one is empty; then returns how long until every one has a token
+    fn take(&self, buckets: &[(String, BucketConfig)], now_ms: u64) -> Result<(), Duration>;
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+    pruned_ms: AtomicU64,
+}
+
+impl Bucket {
+    fn refill(&mut self, config: BucketConfig, now_ms: u64) {
+        let elapsed_secs = now_ms.saturating_sub(self.updated_ms) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed_secs * config.refill_per_sec).min(config.capacity);
+        self.updated_ms = now_ms;
+    }
+
+    // How long until a token is available, zero if one is
+    fn wait(&self, config: BucketConfig) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else if config.refill_per_sec > 0.0 {
+            Duration::from_secs_f64((1.0 - self.tokens) / config.refill_per_sec)
+        } else {
+            Duration::from_secs(u64::from(u32::MAX))
+        }
+    }
+
+    fn refill_ms(&self, config: BucketConfig) -> u64 {
+        let missing = config.capacity - self.tokens;
+        if missing <= 0.0 {
+            0
+        } else if config.refill_per_sec > 0.0 {
+            (missing / config.refill_per_sec * 1000.0).ceil() as u64
+        } else {
+            u64::MAX
+        }
+    }
+}
+
+impl MemoryStore {
+    // Drops buckets that have refilled completely; a fresh bucket is full,
+    // so the next request from that caller is treated the same
+    fn prune(&self, buckets: &mut HashMap<String, Bucket>, now_ms: u64) {
+        if now_ms.saturating_sub(self.pruned_ms.load(Ordering::Relaxed)) < PRUNE_INTERVAL_MS {
+            return;
+        }
+        self.pruned_ms.store(now_ms, Ordering::Relaxed);
+        buckets.retain(|_, bucket| bucket.full_ms > now_ms);
+    }
+}
+
+impl BucketStore for MemoryStore {
+    fn take(&self, buckets: &[(String, BucketConfig)], now_ms: u64) -> Result<(), Duration> {
+        let mut stored = self.buckets.lock().unwrap();
+        self.prune(&mut stored, now_ms);
+
+        let mut wait = Duration::ZERO;
+        for (key, config) in buckets {
+            let bucket = stored.entry(key.clone()).or_insert(Bucket {
+                tokens: config.capacity,
+                updated_ms: now_ms,
+                full_ms: now_ms,
+            });
+            bucket.refill(*config, now_ms);
+            wait = wait.max(bucket.wait(*config));
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for (key, config) in buckets {
+            if let Some(bucket) = stored.get_mut(key) {
+                bucket.tokens -= 1.0;
+                bucket.full_ms = now_ms.saturating_add(bucket.refill_ms(*config));
+            }
+        }
+        Ok(())
+    }
+}
+
+// In-memory buckets, loaded from and periodically written back to a JSON file
+pub struct FileStore {
+    path: String,
+    memory: MemoryStore,
+}
+
+impl FileStore {
+    pub fn open(path: &str) -> FileStore {
+        let buckets = std::fs::read(path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default();
+
+        FileStore {
+            path: path.to_string(),
+            memory: MemoryStore {
+                buckets: Mutex::new(buckets),
+                pruned_ms: AtomicU64::new(0),
+            },
+        }
+    }
+
+    fn snapshot(&self) -> std::io::Result<()> {
+        let contents = serde_json::to_vec(&*self.memory.buckets.lock().unwrap())?;
+        std::fs::write(&self.path, contents)
+    }
+}
+
+impl BucketStore for FileStore {
+    fn take(&self, buckets: &[(String, BucketConfig)], now_ms: u64) -> Result<(), Duration> {
+        self.memory.take(buckets, now_ms)
+    }
+}
+
+fn load_config() -> Result<RateLimitConfig, String> {
+    let Ok(path) = std::env::var("RATE_LIMIT_CONFIG") else {
+        return Ok(RateLimitConfig::default());
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read RATE_LIMIT_CONFIG {}: {}", path, e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Invalid RATE_LIMIT_CONFIG {}: {}", path, e))
+}
+
+// Without RATE_LIMIT_CONFIG no route is limited; a config that cannot be
+// loaded stops the server at startup, see `check_config`
+static CONFIG: Lazy<Result<RateLimitConfig, String>> = Lazy::new(load_config);
+
+pub fn check_config() -> Result<(), String> {
+    CONFIG.as_ref().map(|_| ()).map_err(String::clone)
+}
+
+static FILE_STORE: Lazy<Option<FileStore>> = Lazy::new(|| {
+    CONFIG
+        .as_ref()
+        .ok()
+        .and_then(|config| config.persist_path.as_deref())
+        .map(FileStore::open)
+});
+
+static MEMORY_STORE: Lazy<MemoryStore> = Lazy::new(MemoryStore::default);
+
+fn store() -> &'static dyn BucketStore {
+    match FILE_STORE.as_ref() {
+        Some(store) => store,
+        None => &*MEMORY_STORE,
+    }
+}
+
+// Writes the bucket snapshot every few seconds when a persist path is configured
+pub fn spawn_snapshots() {
+    if FILE_STORE.is_none() {
+        return;
+    }
+
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Some(Err(e)) = FILE_STORE.as_ref().map(FileStore::snapshot) {
+                tracing::error!(error = %e, "Failed to snapshot rate limit buckets");
+            }
+        }
+    });
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    tracing::warn!(retry_after_secs, "Rate limited request");
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after_secs.to_string())],
+        Json(Err::<(), String>(format!(
+            "Rate limit exceeded, retry in {} seconds",
+            retry_after_secs
+        ))),
+    )
+        .into_response()
+}
+
+fn parse_ip(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+    value
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| value.strip_prefix('[')?.split(']').next()?.parse().ok())
+}
+
+// Client-side hops recorded by proxies, nearest last. `Forwarded` (RFC 7239)
+// wins over `X-Forwarded-For` when both are present.
+fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let values = |name: header::HeaderName| -> Vec<String> {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let forwarded = values(header::FORWARDED);
+    if !forwarded.is_empty() {
+        return forwarded
+            .iter()
+            .map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim().eq_ignore_ascii_case("for").then(|| parse_ip(value))?
+                })
+            })
+            .collect();
+    }
+
+    values(header::HeaderName::from_static("x-forwarded-for"))
+        .iter()
+        .map(|value| parse_ip(value))
+        .collect()
+}
+
+// The address per-IP limits apply to. Hops are walked back from the peer
+// while they are trusted proxies; the first other address is the client.
+// An unreadable hop stops the walk at the proxy that reported it.
+fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted: &[IpAddr]) -> IpAddr {
+    let mut client = peer;
+    if !trusted.contains(&client) {
+        return client;
+    }
+    for hop in forwarded_chain(headers).into_iter().rev() {
+        let Some(hop) = hop else {
+            return client;
+        };
+        client = hop;
+        if !trusted.contains(&client) {
+            return client;
+        }
+    }
+    client
+}
+
+// Route layer applying the per-key and per-IP buckets of the matched route.
+// Must run inside `auth::require` so the caller's principal is known. A
+// request rejected by one bucket takes no token from the other.
+pub async fn limit(req: Request, next: Next) -> Response {
+    let rate_limits = match CONFIG.as_ref() {
+        Ok(config) => config,
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(Err::<(), String>(e.clone())))
+                .into_response()
+        }
+    };
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let limits = rate_limits
+        .routes
+        .get(&route)
+        .copied()
+        .unwrap_or(rate_limits.default);
+
+    let mut buckets = Vec::new();
+    if let (Some(config), Some(principal)) = (limits.per_key, req.extensions().get::<Principal>()) {
+        buckets.push((format!("key:{}:{}", principal.name, route), config));
+    }
+    if let (Some(config), Some(ConnectInfo(addr))) =
+        (limits.per_ip, req.extensions().get::<ConnectInfo<SocketAddr>>())
+    {
+        let ip = client_ip(addr.ip(), req.headers(), &rate_limits.trusted_proxies);
+        buckets.push((format!("ip:{}:{}", ip, route), config));
+    }
+
+    if let Err(retry_after) = store().take(&buckets, now_posix_ms()) {
+        return too_many_requests(retry_after);
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const PROXY: &str = "127.0.0.1";
+
+    fn bucket(key: &str, config: BucketConfig) -> [(String, BucketConfig); 1] {
+        [(key.to_string(), config)]
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn ignores_forwarded_headers_from_untrusted_peers() {
+        let forged = headers(&[("x-forwarded-for", "203.0.113.7")]);
+        assert_eq!(client_ip(ip("198.51.100.1"), &forged, &[ip(PROXY)]), ip("198.51.100.1"));
+        assert_eq!(client_ip(ip(PROXY), &forged, &[]), ip(PROXY));
+    }
+
+    #[test]
+    fn takes_the_nearest_untrusted_hop() {
+        let trusted = [ip(PROXY), ip("10.0.0.2")];
+        // The client spoofed the leftmost entry; 203.0.113.7 is what the outer proxy saw
+        let forwarded = headers(&[("x-forwarded-for", "192.0.2.1, 203.0.113.7, 10.0.0.2")]);
+        assert_eq!(client_ip(ip(PROXY), &forwarded, &trusted), ip("203.0.113.7"));
+
+        let rfc7239 = headers(&[(
+            "forwarded",
+            "for=192.0.2.1, for=\"[2001:db8::1]:4711\";proto=https, for=10.0.0.2",
+        )]);
+        assert_eq!(client_ip(ip(PROXY), &rfc7239, &trusted), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn stops_at_unreadable_hops() {
+        let obfuscated = headers(&[("forwarded", "for=_hidden, for=10.0.0.2")]);
+        let trusted = [ip(PROXY), ip("10.0.0.2")];
+        assert_eq!(client_ip(ip(PROXY), &obfuscated, &trusted), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn prunes_full_idle_buckets() {
+        let config = BucketConfig {
+            capacity: 2.0,
+            refill_per_sec: 1.0,
+        };
+        let store = MemoryStore::default();
+        store.take(&bucket("idle", config), PRUNE_INTERVAL_MS).unwrap();
+        store.take(&bucket("busy", config), PRUNE_INTERVAL_MS).unwrap();
+
+        // "idle" is full again a second later; "busy" keeps draining
+        let later = 2 * PRUNE_INTERVAL_MS;
+        store.take(&bucket("busy", config), later - 1).unwrap();
+        store.take(&bucket("busy", config), later - 1).unwrap();
+        store.take(&bucket("other", config), later).unwrap();
+
+        let buckets = store.buckets.lock().unwrap();
+        assert!(!buckets.contains_key("idle"));
+        assert!(buckets.contains_key("busy"));
+        assert!(buckets.contains_key("other"));
+    }
+
+    #[test]
+    fn buckets_drain_and_refill() {
+        let config = BucketConfig {
+            capacity: 2.0,
+            refill_per_sec: 0.5,
+        };
+        let store = MemoryStore::default();
+        store.take(&bucket("key", config), 1_000).unwrap();
+        store.take(&bucket("key", config), 1_000).unwrap();
+
+        // Empty: one token takes two seconds to come back
+        assert_eq!(store.take(&bucket("key", config), 1_000), Err(Duration::from_secs(2)));
+        assert_eq!(store.take(&bucket("key", config), 2_000), Err(Duration::from_secs(1)));
+        store.take(&bucket("key", config), 3_000).unwrap();
+
+        // Refills stop at capacity
+        store.take(&bucket("key", config), 60_000).unwrap();
+        store.take(&bucket("key", config), 60_000).unwrap();
+        assert!(store.take(&bucket("key", config), 60_000).is_err());
+
+        // Other keys have their own bucket
+        store.take(&bucket("other", config), 60_000).unwrap();
+    }
+
+    #[test]
+    fn buckets_without_refill_stay_empty() {
+        let config = BucketConfig {
+            capacity: 1.0,
+            refill_per_sec: 0.0,
+        };
+        let store = MemoryStore::default();
+        store.take(&bucket("key", config), 0).unwrap();
+        assert!(store.take(&bucket("key", config), u64::MAX / 2).unwrap_err() > Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn a_rejected_request_takes_no_token() {
+        let per_key = BucketConfig {
+            capacity: 2.0,
+            refill_per_sec: 0.0,
+        };
+        let per_ip = BucketConfig {
+            capacity: 1.0,
+            refill_per_sec: 1.0,
+        };
+        let both = [("key".to_string(), per_key), ("ip".to_string(), per_ip)];
+        let store = MemoryStore::default();
+        store.take(&both, 1_000).unwrap();
+
+        // The empty per-IP bucket rejects the request before the key pays for it
+        assert_eq!(store.take(&both, 1_000), Err(Duration::from_secs(1)));
+        store.take(&both, 2_000).unwrap();
+        assert!(store.take(&bucket("key", per_key), 2_000).is_err());
+    }
+}
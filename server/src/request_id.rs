@@ -0,0 +1,46 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// Correlation id of the request being handled, if any
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+// The id to forward to TRP and Ogmios so their calls can be matched with ours
+pub fn header_value() -> Option<HeaderValue> {
+    current().and_then(|id| HeaderValue::from_str(&id).ok())
+}
+
+fn from_request(req: &Request) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub fn make_span(req: &Request) -> Span {
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        path = %req.uri().path(),
+        request_id = %from_request(req),
+    )
+}
+
+// Makes the id set by `SetRequestIdLayer` available to outbound calls made by the handler
+pub async fn scope(req: Request, next: Next) -> Response {
+    let id = from_request(&req);
+    REQUEST_ID.scope(id, next.run(req)).await
+}
@@ -0,0 +1,540 @@
+use pallas_addresses::Address;
+use pallas_codec::minicbor;
+use pallas_primitives::alonzo::{BigInt, PlutusData};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use tx3_sdk::core::{BytesEncoding, TirEnvelope};
+use tx3_sdk::trp::{ResolveParams, TxEnvelope};
+use tx3_tir::encoding::{from_bytes, to_bytes, AnyTir, TirVersion};
+use tx3_tir::model::assets::CanonicalAssets;
+use tx3_tir::model::core::{Utxo, UtxoRef, UtxoSet};
+use tx3_tir::model::v1beta0::{Expression, InputQuery, Param, StructExpr, Tx, IR_VERSION};
+use tx3_tir::reduce::{Apply, Error as ReduceError};
+use tx3_tir::{Node, Visitor};
+
+use githoney_common::datum::constructor_index;
+use githoney_common::types::UtxoRef as RequestUtxoRef;
+
+use crate::auth;
+use crate::coin_control::CoinControl;
+use crate::health::{env, json_rpc};
+use crate::pending;
+use crate::routes::trp_options;
+
+// The generated client sends the compiled TIR of a transaction to TRP as is,
+// and TRP resolves every input against the confirmed ledger. Build routes
+// resolve through here instead, which edits the TIR first:
+// - an input naming an unconfirmed output of a pending transaction gets that
+//   output as an already resolved UTxO, so follow-up transactions can be
+//   built before the first one confirms;
+// - coin control pins the wallet input to the requested `inputs`, narrows it
+//   to the wallet's UTxOs minus `exclude_inputs`, and narrows the collateral
+//   query to `collateral`.
+
+fn decode_tir(ir: &str) -> Result<Tx, String> {
+    let bytes = hex::decode(ir).map_err(|e| format!("TIR is not valid hex: {}", e))?;
+    match from_bytes(&bytes, TirVersion::V1Beta0).map_err(|e| e.to_string())? {
+        AnyTir::V1Beta0(tx) => Ok(tx),
+    }
+}
+
+fn encode_tir(tx: &Tx) -> TirEnvelope {
+    let (bytes, _) = to_bytes(tx);
+    TirEnvelope {
+        content: hex::encode(bytes),
+        encoding: BytesEncoding::Hex,
+        version: IR_VERSION.to_string(),
+    }
+}
+
+fn parse_ref(utxo_ref: &str) -> Option<UtxoRef> {
+    let (tx_hash, index) = utxo_ref.split_once('#')?;
+    Some(UtxoRef::new(&hex::decode(tx_hash).ok()?, index.parse().ok()?))
+}
+
+// Plutus data as the TIR expression TRP gives the datum of a resolved UTxO
+fn datum_expression(data: &PlutusData) -> Expression {
+    match data {
+        PlutusData::Constr(constr) => Expression::Struct(StructExpr {
+            constructor: constructor_index(data).unwrap_or_default() as usize,
+            fields: constr.fields.iter().map(datum_expression).collect(),
+        }),
+        PlutusData::Map(pairs) => Expression::Map(
+            pairs
+                .iter()
+                .map(|(key, value)| (datum_expression(key), datum_expression(value)))
+                .collect(),
+        ),
+        PlutusData::BigInt(BigInt::Int(int)) => Expression::Number(i128::from(int.0)),
+        PlutusData::BigInt(BigInt::BigUInt(bytes)) => Expression::Number(big_uint(bytes)),
+        PlutusData::BigInt(BigInt::BigNInt(bytes)) => Expression::Number(-1 - big_uint(bytes)),
+        PlutusData::BoundedBytes(bytes) => Expression::Bytes(bytes.to_vec()),
+        PlutusData::Array(items) => Expression::List(items.iter().map(datum_expression).collect()),
+    }
+}
+
+fn big_uint(bytes: &[u8]) -> i128 {
+    bytes
+        .iter()
+        .fold(0i128, |value, byte| value.saturating_mul(256).saturating_add(*byte as i128))
+}
+
+// An output in the Ogmios UTxO format, as kept in the pending overlay
+fn ogmios_to_utxo(utxo: &Value) -> Result<Utxo, String> {
+    let tx_hash = utxo["transaction"]["id"].as_str().unwrap_or_default();
+    let index = utxo["index"].as_u64().unwrap_or_default();
+    let utxo_ref = parse_ref(&format!("{}#{}", tx_hash, index))
+        .ok_or_else(|| format!("invalid output reference {}#{}", tx_hash, index))?;
+
+    let address = utxo["address"].as_str().unwrap_or_default();
+    let address = Address::from_bech32(address)
+        .map_err(|e| format!("invalid address {:?}: {}", address, e))?;
+
+    let mut assets = CanonicalAssets::empty();
+    for (policy, names) in utxo["value"].as_object().into_iter().flatten() {
+        for (name, quantity) in names.as_object().into_iter().flatten() {
+            let quantity = quantity.as_i64().unwrap_or_default() as i128;
+            assets = assets
+                + if policy == "ada" {
+                    CanonicalAssets::from_naked_amount(quantity)
+                } else {
+                    let policy = hex::decode(policy).map_err(|e| e.to_string())?;
+                    let name = hex::decode(name).map_err(|e| e.to_string())?;
+                    CanonicalAssets::from_defined_asset(&policy, &name, quantity)
+                };
+        }
+    }
+
+    let datum = match utxo["datum"].as_str() {
+        Some(cbor) => {
+            let bytes = hex::decode(cbor).map_err(|e| e.to_string())?;
+            let data: PlutusData = minicbor::decode(&bytes).map_err(|e| e.to_string())?;
+            Some(datum_expression(&data))
+        }
+        None => None,
+    };
+
+    Ok(Utxo {
+        r#ref: utxo_ref,
+        address: address.to_vec(),
+        assets,
+        datum,
+        script: None,
+    })
+}
+
+// The string argument bound to a parameter, e.g. the `bountyref` of an input
+fn arg<'a>(args: &'a Map<String, Value>, expr: &Expression) -> Option<&'a str> {
+    match expr {
+        Expression::EvalParam(param) => match param.as_ref() {
+            Param::ExpectValue(name, _) => args.get(name)?.as_str(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Inputs naming an unconfirmed output, with that output as their resolved UTxO
+fn pending_inputs(tx: &Tx, args: &Map<String, Value>) -> Result<BTreeMap<String, UtxoSet>, String> {
+    let mut resolved = BTreeMap::new();
+    for (name, query) in tx.queries() {
+        let Some(utxo_ref) = arg(args, &query.r#ref) else {
+            continue;
+        };
+        if let Some(utxo) = pending::additional_utxos(&[utxo_ref.to_string()], &auth::caller()).first() {
+            let utxo = ogmios_to_utxo(utxo)
+                .map_err(|e| format!("pending output {}: {}", utxo_ref, e))?;
+            resolved.insert(name, UtxoSet::from([utxo]));
+        }
+    }
+    Ok(resolved)
+}
+
+fn request_ref(utxo_ref: &RequestUtxoRef) -> UtxoRef {
+    UtxoRef::new(
+        &hex::decode(utxo_ref.tx_hash.as_str()).unwrap_or_default(),
+        utxo_ref.index,
+    )
+}
+
+pub(crate) async fn query_utxos(params: Value) -> Result<Vec<Value>, String> {
+    let url = env("OGMIOS_ENDPOINT")?;
+    let api_key = env("DMTR_API_KEY_OGMIOS")?;
+    let (status, body) =
+        json_rpc(&Client::new(), &url, &api_key, "queryLedgerState/utxo", params).await?;
+    match body.get("result").and_then(Value::as_array) {
+        Some(utxos) => Ok(utxos.clone()),
+        None => Err(format!("Ogmios responded with {}: {}", status, body)),
+    }
+}
+
+// The UTxOs at `refs` of the request's `field`, taken from `unconfirmed` or
+// the pending overlay when they are not on chain yet
+async fn lookup_utxos(
+    field: &str,
+    refs: &[RequestUtxoRef],
+    unconfirmed: &[Value],
+) -> Result<UtxoSet, String> {
+    let refs: Vec<String> = refs.iter().map(RequestUtxoRef::to_string).collect();
+    let mut found: Vec<Value> = unconfirmed
+        .iter()
+        .filter(|utxo| refs.contains(&ogmios_ref(utxo)))
+        .cloned()
+        .collect();
+    found.extend(pending::additional_utxos(&refs, &auth::caller()));
+
+    let missing: Vec<Value> = refs
+        .iter()
+        .filter(|utxo_ref| !found.iter().any(|utxo| ogmios_ref(utxo) == **utxo_ref))
+        .filter_map(|utxo_ref| utxo_ref.split_once('#'))
+        .map(|(tx_hash, index)| {
+            json!({ "transaction": { "id": tx_hash }, "index": index.parse::<u64>().unwrap_or_default() })
+        })
+        .collect();
+    if !missing.is_empty() {
+        found.extend(query_utxos(json!({ "outputReferences": missing })).await?);
+    }
+
+    if let Some(absent) = refs
+        .iter()
+        .find(|utxo_ref| !found.iter().any(|utxo| ogmios_ref(utxo) == **utxo_ref))
+    {
+        return Err(format!("Invalid field `{}`: {} does not exist or is spent", field, absent));
+    }
+    found.iter().map(ogmios_to_utxo).collect()
+}
+
+// `txhash#index` of an Ogmios UTxO
+pub(crate) fn ogmios_ref(utxo: &Value) -> String {
+    format!(
+        "{}#{}",
+        utxo["transaction"]["id"].as_str().unwrap_or_default(),
+        utxo["index"].as_u64().unwrap_or_default()
+    )
+}
+
+// Refs of the wallet's UTxOs it may spend: not excluded and not already spent
+// by a pending transaction
+async fn spendable_refs(address: &str, exclude: &[RequestUtxoRef]) -> Result<Vec<UtxoRef>, String> {
+    let exclude: Vec<String> = exclude.iter().map(RequestUtxoRef::to_string).collect();
+    let utxos = query_utxos(json!({ "addresses": [address] })).await?;
+    let caller = auth::caller();
+    let refs: Vec<String> = utxos
+        .iter()
+        .map(ogmios_ref)
+        .filter(|utxo_ref| !exclude.contains(utxo_ref))
+        .filter(|utxo_ref| pending::spent_by_pending(std::slice::from_ref(utxo_ref), &caller).is_none())
+        .collect();
+
+    if refs.is_empty() {
+        return Err(format!(
+            "Invalid field `exclude_inputs`: no UTxO of {} is left to spend",
+            address
+        ));
+    }
+    Ok(refs.iter().filter_map(|utxo_ref| parse_ref(utxo_ref)).collect())
+}
+
+// The input paid from a party's wallet (`from: Party` without a `ref`), which
+// coin control applies to
+fn wallet_input(queries: &BTreeMap<String, InputQuery>) -> Result<Option<(&String, &InputQuery)>, String> {
+    let mut wallet = queries
+        .iter()
+        .filter(|(_, query)| !query.collateral && !query.address.is_none() && query.r#ref.is_none());
+    match (wallet.next(), wallet.next()) {
+        (Some(_), Some(_)) => Err("coin control needs a single wallet input".to_string()),
+        (first, _) => Ok(first),
+    }
+}
+
+// Swaps input queries for narrowed ones wherever the input is used
+struct Narrow {
+    queries: BTreeMap<String, InputQuery>,
+}
+
+impl Visitor for Narrow {
+    fn reduce(&mut self, expr: Expression) -> Result<Expression, ReduceError> {
+        if let Expression::EvalParam(param) = &expr {
+            if let Param::ExpectInput(name, _) = param.as_ref() {
+                if let Some(query) = self.queries.get(name) {
+                    return Ok(Param::ExpectInput(name.clone(), query.clone()).into());
+                }
+            }
+        }
+        Ok(expr)
+    }
+}
+
+// Applies coin control to the queries of a transaction: resolved wallet
+// inputs, and narrowed wallet and collateral queries
+async fn coin_control_inputs(
+    tx: &Tx,
+    args: &Map<String, Value>,
+    coin_control: &CoinControl,
+    unconfirmed: &[Value],
+) -> Result<(BTreeMap<String, UtxoSet>, Narrow), String> {
+    let queries = tx.queries();
+    let mut resolved = BTreeMap::new();
+    let mut narrow = Narrow {
+        queries: BTreeMap::new(),
+    };
+
+    if !coin_control.inputs.is_empty() || !coin_control.exclude_inputs.is_empty() {
+        let (name, query) = wallet_input(&queries)?
+            .ok_or("coin control needs a wallet input, this transaction has none")?;
+
+        if !coin_control.inputs.is_empty() {
+            // Exactly the requested UTxOs make up the wallet input
+            resolved.insert(name.clone(), lookup_utxos("inputs", &coin_control.inputs, unconfirmed).await?);
+        } else {
+            let address = arg(args, &query.address).ok_or("wallet address is not set")?;
+            let refs = spendable_refs(address, &coin_control.exclude_inputs).await?;
+            narrow.queries.insert(
+                name.clone(),
+                InputQuery {
+                    r#ref: Expression::UtxoRefs(refs),
+                    ..query.clone()
+                },
+            );
+        }
+    }
+
+    if !coin_control.collateral.is_empty() {
+        let pinned = lookup_utxos("collateral", &coin_control.collateral, unconfirmed).await?;
+        for (name, query) in queries.iter().filter(|(_, query)| query.collateral) {
+            // The collateral party signs for its collateral, so a UTxO held
+            // by anyone else would make the transaction unsignable
+            let address = arg(args, &query.address).ok_or("collateral address is not set")?;
+            let owner = Address::from_bech32(address)
+                .map_err(|e| format!("invalid collateral address {:?}: {}", address, e))?
+                .to_vec();
+            if let Some(utxo) = pinned.iter().find(|utxo| utxo.address != owner) {
+                return Err(format!(
+                    "Invalid field `collateral`: {} is not held by the collateral party {}",
+                    utxo.r#ref, address
+                ));
+            }
+            narrow.queries.insert(
+                name.clone(),
+                InputQuery {
+                    r#ref: Expression::UtxoRefs(coin_control.collateral.iter().map(request_ref).collect()),
+                    ..query.clone()
+                },
+            );
+        }
+    }
+
+    Ok((resolved, narrow))
+}
+
+// Resolves a protocol transaction from its compiled TIR (`protocol::*_IR`)
+// and its generated params. `unconfirmed` holds outputs, in the Ogmios UTxO
+// format, of transactions that are neither on chain nor pending yet.
+pub async fn resolve<P: Serialize>(
+    ir: &str,
+    params: &P,
+    coin_control: &CoinControl,
+    unconfirmed: &[Value],
+) -> Result<TxEnvelope, String> {
+    let Value::Object(args) = serde_json::to_value(params).map_err(|e| e.to_string())? else {
+        return Err("transaction params are not an object".to_string());
+    };
+
+    let tx = decode_tir(ir)?;
+    let (mut inputs, mut narrow) = coin_control_inputs(&tx, &args, coin_control, unconfirmed).await?;
+    inputs.extend(pending_inputs(&tx, &args)?);
+    let tx = tx
+        .apply(&mut narrow)
+        .and_then(|tx| tx.apply_inputs(&inputs))
+        .map_err(|e| e.to_string())?;
+
+    tx3_sdk::trp::Client::new(trp_options())
+        .resolve(ResolveParams {
+            tir: encode_tir(&tx),
+            args,
+        })
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCRIPT: &str = "addr_test1wr5nj776ygd7d7vwcw3f9fknj4axx0wzy67y3qu8dqtscys7w9q65";
+
+    fn bounty_utxo() -> Value {
+        json!({
+            "transaction": { "id": "aa".repeat(32) },
+            "index": 0,
+            "address": SCRIPT,
+            "value": {
+                "ada": { "lovelace": 2_000_000 },
+                "bb".repeat(28): { "0102": 1 }
+            },
+            // Constr 0 [42, h'cafe', []]
+            "datum": "d8799f182a42cafe80ff"
+        })
+    }
+
+    #[test]
+    fn tir_round_trips() {
+        let tx = decode_tir(protocol::ADD_IR).unwrap();
+        let encoded = encode_tir(&tx);
+        let decoded = decode_tir(&encoded.content).unwrap();
+        assert_eq!(tx.queries(), decoded.queries());
+        assert_eq!(tx.params(), decoded.params());
+    }
+
+    #[test]
+    fn converts_ogmios_utxos() {
+        let utxo = ogmios_to_utxo(&bounty_utxo()).unwrap();
+        assert_eq!(utxo.r#ref.to_string(), format!("{}#0", "aa".repeat(32)));
+        assert_eq!(utxo.assets.naked_amount(), Some(2_000_000));
+        assert_eq!(
+            utxo.assets.asset_amount2(&hex::decode("bb".repeat(28)).unwrap(), &[1, 2]),
+            Some(1)
+        );
+        assert_eq!(
+            utxo.datum,
+            Some(Expression::Struct(StructExpr {
+                constructor: 0,
+                fields: vec![
+                    Expression::Number(42),
+                    Expression::Bytes(vec![0xca, 0xfe]),
+                    Expression::List(vec![]),
+                ],
+            }))
+        );
+    }
+
+    #[test]
+    fn resolved_inputs_are_no_longer_queried() {
+        let tx = decode_tir(protocol::ADD_IR).unwrap();
+        assert!(tx.queries().contains_key("current"));
+
+        let utxo = ogmios_to_utxo(&bounty_utxo()).unwrap();
+        let inputs = BTreeMap::from([("current".to_string(), UtxoSet::from([utxo]))]);
+        let tx = tx.apply_inputs(&inputs).unwrap();
+
+        assert!(!tx.queries().contains_key("current"));
+        assert!(tx.queries().contains_key("sponsor_input"));
+        assert!(!tx.params().contains_key("bountyref"));
+    }
+
+    fn wallet_utxo() -> Value {
+        json!({
+            "transaction": { "id": "cc".repeat(32) },
+            "index": 1,
+            "address": SCRIPT,
+            "value": { "ada": { "lovelace": 10_000_000 } }
+        })
+    }
+
+    fn collateral_utxo(address: &str) -> Value {
+        json!({
+            "transaction": { "id": "dd".repeat(32) },
+            "index": 3,
+            "address": address,
+            "value": { "ada": { "lovelace": 5_000_000 } }
+        })
+    }
+
+    fn party(name: &str, address: &str) -> Map<String, Value> {
+        Map::from_iter([(name.to_string(), json!(address))])
+    }
+
+    #[tokio::test]
+    async fn coin_control_pins_inputs_and_collateral() {
+        let tx = decode_tir(protocol::ADD_IR).unwrap();
+        let coin_control = CoinControl {
+            inputs: vec![format!("{}#1", "cc".repeat(32)).parse().unwrap()],
+            exclude_inputs: vec![],
+            collateral: vec![format!("{}#3", "dd".repeat(32)).parse().unwrap()],
+        };
+
+        let unconfirmed = [wallet_utxo(), collateral_utxo(SCRIPT)];
+        let (inputs, mut narrow) =
+            coin_control_inputs(&tx, &party("sponsor", SCRIPT), &coin_control, &unconfirmed)
+                .await
+                .unwrap();
+        let tx = tx
+            .apply(&mut narrow)
+            .and_then(|tx| tx.apply_inputs(&inputs))
+            .unwrap();
+
+        let queries = tx.queries();
+        assert!(!queries.contains_key("sponsor_input"));
+        let collateral: Vec<_> = queries.values().filter(|query| query.collateral).collect();
+        assert!(!collateral.is_empty());
+        for query in collateral {
+            assert_eq!(
+                query.r#ref,
+                Expression::UtxoRefs(vec![parse_ref(&format!("{}#3", "dd".repeat(32))).unwrap()])
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn coin_control_leaves_unconstrained_transactions_alone() {
+        let tx = decode_tir(protocol::ADD_IR).unwrap();
+        let (inputs, narrow) =
+            coin_control_inputs(&tx, &Map::new(), &CoinControl::default(), &[])
+                .await
+                .unwrap();
+        assert!(inputs.is_empty());
+        assert!(narrow.queries.is_empty());
+    }
+
+    const ADMIN: &str = "addr_test1qpp8qndr4p5cjndgufqctlpklk7c9asf9jz6z76lcmjjyyavuam5ced7vsutn86dghwa46yz8cum5hdc42dv7fedaz6sgkx26d";
+    const GITHONEY: &str = "addr_test1qqzq2j55hh2ml3h08skfgg04lhh7n7epv2ycn90ntr6ys7zrxalmeg3lyamyahkfwdv6fylkyxj0stj8xpplusva7w7s40czuq";
+
+    #[tokio::test]
+    async fn pinned_collateral_is_the_close_collateral() {
+        let tx = decode_tir(protocol::CLOSE_BEFORE_CONTRIBUTOR_IR).unwrap();
+        let (_, original) = tx.queries().into_iter().find(|(_, query)| query.collateral).unwrap();
+        let pinned = format!("{}#3", "dd".repeat(32));
+        let coin_control = CoinControl {
+            collateral: vec![pinned.parse().unwrap()],
+            ..Default::default()
+        };
+
+        let args = party("admin", ADMIN);
+        let (inputs, mut narrow) =
+            coin_control_inputs(&tx, &args, &coin_control, &[collateral_utxo(ADMIN)])
+                .await
+                .unwrap();
+        assert!(inputs.is_empty());
+        let tx = tx.apply(&mut narrow).unwrap();
+
+        let queries = tx.queries();
+        let (_, collateral) = queries.iter().find(|(_, query)| query.collateral).unwrap();
+        assert_eq!(
+            collateral.r#ref,
+            Expression::UtxoRefs(vec![parse_ref(&pinned).unwrap()])
+        );
+        // Still paid from the admin, who holds the pinned UTxO
+        assert_eq!(collateral.address, original.address);
+        assert_eq!(arg(&args, &collateral.address), Some(ADMIN));
+        // The admin wallet input is left to TRP
+        assert!(queries.values().any(|query| !query.collateral && query.r#ref.is_none()));
+    }
+
+    #[tokio::test]
+    async fn rejects_collateral_held_by_another_party() {
+        let tx = decode_tir(protocol::CLOSE_BEFORE_CONTRIBUTOR_IR).unwrap();
+        let coin_control = CoinControl {
+            collateral: vec![format!("{}#3", "dd".repeat(32)).parse().unwrap()],
+            ..Default::default()
+        };
+
+        let error = coin_control_inputs(&tx, &party("admin", ADMIN), &coin_control, &[collateral_utxo(GITHONEY)])
+            .await
+            .err()
+            .unwrap();
+        assert!(error.contains("not held by the collateral party"));
+    }
+}
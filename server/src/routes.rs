@@ -1,13 +1,30 @@
-use axum::{routing::post, Json, Router};
-use once_cell::sync::Lazy;
+use axum::{
+    middleware,
+    routing::{get, post, MethodRouter},
+    Json, Router,
+};
 use std::collections::HashMap;
+use tower::ServiceBuilder;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
 use tx3_sdk::trp::TxEnvelope;
 
-use protocol::{AddParams, Client, ClientOptions, CreateWithLovelaceParams, DeployParams};
+use protocol::ClientOptions;
 
-use crate::evaluate_tx;
+use crate::params::{AddParams, CloseParams, CloseTx, CreateWithLovelaceParams, DeployParams};
 
-fn build_client() -> Client {
+use crate::auth::{self, Scope};
+use crate::coin_control::CoinControl;
+use crate::metrics::{self, BuildOutcome};
+use crate::{
+    batch, bounty_id, coin_control, evaluate_tx, github, health, idempotency, openapi, pending,
+    preview, rate_limit, request_id, resolve, time, tx_check,
+};
+
+// TRP client options for the current request, forwarding its `X-Request-Id`
+pub(crate) fn trp_options() -> ClientOptions {
     let trp_endpoint = std::env::var("TRP_ENDPOINT").unwrap();
     let dmtr_api_key: String = std::env::var("DMTR_API_KEY").unwrap();
     let headers: &[(&str, &str)] = &[("dmtr-api-key", dmtr_api_key.as_str())];
@@ -16,67 +33,278 @@ fn build_client() -> Client {
     for (key, value) in headers {
         headers_hm.insert(key.to_string(), value.to_string());
     }
-    let client_options = ClientOptions {
+    if let Some(id) = request_id::current() {
+        headers_hm.insert(request_id::REQUEST_ID_HEADER.to_string(), id);
+    }
+    ClientOptions {
         endpoint: trp_endpoint,
         headers: Some(headers_hm),
-    };
-    Client::new(client_options)
+    }
+}
+
+type Routes = Vec<(&'static str, MethodRouter)>;
+
+// Public bounty routes
+fn bounty_routes() -> Routes {
+    vec![
+        ("/create-bounty", post(create_bounty)),
+        ("/create-bounties", post(batch::create_bounties)),
+        ("/add-funds", post(add_funds)),
+        ("/add-funds-batch", post(batch::add_funds_batch)),
+        ("/derive-bounty-id", post(bounty_id::derive_bounty_id_route)),
+        ("/preview", post(preview::preview_route)),
+        ("/submit", post(pending::submit_route)),
+    ]
+}
+
+// Operator-only routes
+fn admin_routes() -> Routes {
+    vec![
+        ("/deploy-settings", post(deploy_settings)),
+        ("/close-bounty", post(close_bounty)),
+        ("/github-webhook/pending", get(github::pending_actions)),
+    ]
+}
+
+// Routes outside `auth::require`
+fn open_routes() -> Routes {
+    vec![
+        // GitHub deliveries are authenticated by their own signature
+        ("/github-webhook", post(github::github_webhook)),
+        // Worker deliveries are authenticated by the webhook signature
+        ("/tx-confirmed", post(pending::tx_confirmed_webhook)),
+        ("/openapi.json", get(openapi::openapi_spec)),
+        ("/metrics", get(metrics::metrics_route)),
+        ("/healthz", get(health::healthz)),
+        ("/readyz", get(health::readyz)),
+    ]
+}
+
+fn with_routes(routes: Routes) -> Router {
+    routes
+        .into_iter()
+        .fold(Router::new(), |router, (path, method)| router.route(path, method))
 }
 
-static PROTOCOL: Lazy<Client> = Lazy::new(|| build_client());
+// Every path the router serves, for checking the OpenAPI spec against
+#[cfg(test)]
+pub(crate) fn paths() -> Vec<&'static str> {
+    [bounty_routes(), admin_routes(), open_routes()]
+        .into_iter()
+        .flatten()
+        .map(|(path, _)| path)
+        .collect()
+}
 
 pub fn router() -> Router {
+    let bounty_routes = with_routes(bounty_routes())
+        .route_layer(middleware::from_fn(idempotency::idempotent))
+        .route_layer(middleware::from_fn(rate_limit::limit))
+        .route_layer(middleware::from_fn_with_state(Scope::Bounty, auth::require));
+
+    let admin_routes = with_routes(admin_routes())
+        .route_layer(middleware::from_fn(idempotency::idempotent))
+        .route_layer(middleware::from_fn(rate_limit::limit))
+        .route_layer(middleware::from_fn_with_state(Scope::Admin, auth::require));
+
     Router::new()
-        .route("/deploy-settings", post(deploy_settings))
-        .route("/create-bounty", post(create_bounty))
-        .route("/add-funds", post(add_funds))
+        .merge(bounty_routes)
+        .merge(admin_routes)
+        .merge(with_routes(open_routes()))
+        .layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::new(
+                    request_id::REQUEST_ID_HEADER,
+                    MakeRequestUuid,
+                ))
+                .layer(TraceLayer::new_for_http().make_span_with(request_id::make_span))
+                .layer(PropagateRequestIdLayer::new(request_id::REQUEST_ID_HEADER))
+                .layer(middleware::from_fn(request_id::scope))
+                .layer(middleware::from_fn(metrics::track)),
+        )
+}
+
+pub(crate) fn record_evaluated(tx: &str, result: &Result<TxEnvelope, String>) {
+    let outcome = match result {
+        Ok(_) => BuildOutcome::Ok,
+        Err(_) => BuildOutcome::EvaluationFailed,
+    };
+    metrics::record_build(tx, outcome);
+}
+
+// Withholds a built transaction that failed `tx_check`
+pub(crate) fn reject_built(tx_name: &str, tx: &TxEnvelope, error: String) -> Json<Result<TxEnvelope, String>> {
+    tracing::error!(tx_hash = %tx.hash, error = %error, "Built transaction failed checks");
+    metrics::record_build(tx_name, BuildOutcome::CheckFailed);
+    Json(Err(format!("Transaction check failed: {}", error)))
+}
+
+// Parses a request body into protocol params, reporting which field failed validation
+fn parse_params<T: serde::de::DeserializeOwned>(
+    body: serde_json::Map<String, serde_json::Value>,
+) -> Result<T, String> {
+    serde_path_to_error::deserialize(serde_json::Value::Object(body))
+        .map_err(|e| format!("Invalid field `{}`: {}", e.path(), e.inner()))
+}
+
+// Normalizes the time fields of a request body before parsing it
+pub(crate) fn parse_timed_params<T: serde::de::DeserializeOwned>(
+    mut body: serde_json::Map<String, serde_json::Value>,
+    deadline_field: Option<&str>,
+) -> Result<T, String> {
+    time::normalize_request_times(&mut body, deadline_field)?;
+    parse_params(body)
+}
+
+// Splits off the coin control fields, then parses the rest like `parse_timed_params`
+pub(crate) fn parse_build_params<T: serde::de::DeserializeOwned>(
+    mut body: serde_json::Map<String, serde_json::Value>,
+    deadline_field: Option<&str>,
+) -> Result<(T, CoinControl), String> {
+    let coin_control = coin_control::take(&mut body)?;
+    Ok((parse_timed_params(body, deadline_field)?, coin_control))
 }
 
 async fn create_bounty(
-    Json(req): Json<CreateWithLovelaceParams>,
+    Json(body): Json<serde_json::Map<String, serde_json::Value>>,
 ) -> Json<Result<TxEnvelope, String>> {
-    println!("Received create bounty request: {:?}", req);
+    let (req, coin_control): (CreateWithLovelaceParams, _) =
+        match parse_build_params(body, Some("time_limit")) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                tracing::warn!(error = %e, "Rejected create bounty request");
+                metrics::record_build("create_bounty", BuildOutcome::InvalidParams);
+                return Json(Err(e));
+            }
+        };
+    tracing::info!(params = ?req, "Received create bounty request");
 
-    match PROTOCOL.create_with_lovelace_tx(req).await {
+    let params = protocol::CreateWithLovelaceParams::from(req.clone());
+    let resolved = resolve::resolve(protocol::CREATE_WITH_LOVELACE_IR, &params, &coin_control, &[]);
+    match metrics::time_resolve("create_bounty", resolved).await {
         Ok(tx) => {
-            println!("Generated CBOR: {}", tx.tx);
-            let evaluated_tx_or_err = evaluate_tx::evaluate_tx(tx).await;
+            tracing::info!(tx_hash = %tx.hash, cbor = %tx.tx, "Generated transaction");
+            let checked = tx_check::check_create_bounty(&tx, &req).await;
+            if let Err(e) = checked.and_then(|()| coin_control.check(&tx)) {
+                return reject_built("create_bounty", &tx, e);
+            }
+            let evaluated_tx_or_err = evaluate_tx::evaluate_tx(tx, &[]).await;
+            record_evaluated("create_bounty", &evaluated_tx_or_err);
             Json(evaluated_tx_or_err)
         }
         Err(e) => {
-            println!("Error creating bounty: {:?}", e);
-            Json(Err(format!("Error creating bounty: {:?}", e)))
+            tracing::error!(error = ?e, "Error creating bounty");
+            metrics::record_build("create_bounty", BuildOutcome::ResolveError);
+            Json(Err(format!("Error creating bounty: {}", e)))
         }
     }
 }
 
-async fn add_funds(Json(req): Json<AddParams>) -> Json<Result<TxEnvelope, String>> {
-    println!("Received add funds request: {:?}", req);
+async fn add_funds(
+    Json(body): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Json<Result<TxEnvelope, String>> {
+    let (req, coin_control): (AddParams, _) = match parse_build_params(body, None) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!(error = %e, "Rejected add funds request");
+            metrics::record_build("add_funds", BuildOutcome::InvalidParams);
+            return Json(Err(e));
+        }
+    };
+    tracing::info!(params = ?req, "Received add funds request");
 
-    match PROTOCOL.add_tx(req).await {
+    let params = protocol::AddParams::from(req.clone());
+    match metrics::time_resolve("add_funds", resolve::resolve(protocol::ADD_IR, &params, &coin_control, &[])).await {
         Ok(tx) => {
-            println!("Generated CBOR: {}", tx.tx);
-            let evaluated_tx_or_err = evaluate_tx::evaluate_tx(tx).await;
+            tracing::info!(tx_hash = %tx.hash, cbor = %tx.tx, "Generated transaction");
+            let checked = tx_check::check_add_funds(&tx, &req).await;
+            if let Err(e) = checked.and_then(|()| coin_control.check(&tx)) {
+                return reject_built("add_funds", &tx, e);
+            }
+            let evaluated_tx_or_err = evaluate_tx::evaluate_tx(tx, &[]).await;
+            record_evaluated("add_funds", &evaluated_tx_or_err);
             Json(evaluated_tx_or_err)
         }
         Err(e) => {
-            println!("Error adding funds: {:?}", e);
-            Json(Err(format!("Error adding funds: {:?}", e)))
+            tracing::error!(error = ?e, "Error adding funds");
+            metrics::record_build("add_funds", BuildOutcome::ResolveError);
+            Json(Err(format!("Error adding funds: {}", e)))
         }
     }
 }
 
-async fn deploy_settings(Json(req): Json<DeployParams>) -> Json<Result<TxEnvelope, String>> {
-    println!("Received deploy settings request: {:?}", req);
+async fn close_bounty(
+    Json(body): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Json<Result<TxEnvelope, String>> {
+    let (req, coin_control): (CloseParams, _) = match parse_build_params(body, None) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!(error = %e, "Rejected close bounty request");
+            metrics::record_build("close_bounty", BuildOutcome::InvalidParams);
+            return Json(Err(e));
+        }
+    };
+    tracing::info!(params = ?req, "Received close bounty request");
+
+    let resolved = match CloseTx::from(req.clone()) {
+        CloseTx::BeforeContributor(params) => {
+            let resolved = resolve::resolve(protocol::CLOSE_BEFORE_CONTRIBUTOR_IR, &params, &coin_control, &[]);
+            metrics::time_resolve("close_bounty", resolved).await
+        }
+        CloseTx::AfterContributor(params) => {
+            let resolved = resolve::resolve(protocol::CLOSE_AFTER_CONTRIBUTOR_IR, &params, &coin_control, &[]);
+            metrics::time_resolve("close_bounty", resolved).await
+        }
+    };
+    match resolved {
+        Ok(tx) => {
+            tracing::info!(tx_hash = %tx.hash, cbor = %tx.tx, "Generated transaction");
+            let checked = tx_check::check_close_bounty(&tx, &req);
+            if let Err(e) = checked.and_then(|()| coin_control.check(&tx)) {
+                return reject_built("close_bounty", &tx, e);
+            }
+            let evaluated_tx_or_err = evaluate_tx::evaluate_tx(tx, &[]).await;
+            record_evaluated("close_bounty", &evaluated_tx_or_err);
+            Json(evaluated_tx_or_err)
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, "Error closing bounty");
+            metrics::record_build("close_bounty", BuildOutcome::ResolveError);
+            Json(Err(format!("Error closing bounty: {}", e)))
+        }
+    }
+}
+
+async fn deploy_settings(
+    Json(mut body): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Json<Result<TxEnvelope, String>> {
+    let parsed = coin_control::take(&mut body)
+        .and_then(|coin_control| Ok((parse_params::<DeployParams>(body)?, coin_control)));
+    let (req, coin_control) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!(error = %e, "Rejected deploy settings request");
+            metrics::record_build("deploy_settings", BuildOutcome::InvalidParams);
+            return Json(Err(e));
+        }
+    };
+    tracing::info!(params = ?req, "Received deploy settings request");
 
-    match PROTOCOL.deploy_tx(req).await {
+    let params = protocol::DeployParams::from(req);
+    match metrics::time_resolve("deploy_settings", resolve::resolve(protocol::DEPLOY_IR, &params, &coin_control, &[])).await {
         Ok(tx) => {
-            println!("Generated CBOR: {}", tx.tx);
+            tracing::info!(tx_hash = %tx.hash, cbor = %tx.tx, "Generated transaction");
+            if let Err(e) = coin_control.check(&tx) {
+                return reject_built("deploy_settings", &tx, e);
+            }
+            metrics::record_build("deploy_settings", BuildOutcome::Ok);
+            pending::record_built(&tx, &auth::caller());
             Json(Ok(tx))
         }
         Err(e) => {
-            println!("Error deploying settings: {:?}", e);
-            Json(Err(format!("Error deploying settings: {:?}", e)))
+            tracing::error!(error = ?e, "Error deploying settings");
+            metrics::record_build("deploy_settings", BuildOutcome::ResolveError);
+            Json(Err(format!("Error deploying settings: {}", e)))
         }
     }
 }
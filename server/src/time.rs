@@ -0,0 +1,56 @@
+pub use githoney_common::time::{resolve_deadline, resolve_validity, SlotConfig};
+
+// Slot parameters for `CARDANO_NETWORK` (defaults to preprod). Checked once at
+// startup, so an unknown network stops the server instead of failing requests.
+pub fn network_slots() -> Result<SlotConfig, String> {
+    let network = std::env::var("CARDANO_NETWORK").unwrap_or_else(|_| "preprod".to_string());
+    SlotConfig::for_network(&network).ok_or_else(|| {
+        format!(
+            "Unknown CARDANO_NETWORK {:?}, expected mainnet, preprod or preview",
+            network
+        )
+    })
+}
+
+pub fn now_posix_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn string_field(body: &serde_json::Map<String, serde_json::Value>, field: &str) -> Option<String> {
+    match body.get(field)? {
+        serde_json::Value::String(value) => Some(value.clone()),
+        serde_json::Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+// Rewrites the time fields of a raw request body into the slot and POSIX ms
+// numbers the protocol expects, filling in defaults from the current time.
+pub fn normalize_request_times(
+    body: &mut serde_json::Map<String, serde_json::Value>,
+    deadline_field: Option<&str>,
+) -> Result<(), String> {
+    let slots = network_slots()?;
+    let now = now_posix_ms();
+
+    let (since, until) = resolve_validity(
+        &slots,
+        Some(now),
+        string_field(body, "since").as_deref(),
+        string_field(body, "until").as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Some(field) = deadline_field {
+        let deadline = resolve_deadline(Some(now), string_field(body, field).as_deref())
+            .map_err(|e| e.to_string())?;
+        body.insert(field.to_string(), deadline.into());
+    }
+
+    body.insert("since".to_string(), since.into());
+    body.insert("until".to_string(), until.into());
+    Ok(())
+}
@@ -0,0 +1,484 @@
+use once_cell::sync::Lazy;
+use pallas_addresses::{Address, ShelleyDelegationPart};
+use pallas_primitives::conway::MintedDatumOption;
+use pallas_traverse::{MultiEraOutput, MultiEraTx};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tx3_sdk::trp::TxEnvelope;
+
+use githoney_common::types::{BountyId, PolicyId, UtxoRef};
+
+use crate::params::{AddParams, AssignParams, CloseParams, CreateWithLovelaceParams, MergeParams};
+
+use githoney_common::datum::{
+    decode_githoney_datum, decode_settings_datum, same_datum, GithoneyDatum, SettingsDatum,
+};
+use crate::auth;
+use crate::health::{env, json_rpc};
+use crate::pending;
+use crate::preview::address_text;
+use crate::resolve::{ogmios_ref, query_utxos};
+
+// Values built transactions are checked against. All of them are required
+// and validated at startup, see `config`.
+pub struct CheckConfig {
+    pub(crate) script: Address,
+    pub(crate) minting_policy_id: PolicyId,
+    pub(crate) settings_ref: UtxoRef,
+}
+
+fn load_config() -> Result<CheckConfig, String> {
+    let script = env("GITHONEY_SCRIPT_ADDRESS")?;
+    let script = Address::from_bech32(&script)
+        .map_err(|e| format!("Invalid GITHONEY_SCRIPT_ADDRESS {:?}: {}", script, e))?;
+    let minting_policy_id = env("MINTING_POLICY_ID")?
+        .parse()
+        .map_err(|e| format!("Invalid MINTING_POLICY_ID: {}", e))?;
+    let settings_ref = env("SETTINGS_REF")?
+        .parse()
+        .map_err(|e| format!("Invalid SETTINGS_REF: {}", e))?;
+
+    Ok(CheckConfig {
+        script,
+        minting_policy_id,
+        settings_ref,
+    })
+}
+
+static CONFIG: Lazy<Result<CheckConfig, String>> = Lazy::new(load_config);
+
+// Checked once at startup, so a missing or invalid value stops the server
+// instead of failing every build
+pub fn config() -> Result<&'static CheckConfig, String> {
+    CONFIG.as_ref().map_err(Clone::clone)
+}
+
+// SettingsDatum of the last settings UTxO read, keyed by its reference
+static SETTINGS: Lazy<Mutex<Option<(String, SettingsDatum)>>> = Lazy::new(|| Mutex::new(None));
+
+async fn load_settings(settings_ref: &UtxoRef) -> Result<SettingsDatum, String> {
+    let key = settings_ref.to_string();
+    if let Some((cached_ref, datum)) = SETTINGS.lock().unwrap().as_ref() {
+        if *cached_ref == key {
+            return Ok(datum.clone());
+        }
+    }
+
+    let url = env("OGMIOS_ENDPOINT")?;
+    let api_key = env("DMTR_API_KEY_OGMIOS")?;
+    let params = json!({
+        "outputReferences": [{
+            "transaction": { "id": settings_ref.tx_hash.as_str() },
+            "index": settings_ref.index
+        }]
+    });
+    let (status, body) =
+        json_rpc(&Client::new(), &url, &api_key, "queryLedgerState/utxo", params).await?;
+
+    let utxo = match body.get("result").and_then(Value::as_array) {
+        Some(utxos) => utxos
+            .first()
+            .ok_or_else(|| format!("settings UTxO {} is spent or does not exist", key))?,
+        None => return Err(format!("Ogmios responded with {}: {}", status, body)),
+    };
+
+    let datum = utxo["datum"]
+        .as_str()
+        .and_then(|datum| hex::decode(datum).ok())
+        .and_then(|cbor| decode_settings_datum(&cbor))
+        .ok_or_else(|| format!("settings UTxO {} has no valid SettingsDatum", key))?;
+
+    *SETTINGS.lock().unwrap() = Some((key, datum.clone()));
+    Ok(datum)
+}
+
+fn decode_tx(tx: &TxEnvelope) -> Result<Vec<u8>, String> {
+    hex::decode(&tx.tx).map_err(|e| format!("transaction is not valid hex: {}", e))
+}
+
+fn references_settings(tx: &MultiEraTx, settings_ref: &UtxoRef) -> Result<(), String> {
+    let referenced = tx.reference_inputs().iter().any(|input| {
+        hex::encode(input.hash()) == settings_ref.tx_hash.as_str()
+            && input.index() == u64::from(settings_ref.index)
+    });
+
+    if !referenced {
+        return Err(format!("settings UTxO {} is not a reference input", settings_ref));
+    }
+    Ok(())
+}
+
+fn spends_bounty(tx: &MultiEraTx, bountyref: &UtxoRef) -> Result<(), String> {
+    let spent = tx.inputs().iter().any(|input| {
+        hex::encode(input.hash()) == bountyref.tx_hash.as_str()
+            && input.index() == u64::from(bountyref.index)
+    });
+    if !spent {
+        return Err(format!("bounty UTxO {} is not spent", bountyref));
+    }
+    Ok(())
+}
+
+// (asset name, quantity) of every asset minted or burned under `policy_id`
+fn minted(tx: &MultiEraTx, policy_id: &str) -> Vec<(String, i128)> {
+    tx.mints()
+        .iter()
+        .filter(|policy| hex::encode(policy.policy()) == policy_id)
+        .flat_map(|policy| policy.assets())
+        .map(|asset| (hex::encode(asset.name()), asset.any_coin()))
+        .collect()
+}
+
+// The `count` outputs locked at the script
+fn bounty_outputs<'t>(
+    tx: &'t MultiEraTx<'_>,
+    script: &Address,
+    count: usize,
+) -> Result<Vec<MultiEraOutput<'t>>, String> {
+    let outputs: Vec<_> = tx
+        .outputs()
+        .into_iter()
+        .filter(|output| {
+            output
+                .address()
+                .map(|address| address.to_vec() == script.to_vec())
+                .unwrap_or(false)
+        })
+        .collect();
+
+    match outputs.len() {
+        n if n == count => Ok(outputs),
+        0 => Err("no output goes to the script address".to_string()),
+        n => Err(format!("{} outputs go to the script address, expected {}", n, count)),
+    }
+}
+
+// The single output locked at the script
+fn bounty_output<'t>(
+    tx: &'t MultiEraTx<'_>,
+    script: &Address,
+) -> Result<MultiEraOutput<'t>, String> {
+    Ok(bounty_outputs(tx, script, 1)?.remove(0))
+}
+
+fn bounty_datum(output: &MultiEraOutput) -> Result<GithoneyDatum, String> {
+    match output.datum() {
+        Some(MintedDatumOption::Data(data)) => decode_githoney_datum(data.0.raw_cbor())
+            .ok_or_else(|| "bounty output datum is not a valid GithoneyDatum".to_string()),
+        _ => Err("bounty output has no inline datum".to_string()),
+    }
+}
+
+// Names of the bounty NFTs the output holds; each must have a quantity of 1
+fn bounty_nfts(output: &MultiEraOutput, policy_id: &str) -> Result<Vec<String>, String> {
+    output
+        .non_ada_assets()
+        .iter()
+        .filter(|policy| hex::encode(policy.policy()) == policy_id)
+        .flat_map(|policy| policy.assets())
+        .map(|asset| match asset.any_coin() {
+            1 => Ok(hex::encode(asset.name())),
+            quantity => Err(format!(
+                "bounty output holds {} of token {}, expected 1",
+                quantity,
+                hex::encode(asset.name())
+            )),
+        })
+        .collect()
+}
+
+fn pays_to(address: &Address, payment_key: &str, stake_key: Option<&str>) -> bool {
+    let Address::Shelley(shelley) = address else {
+        return false;
+    };
+    let delegation = match shelley.delegation() {
+        ShelleyDelegationPart::Key(hash) => Some(hex::encode(hash)),
+        _ => None,
+    };
+
+    hex::encode(shelley.payment().as_hash()) == payment_key && delegation.as_deref() == stake_key
+}
+
+// Confirms a create bounty transaction mints exactly one bounty NFT, locks it
+// at the script with a fresh GithoneyDatum and pays the creation fee to the
+// `githoney_address` of the SettingsDatum.
+pub async fn check_create_bounty(
+    tx: &TxEnvelope,
+    params: &CreateWithLovelaceParams,
+) -> Result<(), String> {
+    let config = config()?;
+    let settings = load_settings(&config.settings_ref).await?;
+    let policy_id = config.minting_policy_id.as_str();
+    let bounty_id = params.bounty_id.as_str();
+
+    let bytes = decode_tx(tx)?;
+    let tx = MultiEraTx::decode(&bytes).map_err(|e| format!("transaction does not decode: {}", e))?;
+
+    references_settings(&tx, &config.settings_ref)?;
+
+    let mint = minted(&tx, policy_id);
+    if mint != [(bounty_id.to_string(), 1)] {
+        return Err(format!(
+            "expected a mint of exactly 1 bounty token {}, got {:?}",
+            bounty_id, mint
+        ));
+    }
+
+    let output = bounty_output(&tx, &config.script)?;
+    let nfts = bounty_nfts(&output, policy_id)?;
+    if nfts != [bounty_id] {
+        return Err(format!("bounty output holds tokens {:?}, expected {}", nfts, bounty_id));
+    }
+
+    let datum = bounty_datum(&output)?;
+    if datum.admin_payment_key != params.admin_payment_key.as_str() {
+        return Err(format!("datum admin key is {}", datum.admin_payment_key));
+    }
+    if datum.maintainer_payment_key != params.maintainer_payment_key.as_str() {
+        return Err(format!("datum maintainer key is {}", datum.maintainer_payment_key));
+    }
+    if datum.contributor_payment_key.is_some() || datum.merged {
+        return Err("datum of a new bounty is already assigned or merged".to_string());
+    }
+    if datum.bounty_reward_fee != settings.bounty_reward_fee {
+        return Err(format!(
+            "datum reward fee is {} but the SettingsDatum requires {}",
+            datum.bounty_reward_fee, settings.bounty_reward_fee
+        ));
+    }
+
+    let fee_paid = tx.outputs().iter().any(|output| {
+        output
+            .address()
+            .map(|address| {
+                pays_to(
+                    &address,
+                    &settings.githoney_payment_key,
+                    settings.githoney_stake_key.as_deref(),
+                )
+            })
+            .unwrap_or(false)
+            && output.lovelace_amount() >= settings.bounty_creation_fee
+    });
+    if !fee_paid {
+        return Err(format!(
+            "no output pays the {} lovelace creation fee to the SettingsDatum githoney_address",
+            settings.bounty_creation_fee
+        ));
+    }
+
+    Ok(())
+}
+
+// Confirms an add funds transaction spends the requested bounty UTxO, mints
+// nothing and keeps the bounty NFT at the script with its GithoneyDatum.
+pub async fn check_add_funds(tx: &TxEnvelope, params: &AddParams) -> Result<(), String> {
+    check_add_funds_batch(tx, std::slice::from_ref(params)).await
+}
+
+// The spent bounty UTxOs at `refs` in the Ogmios format, in order, taken from
+// the caller's pending overlay when they are not on chain yet
+async fn spent_bounties(refs: &[&UtxoRef]) -> Result<Vec<Value>, String> {
+    let refs: Vec<String> = refs.iter().map(|utxo_ref| utxo_ref.to_string()).collect();
+    let mut found = pending::additional_utxos(&refs, &auth::caller());
+
+    let missing: Vec<Value> = refs
+        .iter()
+        .filter(|utxo_ref| !found.iter().any(|utxo| ogmios_ref(utxo) == **utxo_ref))
+        .filter_map(|utxo_ref| utxo_ref.split_once('#'))
+        .map(|(tx_hash, index)| {
+            json!({ "transaction": { "id": tx_hash }, "index": index.parse::<u64>().unwrap_or_default() })
+        })
+        .collect();
+    if !missing.is_empty() {
+        found.extend(query_utxos(json!({ "outputReferences": missing })).await?);
+    }
+
+    refs.iter()
+        .map(|utxo_ref| {
+            found
+                .iter()
+                .find(|utxo| ogmios_ref(utxo) == *utxo_ref)
+                .cloned()
+                .ok_or_else(|| format!("bounty UTxO {} is spent or does not exist", utxo_ref))
+        })
+        .collect()
+}
+
+// Like `check_add_funds`, for an `add2`/`add3` transaction topping up every
+// bounty of `top_ups` at once. Each bounty must continue in the script output
+// holding its own NFT, with the datum of the UTxO it spent.
+pub async fn check_add_funds_batch(tx: &TxEnvelope, top_ups: &[AddParams]) -> Result<(), String> {
+    let config = config()?;
+    let policy_id = config.minting_policy_id.as_str();
+
+    let bytes = decode_tx(tx)?;
+    let tx = MultiEraTx::decode(&bytes).map_err(|e| format!("transaction does not decode: {}", e))?;
+
+    references_settings(&tx, &config.settings_ref)?;
+
+    for top_up in top_ups {
+        spends_bounty(&tx, &top_up.bountyref)?;
+    }
+
+    let mint = minted(&tx, policy_id);
+    if !mint.is_empty() {
+        return Err(format!("adding funds must not mint or burn bounty tokens, got {:?}", mint));
+    }
+
+    // Each spent bounty claims its output, so no output is vouched for twice
+    let mut outputs = bounty_outputs(&tx, &config.script, top_ups.len())?;
+    let refs: Vec<&UtxoRef> = top_ups.iter().map(|top_up| &top_up.bountyref).collect();
+    for (bountyref, spent) in refs.iter().zip(spent_bounties(&refs).await?) {
+        let nfts: Vec<&String> = spent[policy_id]
+            .as_object()
+            .map(|assets| assets.keys().collect())
+            .unwrap_or_default();
+        let [nft] = nfts.as_slice() else {
+            return Err(format!("bounty UTxO {} holds tokens {:?}, expected 1", bountyref, nfts));
+        };
+
+        let position = outputs
+            .iter()
+            .position(|output| bounty_nfts(output, policy_id).is_ok_and(|held| held == [nft.as_str()]))
+            .ok_or_else(|| format!("no script output holds only bounty token {} of {}", nft, bountyref))?;
+        let output = outputs.swap_remove(position);
+
+        let spent_datum = spent["datum"].as_str().and_then(|datum| hex::decode(datum).ok());
+        let same = match (output.datum(), spent_datum) {
+            (Some(MintedDatumOption::Data(data)), Some(spent_datum)) => {
+                same_datum(data.0.raw_cbor(), &spent_datum)
+            }
+            _ => false,
+        };
+        if !same {
+            return Err(format!(
+                "bounty output holding {} does not keep the datum of {}",
+                nft, bountyref
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Confirms a close transaction spends the requested bounty UTxO, burns its
+// bounty NFT, leaves nothing at the script and refunds the contributor's
+// deposit when there is one.
+pub fn check_close_bounty(tx: &TxEnvelope, params: &CloseParams) -> Result<(), String> {
+    let config = config()?;
+    let policy_id = config.minting_policy_id.as_str();
+
+    let bytes = decode_tx(tx)?;
+    let tx = MultiEraTx::decode(&bytes).map_err(|e| format!("transaction does not decode: {}", e))?;
+
+    references_settings(&tx, &config.settings_ref)?;
+    spends_bounty(&tx, &params.bountyref)?;
+
+    let mint = minted(&tx, policy_id);
+    if mint != [(params.bounty_id.to_string(), -1)] {
+        return Err(format!(
+            "expected to burn bounty token {}, got {:?}",
+            params.bounty_id, mint
+        ));
+    }
+
+    let locks_funds = tx.outputs().iter().any(|output| {
+        output
+            .address()
+            .map(|address| address.to_vec() == config.script.to_vec())
+            .unwrap_or(false)
+    });
+    if locks_funds {
+        return Err("closing a bounty must not lock an output at the script".to_string());
+    }
+
+    if let Some(contributor) = &params.contributor {
+        let refunded = tx.outputs().iter().any(|output| {
+            output
+                .address()
+                .map(|address| address_text(&address) == contributor.address.as_str())
+                .unwrap_or(false)
+                && output.lovelace_amount() >= contributor.deposit
+        });
+        if !refunded {
+            return Err(format!(
+                "expected to refund {} lovelace to the contributor",
+                contributor.deposit
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// The datum of the single script output of a transaction that moves bounty
+// `bounty_id` without minting or burning, once it is checked to hold its NFT
+fn continuing_bounty(
+    tx: &MultiEraTx,
+    bountyref: &UtxoRef,
+    bounty_id: &BountyId,
+) -> Result<GithoneyDatum, String> {
+    let config = config()?;
+    let policy_id = config.minting_policy_id.as_str();
+
+    references_settings(tx, &config.settings_ref)?;
+    spends_bounty(tx, bountyref)?;
+
+    let mint = minted(tx, policy_id);
+    if !mint.is_empty() {
+        return Err(format!("expected no bounty tokens to be minted or burned, got {:?}", mint));
+    }
+
+    let output = bounty_output(tx, &config.script)?;
+    let nfts = bounty_nfts(&output, policy_id)?;
+    if nfts != [bounty_id.as_str()] {
+        return Err(format!("bounty output holds tokens {:?}, expected {}", nfts, bounty_id));
+    }
+    bounty_datum(&output)
+}
+
+// Confirms an assign transaction keeps the bounty NFT at the script with the
+// requested contributor in its datum.
+pub fn check_assign_bounty(tx: &TxEnvelope, params: &AssignParams, bounty_id: &BountyId) -> Result<(), String> {
+    let bytes = decode_tx(tx)?;
+    let tx = MultiEraTx::decode(&bytes).map_err(|e| format!("transaction does not decode: {}", e))?;
+
+    let datum = continuing_bounty(&tx, &params.bountyref, bounty_id)?;
+    let contributor = (datum.contributor_payment_key.as_deref(), datum.contributor_stake_key.as_deref());
+    if contributor != (Some(params.contributor_payment_key.as_str()), Some(params.contributor_stake_key.as_str())) {
+        return Err(format!("datum contributor is {:?}", contributor));
+    }
+    if datum.merged {
+        return Err("datum of an assigned bounty is already merged".to_string());
+    }
+    Ok(())
+}
+
+// Confirms a merge transaction keeps the bounty NFT at the script with a merged
+// datum and pays the reward fee to GitHoney.
+pub fn check_merge_bounty(tx: &TxEnvelope, params: &MergeParams, bounty_id: &BountyId) -> Result<(), String> {
+    let bytes = decode_tx(tx)?;
+    let tx = MultiEraTx::decode(&bytes).map_err(|e| format!("transaction does not decode: {}", e))?;
+
+    let datum = continuing_bounty(&tx, &params.bountyref, bounty_id)?;
+    if !datum.merged || datum.contributor_payment_key.is_none() {
+        return Err("datum of a merged bounty must be assigned and merged".to_string());
+    }
+
+    let fee_paid = tx.outputs().iter().any(|output| {
+        output
+            .address()
+            .map(|address| address_text(&address) == params.githoneyaddr.as_str())
+            .unwrap_or(false)
+            && output.lovelace_amount() >= params.githoney_fee
+    });
+    if !fee_paid {
+        return Err(format!(
+            "no output pays the {} lovelace reward fee to the githoney address",
+            params.githoney_fee
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use balius_sdk::{Config, Json, Params, WorkerResult};
+use pallas_crypto::hash::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::bounty_index::load_bounty;
+use crate::errors::BountyError;
+use crate::types::{BountyId, WorkerConfig};
+
+// Canonical bounty id for a GitHub issue: blake2b-256 of `owner/repo#issue`,
+// hex-encoded so it can be used directly as the bounty NFT asset name.
+pub fn derive_bounty_id(repo: &str, issue: u64) -> String {
+    let canonical = format!("{}#{}", repo.trim().to_lowercase(), issue);
+    hex::encode(Hasher::<256>::hash(canonical.as_bytes()).as_ref())
+}
+
+// Pre-flight check for a new bounty: derives the id from `repo`/`issue` when given
+// and rejects ids that are already in the bounty index.
+pub fn new_bounty_id(
+    bounty_id: Option<&BountyId>,
+    repo: Option<&str>,
+    issue: Option<u64>,
+) -> Result<String, BountyError> {
+    let bounty_id = match (bounty_id, repo, issue) {
+        (explicit, Some(repo), Some(issue)) => {
+            let derived = derive_bounty_id(repo, issue);
+            if explicit.is_some_and(|explicit| explicit.as_str() != derived) {
+                return Err(BountyError::InvalidBountyId(format!(
+                    "{} does not match the id derived from {}#{}",
+                    explicit.map(BountyId::as_str).unwrap_or_default(),
+                    repo,
+                    issue
+                )));
+            }
+            derived
+        }
+        (Some(explicit), _, _) => explicit.to_string(),
+        (None, _, _) => {
+            return Err(BountyError::InvalidBountyId(
+                "either bounty_id or repo and issue are required".to_string(),
+            ))
+        }
+    };
+
+    if load_bounty(&bounty_id).is_some() {
+        return Err(BountyError::DuplicateBountyId(bounty_id));
+    }
+
+    Ok(bounty_id)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeriveBountyIdParams {
+    pub repo: String,
+    pub issue: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeriveBountyIdResponse {
+    pub bounty_id: String,
+    // Whether a bounty with this id is already in the index
+    pub exists: bool,
+}
+
+pub fn derive_bounty_id_handler(
+    _config: Config<WorkerConfig>,
+    params: Params<DeriveBountyIdParams>,
+) -> WorkerResult<Json<DeriveBountyIdResponse>> {
+    let bounty_id = derive_bounty_id(&params.repo, params.issue);
+    let exists = load_bounty(&bounty_id).is_some();
+
+    Ok(Json(DeriveBountyIdResponse { bounty_id, exists }))
+}
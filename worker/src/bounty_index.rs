@@ -0,0 +1,546 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::wit::balius::app::kv;
+use balius_sdk::{Tx, WorkerResult};
+use serde::{Deserialize, Serialize};
+
+use githoney_common::datum::{decode_githoney_datum, GithoneyDatum};
+use serde_json::json;
+use crate::deadlines::index_deadline;
+use crate::errors::BountyError;
+use crate::types::WorkerConfig;
+use crate::webhooks::{emit, EventType};
+use crate::utils::log_event;
+
+// KV layout for the bounty index
+const BOUNTY_KEY_PREFIX: &str = "bounty:";
+const BOUNTY_IDS_KEY: &str = "bounty-ids";
+const BOUNTY_REF_KEY_PREFIX: &str = "bounty-ref:";
+const CHAIN_TIP_SLOT_KEY: &str = "chain-tip-slot";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BountyStatus {
+    Open,
+    Assigned,
+    Merged,
+    Expired,
+    Claimed,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BountyAction {
+    Create,
+    Add,
+    Assign,
+    Merge,
+    Claim,
+    Close,
+}
+
+impl BountyAction {
+    pub fn event_type(self) -> EventType {
+        match self {
+            BountyAction::Create => EventType::BountyCreated,
+            BountyAction::Add => EventType::FundsAdded,
+            BountyAction::Assign => EventType::Assigned,
+            BountyAction::Merge => EventType::Merged,
+            BountyAction::Claim => EventType::Claimed,
+            BountyAction::Close => EventType::Closed,
+        }
+    }
+}
+
+// A single state transition of a bounty, as seen on chain
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BountyEvent {
+    pub action: BountyAction,
+    pub tx_hash: String,
+    pub block_hash: String,
+    pub block_height: u64,
+    pub block_slot: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BountyRecord {
+    // Hex-encoded asset name of the bounty NFT under `minting_policy_id`
+    pub bounty_id: String,
+    // `txhash#index` of the live bounty UTxO, None once the NFT has been burned
+    pub utxo_ref: Option<String>,
+    pub admin_payment_key: String,
+    pub maintainer_payment_key: String,
+    pub maintainer_stake_key: Option<String>,
+    pub contributor_payment_key: Option<String>,
+    #[serde(default)]
+    pub contributor_stake_key: Option<String>,
+    // Lovelace the contributor added on assignment, refunded if the bounty closes
+    #[serde(default)]
+    pub contributor_deposit: Option<u64>,
+    pub lovelace: u64,
+    pub bounty_reward_fee: u64,
+    // POSIX time in milliseconds
+    pub deadline: u64,
+    pub merged: bool,
+    pub status: BountyStatus,
+    pub history: Vec<BountyEvent>,
+}
+
+impl BountyRecord {
+    // Open and assigned bounties past their deadline are reported as expired
+    pub fn effective_status(&self, now_posix_ms: u64) -> BountyStatus {
+        match self.status {
+            BountyStatus::Open | BountyStatus::Assigned if self.deadline < now_posix_ms => {
+                BountyStatus::Expired
+            }
+            status => status,
+        }
+    }
+}
+
+fn bounty_key(bounty_id: &str) -> String {
+    format!("{}{}", BOUNTY_KEY_PREFIX, bounty_id)
+}
+
+pub fn load_bounty(bounty_id: &str) -> Option<BountyRecord> {
+    kv::get_value(&bounty_key(bounty_id))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+pub fn save_bounty(record: &BountyRecord) -> WorkerResult<()> {
+    let value = serde_json::to_vec(record)?;
+    kv::set_value(&bounty_key(&record.bounty_id), &value)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to store bounty: {:?}", e)))?;
+    index_deadline(record)?;
+
+    let mut ids = list_bounty_ids();
+    if !ids.contains(&record.bounty_id) {
+        ids.push(record.bounty_id.clone());
+        kv::set_value(BOUNTY_IDS_KEY, &serde_json::to_vec(&ids)?).map_err(|e| {
+            balius_sdk::Error::Internal(format!("Failed to store bounty index: {:?}", e))
+        })?;
+    }
+
+    Ok(())
+}
+
+fn bounty_ref_key(bounty_ref: &str) -> String {
+    format!("{}{}", BOUNTY_REF_KEY_PREFIX, bounty_ref)
+}
+
+// Remembers which bounty a UTxO belonged to, so spent refs can be recognised later
+fn save_bounty_ref(bounty_ref: &str, bounty_id: &str) -> WorkerResult<()> {
+    kv::set_value(&bounty_ref_key(bounty_ref), bounty_id.as_bytes())
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to store bounty ref: {:?}", e)))
+}
+
+pub fn bounty_id_for_ref(bounty_ref: &str) -> Option<String> {
+    kv::get_value(&bounty_ref_key(bounty_ref))
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+}
+
+// Resolves the bounty UTxO to spend: the explicit ref if it is still live,
+// otherwise the latest indexed UTxO for `bounty_id`. When both are given, an
+// indexed ref must belong to that bounty.
+pub fn resolve_bounty_ref(
+    bounty_id: Option<&str>,
+    bounty_ref: Option<&str>,
+) -> Result<String, BountyError> {
+    match (bounty_id, bounty_ref) {
+        (bounty_id, Some(bounty_ref)) => {
+            let Some(record) = bounty_id_for_ref(bounty_ref).and_then(|id| load_bounty(&id)) else {
+                // Not indexed yet (e.g. created before the worker started), trust the caller
+                return Ok(bounty_ref.to_string());
+            };
+
+            if let Some(bounty_id) = bounty_id.filter(|id| *id != record.bounty_id) {
+                return Err(BountyError::BountyRefMismatch {
+                    bounty_ref: bounty_ref.to_string(),
+                    bounty_id: bounty_id.to_string(),
+                    indexed_bounty_id: record.bounty_id,
+                });
+            }
+
+            if record.utxo_ref.as_deref() != Some(bounty_ref) {
+                return Err(BountyError::StaleBountyRef {
+                    bounty_ref: bounty_ref.to_string(),
+                    current_ref: record.utxo_ref,
+                });
+            }
+
+            Ok(bounty_ref.to_string())
+        }
+        (Some(bounty_id), None) => {
+            let record =
+                load_bounty(bounty_id).ok_or_else(|| BountyError::NotFound(bounty_id.to_string()))?;
+
+            record
+                .utxo_ref
+                .ok_or_else(|| BountyError::NotLive(bounty_id.to_string()))
+        }
+        (None, None) => Err(BountyError::MissingBountyRef),
+    }
+}
+
+// Bounty ids in the order they were first indexed
+pub fn list_bounty_ids() -> Vec<String> {
+    kv::get_value(BOUNTY_IDS_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+// Slot of the latest block seen, None until the first block arrives
+pub fn chain_tip_slot() -> Option<u64> {
+    kv::get_value(CHAIN_TIP_SLOT_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+fn set_chain_tip_slot(slot: u64) -> WorkerResult<()> {
+    kv::set_value(CHAIN_TIP_SLOT_KEY, &serde_json::to_vec(&slot)?)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to store chain tip: {:?}", e)))
+}
+
+// Webhook payload for every indexed bounty transition
+#[derive(Serialize, Deserialize)]
+pub struct BountyChangedPayload {
+    pub bounty_id: String,
+    pub bounty_ref: Option<String>,
+    pub maintainer_payment_key: String,
+    pub contributor_payment_key: Option<String>,
+    pub lovelace: u64,
+    pub deadline: u64,
+    pub status: BountyStatus,
+    pub event: BountyEvent,
+}
+
+// Announces the latest transition of `record` to its subscribers
+fn notify(config: &WorkerConfig, record: &BountyRecord) {
+    let Some(event) = record.history.last() else {
+        return;
+    };
+
+    let payload = BountyChangedPayload {
+        bounty_id: record.bounty_id.clone(),
+        bounty_ref: record.utxo_ref.clone(),
+        maintainer_payment_key: record.maintainer_payment_key.clone(),
+        contributor_payment_key: record.contributor_payment_key.clone(),
+        lovelace: record.lovelace,
+        deadline: record.deadline,
+        status: record.status,
+        event: event.clone(),
+    };
+
+    if let Err(e) = emit(config, event.action.event_type(), Some(record), &payload) {
+        log_event(
+            worker::logging::Level::Error,
+            "bounty_index",
+            json!({
+                "message": "Failed to emit bounty webhook",
+                "bounty_id": record.bounty_id,
+                "error": format!("{:?}", e),
+            }),
+        );
+    }
+}
+
+// Transition of a bounty whose NFT the transaction locks at the script again,
+// with `datum` as its new state
+fn output_transition(
+    previous: Option<&BountyRecord>,
+    datum: &GithoneyDatum,
+    minted: bool,
+) -> (BountyAction, BountyStatus) {
+    let action = match previous {
+        _ if minted => BountyAction::Create,
+        Some(prev) if datum.merged && !prev.merged => BountyAction::Merge,
+        Some(prev)
+            if datum.contributor_payment_key.is_some() && prev.contributor_payment_key.is_none() =>
+        {
+            BountyAction::Assign
+        }
+        _ => BountyAction::Add,
+    };
+
+    let status = if datum.merged {
+        BountyStatus::Merged
+    } else if datum.contributor_payment_key.is_some() {
+        BountyStatus::Assigned
+    } else {
+        BountyStatus::Open
+    };
+    (action, status)
+}
+
+// What the contributor deposited: the lovelace added by the assignment, kept
+// until the bounty is spent again
+fn contributor_deposit(previous: Option<&BountyRecord>, action: BountyAction, lovelace: u64) -> Option<u64> {
+    match (action, previous) {
+        (BountyAction::Assign, Some(prev)) => Some(lovelace.saturating_sub(prev.lovelace)),
+        (_, Some(prev)) => prev.contributor_deposit,
+        (_, None) => None,
+    }
+}
+
+// Transition of a bounty whose NFT the transaction burns: merged bounties are
+// claimed by their contributor, others closed
+fn burn_transition(record: &BountyRecord) -> (BountyAction, BountyStatus) {
+    if record.merged {
+        (BountyAction::Claim, BountyStatus::Claimed)
+    } else {
+        (BountyAction::Close, BountyStatus::Closed)
+    }
+}
+
+// Updates the bounty index with every bounty NFT minted, moved or burned by the transaction
+pub fn index_transaction(config: &WorkerConfig, tx_event: &Tx) -> WorkerResult<()> {
+    if tx_event.block_slot > chain_tip_slot().unwrap_or_default() {
+        set_chain_tip_slot(tx_event.block_slot)?;
+    }
+
+    let script_addr_bytes = pallas_addresses::Address::from_bech32(&config.githoney_script_address)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid script address: {}", e)))?
+        .to_vec();
+    let policy_id = hex::decode(&config.minting_policy_id)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid minting policy id: {}", e)))?;
+
+    let event = |action: BountyAction| BountyEvent {
+        action,
+        tx_hash: hex::encode(&tx_event.hash),
+        block_hash: hex::encode(&tx_event.block_hash),
+        block_height: tx_event.block_height,
+        block_slot: tx_event.block_slot,
+    };
+
+    let mut minted = Vec::new();
+    let mut burned = Vec::new();
+    for asset in tx_event
+        .tx
+        .mint
+        .iter()
+        .filter(|multiasset| multiasset.policy_id.to_vec() == policy_id)
+        .flat_map(|multiasset| multiasset.assets.iter())
+    {
+        let bounty_id = hex::encode(&asset.name);
+        if asset.mint_coin > 0 {
+            minted.push(bounty_id);
+        } else if asset.mint_coin < 0 {
+            burned.push(bounty_id);
+        }
+    }
+
+    for (index, output) in tx_event.tx.outputs.iter().enumerate() {
+        if output.address.to_vec() != script_addr_bytes {
+            continue;
+        }
+
+        let Some(bounty_id) = output
+            .assets
+            .iter()
+            .filter(|multiasset| multiasset.policy_id.to_vec() == policy_id)
+            .flat_map(|multiasset| multiasset.assets.iter())
+            .find(|asset| asset.output_coin == 1)
+            .map(|asset| hex::encode(&asset.name))
+        else {
+            continue;
+        };
+
+        let Some(datum) = output
+            .datum
+            .as_ref()
+            .and_then(|datum| decode_githoney_datum(&datum.original_cbor))
+        else {
+            log_event(
+                worker::logging::Level::Warn,
+                "bounty_index",
+                json!({
+                    "message": "Bounty output has no valid GithoneyDatum",
+                    "bounty_id": bounty_id,
+                }),
+            );
+            continue;
+        };
+
+        let previous = load_bounty(&bounty_id);
+        let (action, status) =
+            output_transition(previous.as_ref(), &datum, minted.contains(&bounty_id));
+
+        let deposit = contributor_deposit(previous.as_ref(), action, output.coin);
+        let mut history = previous.map(|prev| prev.history).unwrap_or_default();
+        history.push(event(action));
+
+        let utxo_ref = format!("{}#{}", hex::encode(&tx_event.hash), index);
+        save_bounty_ref(&utxo_ref, &bounty_id)?;
+
+        let record = BountyRecord {
+            bounty_id: bounty_id.clone(),
+            utxo_ref: Some(utxo_ref),
+            admin_payment_key: datum.admin_payment_key,
+            maintainer_payment_key: datum.maintainer_payment_key,
+            maintainer_stake_key: datum.maintainer_stake_key,
+            contributor_payment_key: datum.contributor_payment_key,
+            contributor_stake_key: datum.contributor_stake_key,
+            contributor_deposit: deposit,
+            lovelace: output.coin,
+            bounty_reward_fee: datum.bounty_reward_fee,
+            deadline: datum.deadline,
+            merged: datum.merged,
+            status,
+            history,
+        };
+        save_bounty(&record)?;
+
+        log_event(
+            worker::logging::Level::Info,
+            "bounty_index",
+            json!({
+                "message": "Indexed bounty",
+                "bounty_id": bounty_id,
+                "action": format!("{:?}", action),
+            }),
+        );
+        notify(config, &record);
+    }
+
+    for bounty_id in burned {
+        let Some(mut record) = load_bounty(&bounty_id) else {
+            continue;
+        };
+
+        let (action, status) = burn_transition(&record);
+        record.status = status;
+        record.utxo_ref = None;
+        record.history.push(event(action));
+        save_bounty(&record)?;
+
+        log_event(
+            worker::logging::Level::Info,
+            "bounty_index",
+            json!({
+                "message": "Indexed bounty",
+                "bounty_id": bounty_id,
+                "action": format!("{:?}", action),
+            }),
+        );
+        notify(config, &record);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datum(contributor: Option<&str>, merged: bool) -> GithoneyDatum {
+        GithoneyDatum {
+            admin_payment_key: "aa".to_string(),
+            maintainer_payment_key: "bb".to_string(),
+            maintainer_stake_key: None,
+            contributor_payment_key: contributor.map(str::to_string),
+            contributor_stake_key: None,
+            bounty_reward_fee: 2,
+            deadline: 1_000,
+            merged,
+        }
+    }
+
+    fn record(contributor: Option<&str>, merged: bool, status: BountyStatus) -> BountyRecord {
+        let datum = datum(contributor, merged);
+        BountyRecord {
+            bounty_id: "01".to_string(),
+            utxo_ref: Some(format!("{}#0", "cc".repeat(32))),
+            admin_payment_key: datum.admin_payment_key,
+            maintainer_payment_key: datum.maintainer_payment_key,
+            maintainer_stake_key: datum.maintainer_stake_key,
+            contributor_payment_key: datum.contributor_payment_key,
+            contributor_stake_key: datum.contributor_stake_key,
+            contributor_deposit: None,
+            lovelace: 10_000_000,
+            bounty_reward_fee: datum.bounty_reward_fee,
+            deadline: datum.deadline,
+            merged,
+            status,
+            history: vec![],
+        }
+    }
+
+    #[test]
+    fn follows_the_bounty_lifecycle() {
+        let open = record(None, false, BountyStatus::Open);
+        let assigned = record(Some("dd"), false, BountyStatus::Assigned);
+
+        assert_eq!(
+            output_transition(None, &datum(None, false), true),
+            (BountyAction::Create, BountyStatus::Open)
+        );
+        assert_eq!(
+            output_transition(Some(&open), &datum(None, false), false),
+            (BountyAction::Add, BountyStatus::Open)
+        );
+        assert_eq!(
+            output_transition(Some(&open), &datum(Some("dd"), false), false),
+            (BountyAction::Assign, BountyStatus::Assigned)
+        );
+        assert_eq!(
+            output_transition(Some(&assigned), &datum(Some("dd"), false), false),
+            (BountyAction::Add, BountyStatus::Assigned)
+        );
+        assert_eq!(
+            output_transition(Some(&assigned), &datum(Some("dd"), true), false),
+            (BountyAction::Merge, BountyStatus::Merged)
+        );
+    }
+
+    #[test]
+    fn unindexed_bounties_are_picked_up_as_they_are() {
+        // Created before the worker started indexing
+        assert_eq!(
+            output_transition(None, &datum(Some("dd"), false), false),
+            (BountyAction::Add, BountyStatus::Assigned)
+        );
+    }
+
+    #[test]
+    fn burning_claims_merged_bounties_and_closes_others() {
+        assert_eq!(
+            burn_transition(&record(Some("dd"), true, BountyStatus::Merged)),
+            (BountyAction::Claim, BountyStatus::Claimed)
+        );
+        assert_eq!(
+            burn_transition(&record(None, false, BountyStatus::Open)),
+            (BountyAction::Close, BountyStatus::Closed)
+        );
+    }
+
+    #[test]
+    fn assignment_records_the_contributor_deposit() {
+        let open = record(None, false, BountyStatus::Open);
+        assert_eq!(contributor_deposit(Some(&open), BountyAction::Assign, 12_000_000), Some(2_000_000));
+        assert_eq!(contributor_deposit(Some(&open), BountyAction::Add, 12_000_000), None);
+
+        let assigned = BountyRecord {
+            contributor_deposit: Some(2_000_000),
+            ..record(Some("dd"), false, BountyStatus::Assigned)
+        };
+        assert_eq!(contributor_deposit(Some(&assigned), BountyAction::Add, 15_000_000), Some(2_000_000));
+        assert_eq!(contributor_deposit(None, BountyAction::Create, 10_000_000), None);
+    }
+
+    #[test]
+    fn live_bounties_past_their_deadline_are_expired() {
+        let open = record(None, false, BountyStatus::Open);
+        assert_eq!(open.effective_status(1_000), BountyStatus::Open);
+        assert_eq!(open.effective_status(1_001), BountyStatus::Expired);
+
+        let assigned = record(Some("dd"), false, BountyStatus::Assigned);
+        assert_eq!(assigned.effective_status(1_001), BountyStatus::Expired);
+
+        let merged = record(Some("dd"), true, BountyStatus::Merged);
+        assert_eq!(merged.effective_status(1_001), BountyStatus::Merged);
+    }
+}
@@ -0,0 +1,102 @@
+use balius_sdk::{Config, Json, Params, WorkerResult};
+use serde::{Deserialize, Serialize};
+
+use crate::bounty_index::{
+    chain_tip_slot, list_bounty_ids, load_bounty, BountyEvent, BountyRecord, BountyStatus,
+};
+use crate::errors::BountyError;
+use crate::time::network_slots;
+use crate::types::WorkerConfig;
+
+const DEFAULT_PAGE_LIMIT: usize = 50;
+const MAX_PAGE_LIMIT: usize = 200;
+
+#[derive(Serialize, Deserialize)]
+pub struct GetBountyParams {
+    pub bounty_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListBountiesParams {
+    pub maintainer: Option<String>,
+    pub contributor: Option<String>,
+    pub status: Option<BountyStatus>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BountyPage {
+    pub items: Vec<BountyRecord>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+// POSIX time of the current chain tip
+fn tip_posix_ms(config: &WorkerConfig) -> WorkerResult<u64> {
+    Ok(network_slots(&config.network)?.slot_to_posix_ms(chain_tip_slot().unwrap_or_default()))
+}
+
+// Loads a bounty with its status resolved against the current chain tip
+fn load_bounty_at_tip(config: &WorkerConfig, bounty_id: &str) -> WorkerResult<BountyRecord> {
+    let mut record =
+        load_bounty(bounty_id).ok_or_else(|| BountyError::NotFound(bounty_id.to_string()))?;
+    record.status = record.effective_status(tip_posix_ms(config)?);
+    Ok(record)
+}
+
+pub fn get_bounty(
+    config: Config<WorkerConfig>,
+    params: Params<GetBountyParams>,
+) -> WorkerResult<Json<BountyRecord>> {
+    Ok(Json(load_bounty_at_tip(&config, &params.bounty_id)?))
+}
+
+pub fn get_bounty_history(
+    config: Config<WorkerConfig>,
+    params: Params<GetBountyParams>,
+) -> WorkerResult<Json<Vec<BountyEvent>>> {
+    Ok(Json(load_bounty_at_tip(&config, &params.bounty_id)?.history))
+}
+
+pub fn list_bounties(
+    config: Config<WorkerConfig>,
+    params: Params<ListBountiesParams>,
+) -> WorkerResult<Json<BountyPage>> {
+    let now = tip_posix_ms(&config)?;
+    let offset = params.offset.unwrap_or(0);
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .min(MAX_PAGE_LIMIT);
+
+    let matching: Vec<BountyRecord> = list_bounty_ids()
+        .iter()
+        .filter_map(|bounty_id| load_bounty(bounty_id))
+        .map(|mut record| {
+            record.status = record.effective_status(now);
+            record
+        })
+        .filter(|record| {
+            params
+                .maintainer
+                .as_ref()
+                .is_none_or(|key| &record.maintainer_payment_key == key)
+        })
+        .filter(|record| {
+            params
+                .contributor
+                .as_ref()
+                .is_none_or(|key| record.contributor_payment_key.as_ref() == Some(key))
+        })
+        .filter(|record| params.status.is_none_or(|status| record.status == status))
+        .collect();
+
+    Ok(Json(BountyPage {
+        total: matching.len(),
+        items: matching.into_iter().skip(offset).take(limit).collect(),
+        offset,
+        limit,
+    }))
+}
@@ -3,13 +3,27 @@ use balius_sdk::wit::balius::app as worker;
 use balius_sdk::wit::balius::app::kv;
 use balius_sdk::{Ack, Config, Json, Params, Tx, WorkerResult};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
+use crate::bounty_index::index_transaction;
+use crate::collateral::observe_transaction;
+use crate::deadlines::check_deadlines;
+use crate::settings::observe_settings_spend;
 use crate::types::WorkerConfig;
+use crate::webhooks::{emit, flush_outbox, EventType};
+use crate::utils::log_event;
 
 // Transaction tracking status
 const TX_STATUS_PENDING: &str = "pending";
 const TX_STATUS_CONFIRMED: &str = "confirmed";
 
+// Tracks a submitted transaction, so its confirmation is sent as a
+// `tx-confirmed` webhook
+pub fn track_transaction(tx_hash: &str) -> WorkerResult<()> {
+    kv::set_value(tx_hash, TX_STATUS_PENDING.as_bytes())
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to track {}: {:?}", tx_hash, e)))
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct EmptyParams {}
 
@@ -30,7 +44,7 @@ pub struct WebhookPayload {
 
 // Helper function to send webhook notification
 pub fn send_confirmation_webhook(
-    webhook_url: &str,
+    config: &WorkerConfig,
     tx_hash: &str,
     block_hash: &str,
     block_height: u64,
@@ -43,36 +57,24 @@ pub fn send_confirmation_webhook(
         block_slot,
     };
 
-    worker::logging::log(
+    log_event(
         worker::logging::Level::Info,
         "webhook",
-        &format!("Sending confirmation webhook for tx: {}", tx_hash),
+        json!({
+            "message": "Sending confirmation webhook",
+            "tx_hash": tx_hash,
+        }),
     );
 
-    let url = url::Url::parse(webhook_url)
-        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid webhook URL: {}", e)))?;
-
-    let response = balius_sdk::http::HttpRequest::post(url)
-        .header("Content-Type", "application/json")
-        .json(&payload)?
-        .send()?;
-
-    if !response.is_ok() {
-        worker::logging::log(
-            worker::logging::Level::Error,
-            "webhook",
-            &format!("Webhook failed with status: {}", response.status),
-        );
-        return Err(balius_sdk::Error::Internal(format!(
-            "Webhook request failed with status {}",
-            response.status
-        )));
-    }
+    emit(config, EventType::TxConfirmed, None, &payload)?;
 
-    worker::logging::log(
+    log_event(
         worker::logging::Level::Info,
         "webhook",
-        &format!("Successfully sent webhook for tx: {}", tx_hash),
+        json!({
+            "message": "Webhook sent or queued",
+            "tx_hash": tx_hash,
+        }),
     );
 
     Ok(())
@@ -82,18 +84,78 @@ pub fn send_confirmation_webhook(
 pub fn handle_transaction_event(config: Config<WorkerConfig>, tx_event: Tx) -> WorkerResult<Ack> {
     let tx_hash = hex::encode(&tx_event.hash);
 
+    // Keep the bounty index in sync with every bounty state transition
+    if let Err(e) = index_transaction(&config, &tx_event) {
+        log_event(
+            worker::logging::Level::Error,
+            "tx_handler",
+            json!({
+                "message": "Failed to index bounty transitions",
+                "tx_hash": tx_hash,
+                "error": format!("{:?}", e),
+            }),
+        );
+    }
+
+    if let Err(e) = observe_settings_spend(&config, &tx_event) {
+        log_event(
+            worker::logging::Level::Error,
+            "tx_handler",
+            json!({
+                "message": "Failed to record spent settings",
+                "tx_hash": tx_hash,
+                "error": format!("{:?}", e),
+            }),
+        );
+    }
+
+    if let Err(e) = check_deadlines(&config, tx_event.block_slot) {
+        log_event(
+            worker::logging::Level::Error,
+            "tx_handler",
+            json!({
+                "message": "Failed to check bounty deadlines",
+                "error": format!("{:?}", e),
+            }),
+        );
+    }
+
+    if let Err(e) = flush_outbox(&config) {
+        log_event(
+            worker::logging::Level::Error,
+            "tx_handler",
+            json!({
+                "message": "Failed to flush webhook outbox",
+                "error": format!("{:?}", e),
+            }),
+        );
+    }
+
+    if let Err(e) = observe_transaction(&tx_hash) {
+        log_event(
+            worker::logging::Level::Error,
+            "tx_handler",
+            json!({
+                "message": "Failed to update reserved collateral",
+                "error": format!("{:?}", e),
+            }),
+        );
+    }
+
     // Decode monitoring address from config
     let monitoring_addr_bytes = pallas_addresses::Address::from_bech32(&config.monitoring_address)
         .expect("Invalid bech32 monitoring address in config")
         .to_vec();
 
-    worker::logging::log(
+    log_event(
         worker::logging::Level::Info,
         "tx_handler",
-        &format!(
-            "=== TX EVENT RECEIVED: {} (block: {}, slot: {}) ===",
-            tx_hash, tx_event.block_height, tx_event.block_slot
-        ),
+        json!({
+            "message": "Transaction event received",
+            "tx_hash": tx_hash,
+            "block_height": tx_event.block_height,
+            "block_slot": tx_event.block_slot,
+        }),
     );
 
     // Manual filtering: Check if any input matches the monitoring address
@@ -104,34 +166,42 @@ pub fn handle_transaction_event(config: Config<WorkerConfig>, tx_event: Tx) -> W
         .filter_map(|input| input.as_output.as_ref())
         .any(|output| output.address.to_vec() == monitoring_addr_bytes);
 
-    if !has_monitored_address {
-        worker::logging::log(
+    // Tracked transactions are confirmed whatever addresses they touch
+    let tracked = kv::get_value(&tx_hash).ok();
+    if !has_monitored_address && tracked.is_none() {
+        log_event(
             worker::logging::Level::Debug,
             "tx_handler",
-            &format!(
-                "Transaction does not involve monitoring address, skipping: {}",
-                tx_hash
-            ),
+            json!({
+                "message": "Transaction does not involve monitoring address, skipping",
+                "tx_hash": tx_hash,
+            }),
         );
         return Ok(Ack);
     }
 
-    worker::logging::log(
+    log_event(
         worker::logging::Level::Info,
         "tx_handler",
-        &format!("Transaction involves monitoring address: {}", tx_hash),
+        json!({
+            "message": "Transaction involves monitoring address",
+            "tx_hash": tx_hash,
+        }),
     );
 
     // Check if this transaction is being tracked
-    match kv::get_value(&tx_hash) {
-        Ok(status_bytes) => {
+    match tracked {
+        Some(status_bytes) => {
             let status = String::from_utf8_lossy(&status_bytes);
 
             if status == TX_STATUS_PENDING {
-                worker::logging::log(
+                log_event(
                     worker::logging::Level::Info,
                     "tx_handler",
-                    &format!("Found pending transaction confirmed: {}", tx_hash),
+                    json!({
+                        "message": "Found pending transaction confirmed",
+                        "tx_hash": tx_hash,
+                    }),
                 );
 
                 // Extract block information
@@ -141,48 +211,66 @@ pub fn handle_transaction_event(config: Config<WorkerConfig>, tx_event: Tx) -> W
 
                 // Send webhook notification
                 if let Err(e) = send_confirmation_webhook(
-                    &config.webhook_url,
+                    &config,
                     &tx_hash,
                     &block_hash,
                     block_height,
                     block_slot,
                 ) {
-                    worker::logging::log(
+                    log_event(
                         worker::logging::Level::Error,
                         "tx_handler",
-                        &format!("Failed to send webhook: {:?}", e),
+                        json!({
+                            "message": "Failed to send webhook",
+                            "tx_hash": tx_hash,
+                            "error": format!("{:?}", e),
+                        }),
                     );
                     // Continue processing even if webhook fails
                 }
 
                 // Update status to confirmed
                 if let Err(e) = kv::set_value(&tx_hash, TX_STATUS_CONFIRMED.as_bytes()) {
-                    worker::logging::log(
+                    log_event(
                         worker::logging::Level::Error,
                         "tx_handler",
-                        &format!("Failed to update tx status: {:?}", e),
+                        json!({
+                            "message": "Failed to update tx status",
+                            "tx_hash": tx_hash,
+                            "error": format!("{:?}", e),
+                        }),
                     );
                 }
 
-                worker::logging::log(
+                log_event(
                     worker::logging::Level::Info,
                     "tx_handler",
-                    &format!("Transaction confirmation processed: {}", tx_hash),
+                    json!({
+                        "message": "Transaction confirmation processed",
+                        "tx_hash": tx_hash,
+                    }),
                 );
             } else {
-                worker::logging::log(
+                log_event(
                     worker::logging::Level::Debug,
                     "tx_handler",
-                    &format!("Transaction already {}: {}", status, tx_hash),
+                    json!({
+                        "message": "Transaction already processed",
+                        "tx_hash": tx_hash,
+                        "status": status.as_ref(),
+                    }),
                 );
             }
         }
-        Err(_) => {
+        None => {
             // Transaction not being tracked, ignore it
-            worker::logging::log(
+            log_event(
                 worker::logging::Level::Debug,
                 "tx_handler",
-                &format!("Transaction not tracked: {}", tx_hash),
+                json!({
+                    "message": "Transaction not tracked",
+                    "tx_hash": tx_hash,
+                }),
             );
         }
     }
@@ -190,15 +278,16 @@ pub fn handle_transaction_event(config: Config<WorkerConfig>, tx_event: Tx) -> W
     Ok(Ack)
 }
 
-pub fn get_latest_block(
-    config: Config<WorkerConfig>,
-    _params: Params<EmptyParams>,
-) -> WorkerResult<Json<BlockfrostResponse>> {
+// Latest block of the configured network according to Blockfrost
+pub fn latest_block(config: &WorkerConfig) -> WorkerResult<serde_json::Value> {
     let project_id =
         std::env::var("BLOCKFROST_PROJECT_ID").unwrap_or_else(|_| config.project_id.clone());
 
-    let url = url::Url::parse("https://cardano-preprod.blockfrost.io/api/v0/blocks/latest")
-        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid URL: {}", e)))?;
+    let url = url::Url::parse(&format!(
+        "https://cardano-{}.blockfrost.io/api/v0/blocks/latest",
+        config.network
+    ))
+    .map_err(|e| balius_sdk::Error::Internal(format!("Invalid URL: {}", e)))?;
 
     let response = balius_sdk::http::HttpRequest::get(url)
         .header("project_id", project_id.as_str())
@@ -211,8 +300,15 @@ pub fn get_latest_block(
         )));
     }
 
-    let data: serde_json::Value = serde_json::from_slice(&response.body)
-        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to parse JSON: {}", e)))?;
+    serde_json::from_slice(&response.body)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to parse JSON: {}", e)))
+}
 
-    Ok(Json(BlockfrostResponse { data }))
+pub fn get_latest_block(
+    config: Config<WorkerConfig>,
+    _params: Params<EmptyParams>,
+) -> WorkerResult<Json<BlockfrostResponse>> {
+    Ok(Json(BlockfrostResponse {
+        data: latest_block(&config)?,
+    }))
 }
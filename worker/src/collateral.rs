@@ -0,0 +1,408 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::wit::balius::app::{kv, ledger, sign, submit};
+use balius_sdk::{Config, Json, Params, WorkerResult};
+use pallas_codec::minicbor;
+use pallas_crypto::hash::Hasher;
+use pallas_traverse::{Era, MultiEraOutput};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::bounty_index::chain_tip_slot;
+use crate::chainsync::EmptyParams;
+use crate::time::recent_tip_slot;
+use crate::types::{CoinControl, UtxoRef, WorkerConfig};
+use crate::utils::{log_event, parse_utxo_ref};
+
+const COLLATERAL_KEY: &str = "collateral-utxo";
+const SIGNING_KEY: &str = "payment-key";
+
+// Every tx3 definition asks for `collateral { min_amount: Ada(5000000) }`
+const COLLATERAL_LOVELACE: u64 = 5_000_000;
+// A pure-ADA UTxO up to this size is reserved as is instead of splitting one off
+const MAX_CLEAN_COLLATERAL_LOVELACE: u64 = 10_000_000;
+// Change output of the split transaction, kept well above min-ADA
+const MIN_CHANGE_LOVELACE: u64 = 2_000_000;
+// Linear fee parameters (`minFeeA`, `minFeeB`) of the current protocol parameters
+const MIN_FEE_A: u64 = 44;
+const MIN_FEE_B: u64 = 155_381;
+// Upper bound on the split transaction fee, used to size it
+const FEE_PLACEHOLDER: u64 = 1_000_000;
+// Validity of the split transaction; it is forgotten once this has passed
+const SPLIT_TTL_SLOTS: u64 = 600;
+const MAX_SEARCH_PAGES: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CollateralStatus {
+    // Output of a split transaction that has not been seen on chain yet
+    Pending,
+    Ready,
+}
+
+// The UTxO at `githoney_addr` reserved as collateral for transactions that
+// take their collateral from there
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReservedCollateral {
+    pub utxo_ref: String,
+    pub status: CollateralStatus,
+    // Hash and TTL of the split transaction that creates it, if any
+    pub split_tx: Option<String>,
+    pub expires_slot: Option<u64>,
+}
+
+fn load_reserved() -> Option<ReservedCollateral> {
+    kv::get_value(COLLATERAL_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+fn save_reserved(reserved: &ReservedCollateral) -> WorkerResult<()> {
+    kv::set_value(COLLATERAL_KEY, &serde_json::to_vec(reserved)?)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to store collateral: {:?}", e)))
+}
+
+fn internal(message: String) -> balius_sdk::Error {
+    balius_sdk::Error::Internal(message)
+}
+
+// The worker signs for `githoney_addr`, so its `payment-key` must be that address's key
+fn check_signer(config: &WorkerConfig) -> WorkerResult<()> {
+    let public_key = hex::decode(&config.payment_key_public)
+        .map_err(|e| internal(format!("Invalid payment_key_public: {}", e)))?;
+    let key_hash = hex::encode(Hasher::<224>::hash(&public_key).as_ref());
+
+    if key_hash != config.githoney_payment_cred.to_lowercase() {
+        return Err(internal(format!(
+            "payment-key ({}) does not control githoney_addr ({})",
+            key_hash, config.githoney_payment_cred
+        )));
+    }
+    Ok(())
+}
+
+fn is_unspent(utxo_ref: &str) -> WorkerResult<bool> {
+    let (tx_hash, tx_index) = parse_utxo_ref(utxo_ref)
+        .ok_or_else(|| internal(format!("Invalid collateral UTxO: {}", utxo_ref)))?;
+    let utxos = ledger::read_utxos(&[ledger::TxoRef { tx_hash, tx_index }])
+        .map_err(|e| internal(format!("Failed to read collateral UTxO: {:?}", e)))?;
+    Ok(!utxos.is_empty())
+}
+
+// (`txhash#index`, lovelace) of every pure-ADA UTxO at `address`
+fn ada_only_utxos(address: &[u8]) -> WorkerResult<Vec<(String, u64)>> {
+    let pattern = ledger::UtxoPattern {
+        address: Some(ledger::AddressPattern {
+            exact_address: address.to_vec(),
+        }),
+        asset: None,
+    };
+
+    let mut found = Vec::new();
+    let mut start: Option<String> = None;
+    for _ in 0..MAX_SEARCH_PAGES {
+        let page = ledger::search_utxos(&pattern, start.as_deref(), 100)
+            .map_err(|e| internal(format!("Failed to search wallet UTxOs: {:?}", e)))?;
+
+        for utxo in page.utxos {
+            let Ok(output) = MultiEraOutput::decode(Era::Conway, &utxo.body) else {
+                continue;
+            };
+            if output.non_ada_assets().is_empty()
+                && output.datum().is_none()
+                && output.script_ref().is_none()
+            {
+                found.push((
+                    format!("{}#{}", hex::encode(&utxo.ref_.tx_hash), utxo.ref_.tx_index),
+                    output.lovelace_amount(),
+                ));
+            }
+        }
+
+        match page.next_token {
+            Some(next) => start = Some(next),
+            None => break,
+        }
+    }
+    Ok(found)
+}
+
+///// SPLIT TRANSACTION /////
+type EncodeResult = Result<(), minicbor::encode::Error<std::convert::Infallible>>;
+
+// Body of a transaction spending `input` into a collateral output and change,
+// both back to `address`
+fn encode_body(
+    encoder: &mut minicbor::Encoder<Vec<u8>>,
+    input: (&[u8], u32),
+    address: &[u8],
+    input_lovelace: u64,
+    fee: u64,
+    ttl: u64,
+) -> EncodeResult {
+    encoder.map(4)?;
+    encoder
+        .u8(0)?
+        .array(1)?
+        .array(2)?
+        .bytes(input.0)?
+        .u32(input.1)?;
+    encoder.u8(1)?.array(2)?;
+    encoder.array(2)?.bytes(address)?.u64(COLLATERAL_LOVELACE)?;
+    encoder
+        .array(2)?
+        .bytes(address)?
+        .u64(input_lovelace - COLLATERAL_LOVELACE - fee)?;
+    encoder.u8(2)?.u64(fee)?;
+    encoder.u8(3)?.u64(ttl)?;
+    Ok(())
+}
+
+// `[body, { 0: [[vkey, signature]] }, true, null]`
+fn encode_tx(
+    body: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<Vec<u8>, minicbor::encode::Error<std::convert::Infallible>> {
+    let mut encoder = minicbor::Encoder::new(Vec::new());
+    encoder.array(4)?;
+    encoder.writer_mut().extend_from_slice(body);
+    encoder
+        .map(1)?
+        .u8(0)?
+        .array(1)?
+        .array(2)?
+        .bytes(public_key)?
+        .bytes(signature)?;
+    encoder.bool(true)?.null()?;
+    Ok(encoder.into_writer())
+}
+
+fn body_bytes(
+    input: (&[u8], u32),
+    address: &[u8],
+    input_lovelace: u64,
+    fee: u64,
+    ttl: u64,
+) -> WorkerResult<Vec<u8>> {
+    let mut encoder = minicbor::Encoder::new(Vec::new());
+    encode_body(&mut encoder, input, address, input_lovelace, fee, ttl)
+        .map_err(|e| internal(format!("Failed to encode split transaction: {}", e)))?;
+    Ok(encoder.into_writer())
+}
+
+// Builds, signs and submits a transaction splitting COLLATERAL_LOVELACE off
+// `source`, and returns the reservation of its first output
+fn split_collateral(
+    config: &WorkerConfig,
+    address: &[u8],
+    source: &(String, u64),
+    tip_slot: u64,
+) -> WorkerResult<ReservedCollateral> {
+    let (tx_hash, tx_index) = parse_utxo_ref(&source.0)
+        .ok_or_else(|| internal(format!("Invalid wallet UTxO: {}", source.0)))?;
+    let input = (tx_hash.as_slice(), tx_index);
+    let public_key = hex::decode(&config.payment_key_public)
+        .map_err(|e| internal(format!("Invalid payment_key_public: {}", e)))?;
+    let ttl = tip_slot + SPLIT_TTL_SLOTS;
+
+    // Size with a placeholder fee encoded in as many bytes as the real one,
+    // plus a few bytes in case the change amount encodes longer
+    let draft = body_bytes(input, address, source.1, FEE_PLACEHOLDER, ttl)?;
+    let draft_tx = encode_tx(&draft, &public_key, &[0u8; 64])
+        .map_err(|e| internal(format!("Failed to encode split transaction: {}", e)))?;
+    let fee = MIN_FEE_A * (draft_tx.len() as u64 + 8) + MIN_FEE_B;
+
+    let body = body_bytes(input, address, source.1, fee, ttl)?;
+    let body_hash = Hasher::<256>::hash(&body);
+    let signature = sign::sign_payload(SIGNING_KEY, body_hash.as_ref())
+        .map_err(|e| internal(format!("Sign error: {:?}", e)))?;
+    let tx = encode_tx(&body, &public_key, &signature)
+        .map_err(|e| internal(format!("Failed to encode split transaction: {}", e)))?;
+
+    submit::submit_tx(&tx)
+        .map_err(|e| internal(format!("Failed to submit split transaction: {:?}", e)))?;
+
+    let split_tx = hex::encode(body_hash.as_ref());
+    log_event(
+        worker::logging::Level::Info,
+        "collateral",
+        json!({
+            "message": "Submitted collateral split",
+            "tx_hash": split_tx,
+            "source": source.0,
+        }),
+    );
+
+    Ok(ReservedCollateral {
+        utxo_ref: format!("{}#0", split_tx),
+        status: CollateralStatus::Pending,
+        split_tx: Some(split_tx),
+        expires_slot: Some(ttl),
+    })
+}
+
+// Returns the reserved collateral once it is on chain. Otherwise reserves a
+// clean pure-ADA UTxO, or submits a split transaction creating one and
+// returns None until it confirms.
+pub fn ensure_collateral(config: &WorkerConfig) -> WorkerResult<Option<ReservedCollateral>> {
+    if let Some(reserved) = load_reserved() {
+        match reserved.status {
+            CollateralStatus::Ready if is_unspent(&reserved.utxo_ref)? => {
+                return Ok(Some(reserved))
+            }
+            CollateralStatus::Pending if is_unspent(&reserved.utxo_ref)? => {
+                let ready = ReservedCollateral {
+                    status: CollateralStatus::Ready,
+                    ..reserved
+                };
+                save_reserved(&ready)?;
+                return Ok(Some(ready));
+            }
+            CollateralStatus::Pending
+                if reserved
+                    .expires_slot
+                    .is_some_and(|ttl| ttl >= chain_tip_slot().unwrap_or_default()) =>
+            {
+                return Ok(None);
+            }
+            _ => {
+                log_event(
+                    worker::logging::Level::Warn,
+                    "collateral",
+                    json!({
+                        "message": "Reserved collateral is gone, reserving another",
+                        "utxo_ref": reserved.utxo_ref,
+                    }),
+                );
+            }
+        }
+    }
+
+    check_signer(config)?;
+    let address = pallas_addresses::Address::from_bech32(&config.githoney_addr)
+        .map_err(|e| internal(format!("Invalid githoney_addr: {}", e)))?
+        .to_vec();
+    let utxos = ada_only_utxos(&address)?;
+
+    let clean = utxos
+        .iter()
+        .filter(|(_, lovelace)| {
+            (COLLATERAL_LOVELACE..MAX_CLEAN_COLLATERAL_LOVELACE).contains(lovelace)
+        })
+        .min_by_key(|(_, lovelace)| *lovelace);
+    if let Some((utxo_ref, _)) = clean {
+        let reserved = ReservedCollateral {
+            utxo_ref: utxo_ref.clone(),
+            status: CollateralStatus::Ready,
+            split_tx: None,
+            expires_slot: None,
+        };
+        save_reserved(&reserved)?;
+        return Ok(Some(reserved));
+    }
+
+    // Leave room for the fee, which stays under FEE_PLACEHOLDER for this transaction
+    let source = utxos
+        .iter()
+        .filter(|(_, lovelace)| {
+            *lovelace >= COLLATERAL_LOVELACE + MIN_CHANGE_LOVELACE + FEE_PLACEHOLDER
+        })
+        .max_by_key(|(_, lovelace)| *lovelace)
+        .ok_or_else(|| {
+            internal(format!(
+                "githoney_addr has no pure-ADA UTxO large enough to split off {} lovelace of collateral",
+                COLLATERAL_LOVELACE
+            ))
+        })?;
+
+    // The split's TTL counts from the tip, so while the worker catches up a
+    // split would already be expired when submitted
+    let tip_slot = match recent_tip_slot(config) {
+        Ok(tip_slot) => tip_slot,
+        Err(e) => {
+            log_event(
+                worker::logging::Level::Warn,
+                "collateral",
+                json!({
+                    "message": "Deferring collateral split until the chain tip is recent",
+                    "error": format!("{:?}", e),
+                }),
+            );
+            return Ok(None);
+        }
+    };
+    let reserved = split_collateral(config, &address, source, tip_slot)?;
+    save_reserved(&reserved)?;
+    Ok(None)
+}
+
+// Marks the reservation ready when its split transaction is seen on chain
+pub fn observe_transaction(tx_hash: &str) -> WorkerResult<()> {
+    match load_reserved() {
+        Some(reserved)
+            if reserved.status == CollateralStatus::Pending
+                && reserved.split_tx.as_deref() == Some(tx_hash) =>
+        {
+            log_event(
+                worker::logging::Level::Info,
+                "collateral",
+                json!({
+                    "message": "Collateral split confirmed",
+                    "utxo_ref": reserved.utxo_ref,
+                }),
+            );
+            save_reserved(&ReservedCollateral {
+                status: CollateralStatus::Ready,
+                ..reserved
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+// Coin control for builds whose collateral comes from `githoney_addr`: the
+// reserved collateral, which the tx builder pins as the transaction's
+// collateral and keeps out of its regular inputs. Builds go ahead without it
+// while it is being created. Transactions taking collateral from another
+// party must not use it, as only the worker can sign for it.
+pub fn githoney_coin_control(config: &WorkerConfig, requested: &CoinControl) -> CoinControl {
+    if !requested.collateral.is_empty() {
+        return requested.clone();
+    }
+
+    let reserved = match ensure_collateral(config) {
+        Ok(Some(reserved)) => reserved,
+        Ok(None) => return requested.clone(),
+        Err(e) => {
+            log_event(
+                worker::logging::Level::Error,
+                "collateral",
+                json!({
+                    "message": "Failed to reserve collateral",
+                    "error": format!("{:?}", e),
+                }),
+            );
+            return requested.clone();
+        }
+    };
+    let Ok(utxo_ref) = reserved.utxo_ref.parse::<UtxoRef>() else {
+        return requested.clone();
+    };
+
+    let mut coin_control = requested.clone();
+    coin_control.collateral = vec![utxo_ref.clone()];
+    if !coin_control.exclude_inputs.contains(&utxo_ref) {
+        coin_control.exclude_inputs.push(utxo_ref);
+    }
+    coin_control
+}
+
+// Operator handler: reports the reserved collateral, creating it if needed
+pub fn reserve_collateral(
+    config: Config<WorkerConfig>,
+    _params: Params<EmptyParams>,
+) -> WorkerResult<Json<Option<ReservedCollateral>>> {
+    ensure_collateral(&config)?;
+    Ok(Json(load_reserved()))
+}
@@ -0,0 +1,208 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::wit::balius::app::kv;
+use balius_sdk::WorkerResult;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::bounty_index::{list_bounty_ids, load_bounty, BountyRecord, BountyStatus};
+use crate::offchain_handlers::build_close_tx;
+use crate::time::{network_slots, recent_tip_slot};
+use crate::types::{TxEnvelope, WorkerConfig};
+use crate::webhooks::{emit, EventType};
+use crate::utils::log_event;
+
+const LAST_DEADLINE_CHECK_KEY: &str = "deadline-check-slot";
+const EXPIRED_NOTIFIED_KEY_PREFIX: &str = "expired-notified:";
+// `(deadline, bounty_id)` of every live bounty not yet announced as expired,
+// earliest deadline first
+const DEADLINE_INDEX_KEY: &str = "deadline-index";
+
+type DeadlineIndex = Vec<(u64, String)>;
+
+// Webhook payload announcing a bounty whose deadline passed before it was merged
+#[derive(Serialize, Deserialize)]
+pub struct BountyExpiredPayload {
+    pub bounty_id: String,
+    pub bounty_ref: Option<String>,
+    pub deadline: u64,
+    pub block_slot: u64,
+    // Unsigned close transaction for the admin key
+    pub tx: TxEnvelope,
+}
+
+fn expired_notified_key(bounty_id: &str) -> String {
+    format!("{}{}", EXPIRED_NOTIFIED_KEY_PREFIX, bounty_id)
+}
+
+fn already_notified(bounty_id: &str) -> bool {
+    kv::get_value(&expired_notified_key(bounty_id)).is_ok()
+}
+
+// Whether `record` still waits for its deadline: live, unmerged and not announced yet
+fn awaits_deadline(record: &BountyRecord) -> bool {
+    record.utxo_ref.is_some()
+        && matches!(record.status, BountyStatus::Open | BountyStatus::Assigned)
+        && !already_notified(&record.bounty_id)
+}
+
+// Moves `bounty_id` to its place in `index`, or drops it when `deadline` is None
+fn place(index: &mut DeadlineIndex, bounty_id: &str, deadline: Option<u64>) {
+    index.retain(|(_, id)| id != bounty_id);
+    if let Some(deadline) = deadline {
+        let at = index.partition_point(|entry| entry.0 <= deadline);
+        index.insert(at, (deadline, bounty_id.to_string()));
+    }
+}
+
+// Entries whose deadline is before `now_posix_ms`
+fn due(index: &DeadlineIndex, now_posix_ms: u64) -> &[(u64, String)] {
+    &index[..index.partition_point(|(deadline, _)| *deadline < now_posix_ms)]
+}
+
+// The stored index, built from every indexed bounty the first time
+fn load_deadline_index() -> DeadlineIndex {
+    if let Ok(bytes) = kv::get_value(DEADLINE_INDEX_KEY) {
+        return serde_json::from_slice(&bytes).unwrap_or_default();
+    }
+
+    let mut index = DeadlineIndex::new();
+    for record in list_bounty_ids().iter().filter_map(|id| load_bounty(id)) {
+        if awaits_deadline(&record) {
+            place(&mut index, &record.bounty_id, Some(record.deadline));
+        }
+    }
+    index
+}
+
+fn save_deadline_index(index: &DeadlineIndex) -> WorkerResult<()> {
+    kv::set_value(DEADLINE_INDEX_KEY, &serde_json::to_vec(index)?).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to store deadline index: {:?}", e))
+    })
+}
+
+// Keeps the deadline index in step with a saved bounty record
+pub fn index_deadline(record: &BountyRecord) -> WorkerResult<()> {
+    let mut index = load_deadline_index();
+    let deadline = awaits_deadline(record).then_some(record.deadline);
+    place(&mut index, &record.bounty_id, deadline);
+    save_deadline_index(&index)
+}
+
+fn last_checked_slot() -> u64 {
+    kv::get_value(LAST_DEADLINE_CHECK_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+// Only marks the bounty once its close transaction was built and announced,
+// so a failed build is retried on the next block instead of being dropped
+fn notify_expired(config: &WorkerConfig, record: &BountyRecord, block_slot: u64) -> WorkerResult<()> {
+    let tx = build_close_tx(config, record, block_slot)?;
+
+    let payload = BountyExpiredPayload {
+        bounty_id: record.bounty_id.clone(),
+        bounty_ref: record.utxo_ref.clone(),
+        deadline: record.deadline,
+        block_slot,
+        tx,
+    };
+
+    emit(config, EventType::Expired, Some(record), &payload)?;
+
+    kv::set_value(&expired_notified_key(&record.bounty_id), &block_slot.to_be_bytes())
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to mark bounty expired: {:?}", e)))
+}
+
+// Runs once per block near the tip: hands the admin an unsigned close
+// transaction through the webhook for every bounty whose deadline passed
+// unmerged. Replayed blocks are skipped, since a close built from an old slot
+// would have expired before anyone could sign it.
+pub fn check_deadlines(config: &WorkerConfig, block_slot: u64) -> WorkerResult<()> {
+    if block_slot <= last_checked_slot() {
+        return Ok(());
+    }
+    let tip = match recent_tip_slot(config) {
+        Ok(tip) => tip,
+        Err(e) => {
+            log_event(
+                worker::logging::Level::Debug,
+                "deadlines",
+                json!({ "message": "Deferring deadline checks", "error": format!("{:?}", e) }),
+            );
+            return Ok(());
+        }
+    };
+    kv::set_value(LAST_DEADLINE_CHECK_KEY, &serde_json::to_vec(&block_slot)?).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to store deadline check slot: {:?}", e))
+    })?;
+
+    let now = network_slots(&config.network)?.slot_to_posix_ms(tip);
+    let mut index = load_deadline_index();
+    let due: DeadlineIndex = due(&index, now).to_vec();
+
+    for (_, bounty_id) in due {
+        let record = load_bounty(&bounty_id)
+            .filter(|record| awaits_deadline(record) && record.effective_status(now) == BountyStatus::Expired);
+        let Some(record) = record else {
+            place(&mut index, &bounty_id, None);
+            continue;
+        };
+
+        log_event(
+            worker::logging::Level::Info,
+            "deadlines",
+            json!({
+                "message": "Bounty expired",
+                "bounty_id": bounty_id,
+                "deadline": record.deadline,
+            }),
+        );
+
+        // Leave it in the index so the next block retries
+        match notify_expired(config, &record, tip) {
+            Ok(()) => place(&mut index, &bounty_id, None),
+            Err(e) => log_event(
+                worker::logging::Level::Error,
+                "deadlines",
+                json!({
+                    "message": "Failed to notify expired bounty",
+                    "bounty_id": bounty_id,
+                    "error": format!("{:?}", e),
+                }),
+            ),
+        }
+    }
+
+    save_deadline_index(&index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_bounties_in_deadline_order() {
+        let mut index = DeadlineIndex::new();
+        place(&mut index, "b", Some(2_000));
+        place(&mut index, "a", Some(1_000));
+        place(&mut index, "c", Some(3_000));
+        assert_eq!(index.iter().map(|(_, id)| id.as_str()).collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        // Re-placing moves an entry instead of duplicating it
+        place(&mut index, "a", Some(2_500));
+        assert_eq!(index.iter().map(|(_, id)| id.as_str()).collect::<Vec<_>>(), ["b", "a", "c"]);
+
+        place(&mut index, "b", None);
+        assert_eq!(index.len(), 2);
+    }
+
+    #[test]
+    fn only_passed_deadlines_are_due() {
+        let index: DeadlineIndex = vec![(1_000, "a".to_string()), (2_000, "b".to_string())];
+        assert!(due(&index, 1_000).is_empty());
+        assert_eq!(due(&index, 1_001), &index[..1]);
+        assert_eq!(due(&index, 5_000), &index[..]);
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::time::TimeError;
+
+// Bounty-level errors surfaced to request handler callers
+#[derive(Debug)]
+pub enum BountyError {
+    NotFound(String),
+    NotLive(String),
+    InvalidBountyId(String),
+    DuplicateBountyId(String),
+    InvalidTime {
+        field: &'static str,
+        reason: String,
+    },
+    InvalidField {
+        field: String,
+        reason: String,
+    },
+    FeeMismatch {
+        field: &'static str,
+        given: u64,
+        expected: u64,
+    },
+    MissingBountyRef,
+    BountyRefMismatch {
+        bounty_ref: String,
+        bounty_id: String,
+        indexed_bounty_id: String,
+    },
+    StaleBountyRef {
+        bounty_ref: String,
+        current_ref: Option<String>,
+    },
+    SubscriptionNotFound(String),
+    // The chain tip is unknown or still catching up with the network
+    TipNotReady(String),
+}
+
+impl fmt::Display for BountyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BountyError::NotFound(bounty_id) => write!(f, "BountyNotFound: {}", bounty_id),
+            BountyError::NotLive(bounty_id) => {
+                write!(f, "BountyNotLive: {} has already been claimed or closed", bounty_id)
+            }
+            BountyError::InvalidBountyId(reason) => write!(f, "InvalidBountyId: {}", reason),
+            BountyError::DuplicateBountyId(bounty_id) => {
+                write!(f, "DuplicateBountyId: {} is already indexed", bounty_id)
+            }
+            BountyError::InvalidTime { field, reason } => {
+                write!(f, "InvalidTime: {}: {}", field, reason)
+            }
+            BountyError::InvalidField { field, reason } => {
+                write!(f, "InvalidField: {}: {}", field, reason)
+            }
+            BountyError::FeeMismatch {
+                field,
+                given,
+                expected,
+            } => write!(
+                f,
+                "FeeMismatch: {} is {} but the on-chain SettingsDatum requires {}",
+                field, given, expected
+            ),
+            BountyError::MissingBountyRef => {
+                write!(f, "MissingBountyRef: either bounty_id or bountyref is required")
+            }
+            BountyError::BountyRefMismatch {
+                bounty_ref,
+                bounty_id,
+                indexed_bounty_id,
+            } => write!(
+                f,
+                "BountyRefMismatch: {} holds bounty {}, not {}",
+                bounty_ref, indexed_bounty_id, bounty_id
+            ),
+            BountyError::StaleBountyRef {
+                bounty_ref,
+                current_ref: Some(current_ref),
+            } => write!(
+                f,
+                "StaleBountyRef: {} has already been spent, current bounty UTxO is {}",
+                bounty_ref, current_ref
+            ),
+            BountyError::StaleBountyRef {
+                bounty_ref,
+                current_ref: None,
+            } => write!(
+                f,
+                "StaleBountyRef: {} has already been spent and the bounty is no longer live",
+                bounty_ref
+            ),
+            BountyError::SubscriptionNotFound(id) => {
+                write!(f, "SubscriptionNotFound: {}", id)
+            }
+            BountyError::TipNotReady(reason) => write!(f, "TipNotReady: {}", reason),
+        }
+    }
+}
+
+impl From<BountyError> for balius_sdk::Error {
+    fn from(e: BountyError) -> Self {
+        balius_sdk::Error::Internal(e.to_string())
+    }
+}
+
+impl From<TimeError> for BountyError {
+    fn from(e: TimeError) -> Self {
+        BountyError::InvalidTime {
+            field: e.field,
+            reason: e.reason,
+        }
+    }
+}
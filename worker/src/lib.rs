@@ -1,40 +1,73 @@
+mod bounty_id;
+mod bounty_index;
+mod bounty_queries;
 mod chainsync;
+mod collateral;
+mod deadlines;
+mod errors;
 mod offchain_handlers;
+mod outbound;
+mod preview;
+mod settings;
 mod signature;
+mod subscriptions;
+mod time;
 mod types;
 mod utils;
+mod webhooks;
 
 use balius_sdk::wit::balius::app as worker;
+use balius_sdk::wit::balius::app::driver::UtxoPattern;
 use balius_sdk::{FnHandler, Worker};
+use serde_json::json;
 // use balius_sdk::wit::balius::app::submit;
-// use balius_sdk::wit::balius::app::driver::UtxoPattern;
 
-use crate::chainsync::get_latest_block;
-use crate::offchain_handlers::{add_funds, create_bounty, publish_settings};
-use crate::signature::sign_payload;
+use crate::bounty_id::derive_bounty_id_handler;
+use crate::bounty_queries::{get_bounty, get_bounty_history, list_bounties};
+use crate::chainsync::{get_latest_block, handle_transaction_event};
+use crate::collateral::reserve_collateral;
+use crate::offchain_handlers::{add_funds, add_funds_batch, create_bounty, publish_settings};
+use crate::preview::preview_tx;
+use crate::settings::quote_bounty;
+use crate::signature::{sign_payload, submit_tx};
+use crate::subscriptions::{subscribe, unsubscribe};
+use crate::utils::log_event;
+use crate::webhooks::replay_webhooks;
 
 #[balius_sdk::main]
 fn main() -> Worker {
     balius_sdk::logging::init();
 
-    worker::logging::log(
+    log_event(
         worker::logging::Level::Info,
         "init",
-        "Worker initialized - monitoring all transactions with manual filtering",
+        json!({ "message": "Worker initialized - monitoring all transactions with manual filtering" }),
     );
 
     Worker::new()
         .with_signer("payment-key", "ed25519") // Register signing key (loaded via baliusd config)
         .with_request_handler("get-latest-block", FnHandler::from(get_latest_block))
         .with_request_handler("sign-payload", FnHandler::from(sign_payload))
+        .with_request_handler("submit-tx", FnHandler::from(submit_tx))
         .with_request_handler("publish-settings", FnHandler::from(publish_settings))
         .with_request_handler("create-bounty", FnHandler::from(create_bounty))
         .with_request_handler("add-funds", FnHandler::from(add_funds))
-    // .with_tx_handler(
-    //     UtxoPattern {
-    //         address: None,  // Monitor ALL transactions, filter manually in handler
-    //         token: None,
-    //     },
-    //     FnHandler::from(handle_transaction_event),
-    // )
+        .with_request_handler("add-funds-batch", FnHandler::from(add_funds_batch))
+        .with_request_handler("get-bounty", FnHandler::from(get_bounty))
+        .with_request_handler("list-bounties", FnHandler::from(list_bounties))
+        .with_request_handler("get-bounty-history", FnHandler::from(get_bounty_history))
+        .with_request_handler("derive-bounty-id", FnHandler::from(derive_bounty_id_handler))
+        .with_request_handler("quote-bounty", FnHandler::from(quote_bounty))
+        .with_request_handler("preview-tx", FnHandler::from(preview_tx))
+        .with_request_handler("replay-webhooks", FnHandler::from(replay_webhooks))
+        .with_request_handler("subscribe", FnHandler::from(subscribe))
+        .with_request_handler("unsubscribe", FnHandler::from(unsubscribe))
+        .with_request_handler("reserve-collateral", FnHandler::from(reserve_collateral))
+        .with_tx_handler(
+            UtxoPattern {
+                address: None, // Monitor ALL transactions, filter manually in handler
+                token: None,
+            },
+            FnHandler::from(handle_transaction_event),
+        )
 }
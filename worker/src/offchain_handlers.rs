@@ -1,30 +1,75 @@
 use balius_sdk::{Config, Json, Params, WorkerResult};
 
 use crate::{
-    types::{TxEnvelope, WorkerConfig},
-    utils::do_tx_building_request,
+    bounty_id::new_bounty_id,
+    bounty_index::{resolve_bounty_ref, BountyRecord},
+    collateral::githoney_coin_control,
+    errors::BountyError,
+    settings::load_settings,
+    time::{de_time_input, network_slots, resolve_deadline, resolve_validity, tip_posix_ms},
+    types::{
+        Address, AssetName, BountyId, CoinControl, HexString, KeyHash, PolicyId, TxEnvelope,
+        UtxoRef,
+        WorkerConfig,
+    },
+    utils::{do_tx_building_request, key_address, parse_params, CallIds},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateWithLovelaceParams {
-    pub bounty_creation_fee: String,
-    pub bounty_id: String,
-    pub bounty_rewards_fee: String,
-    pub maintainer: String,
-    pub maintainer_payment_key: String,
-    pub maintainer_stake_key: String,
-    pub min_ada: String,
-    pub reward_amount: String,
-    pub since: String,
-    pub time_limit: String,
-    pub until: String,
+    // Either an explicit hex bounty id or the GitHub issue to derive it from
+    #[serde(default, skip_serializing)]
+    pub bounty_id: Option<BountyId>,
+    #[serde(default, skip_serializing)]
+    pub repo: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub issue: Option<u64>,
+    // Fees default to the on-chain SettingsDatum values
+    #[serde(default, skip_serializing)]
+    pub bounty_creation_fee: Option<u64>,
+    #[serde(default, skip_serializing)]
+    pub bounty_rewards_fee: Option<u64>,
+    pub maintainer: Address,
+    pub maintainer_payment_key: KeyHash,
+    pub maintainer_stake_key: KeyHash,
+    // Lovelace amounts
+    pub min_ada: u64,
+    pub reward_amount: u64,
+    // Slots, ISO-8601 timestamps or durations such as "2h"; default to the current tip
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub since: Option<String>,
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub time_limit: Option<String>,
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub until: Option<String>,
+    #[serde(flatten)]
+    pub coin_control: CoinControl,
+}
+
+// Uses the on-chain fee, rejecting caller-provided values the validator would refuse
+fn settings_fee(field: &'static str, given: Option<u64>, expected: u64) -> Result<u64, BountyError> {
+    match given {
+        Some(given) if given != expected => Err(BountyError::FeeMismatch {
+            field,
+            given,
+            expected,
+        }),
+        _ => Ok(expected),
+    }
 }
 
 #[derive(Serialize)]
 pub struct CreateWithLovelaceParamsExt<'a> {
     #[serde(flatten)]
     _base: &'a CreateWithLovelaceParams,
+    bounty_id: &'a str,
+    bounty_creation_fee: u64,
+    bounty_rewards_fee: u64,
+    since: u64,
+    time_limit: u64,
+    until: u64,
     githoneyaddr: &'a String,
     script: &'a String,
     admin_payment_key: &'a String,
@@ -33,13 +78,63 @@ pub struct CreateWithLovelaceParamsExt<'a> {
 }
 pub fn create_bounty(
     config: Config<WorkerConfig>,
-    params: Params<CreateWithLovelaceParams>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<TxEnvelope>> {
+    let ids = CallIds::from_params("create-bounty", &params.0)?;
+    let params: CreateWithLovelaceParams = parse_params(params.0)?;
+
+    let bounty_id = new_bounty_id(
+        params.bounty_id.as_ref(),
+        params.repo.as_deref(),
+        params.issue,
+    )?;
+
+    build_create_bounty(&config, &ids, &params, &bounty_id)
+}
+
+// Builds the createWithLovelace transaction for `params` under `bounty_id`,
+// with the fees of the on-chain SettingsDatum
+pub fn build_create_bounty(
+    config: &WorkerConfig,
+    ids: &CallIds,
+    params: &CreateWithLovelaceParams,
+    bounty_id: &str,
 ) -> WorkerResult<Json<TxEnvelope>> {
     let protocol_url =
         url::Url::parse(&format!("{}/create-bounty", &config.tx_builder_base_url)).unwrap();
 
+    let settings = load_settings(config)?;
+    let bounty_creation_fee = settings_fee(
+        "bounty_creation_fee",
+        params.bounty_creation_fee,
+        settings.bounty_creation_fee,
+    )?;
+    let bounty_rewards_fee = settings_fee(
+        "bounty_rewards_fee",
+        params.bounty_rewards_fee,
+        settings.bounty_reward_fee,
+    )?;
+
+    let slots = network_slots(&config.network)?;
+    let now = tip_posix_ms(&slots);
+    let (since, until) = resolve_validity(
+        &slots,
+        now,
+        params.since.as_deref(),
+        params.until.as_deref(),
+    )
+    .map_err(BountyError::from)?;
+    let time_limit =
+        resolve_deadline(now, params.time_limit.as_deref()).map_err(BountyError::from)?;
+
     let body = Some(serde_json::to_vec(&CreateWithLovelaceParamsExt {
-        _base: &params.0,
+        _base: params,
+        bounty_id,
+        bounty_creation_fee,
+        bounty_rewards_fee,
+        since,
+        time_limit,
+        until,
         githoneyaddr: &config.githoney_addr,
         script: &config.githoney_script_address,
         admin_payment_key: &config.admin_payment_cred,
@@ -47,20 +142,22 @@ pub fn create_bounty(
         minting_policy_id: &config.minting_policy_id,
     })?);
 
-    do_tx_building_request(protocol_url, body)
+    do_tx_building_request(config, ids, protocol_url, body)
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DeployParams {
-    pub creation_fee: String,
-    pub reward_fee: String,
-    pub script: String,
-    pub script_version: String,
-    pub settings_minting_policy: String,
-    pub settings_minting_version: String,
-    pub settings_policy_id: String,
-    pub settings_token_name: String,
-    pub utxo_ref: String,
+    pub creation_fee: u64,
+    pub reward_fee: u64,
+    pub script: Address,
+    pub script_version: u64,
+    pub settings_minting_policy: HexString,
+    pub settings_minting_version: u64,
+    pub settings_policy_id: PolicyId,
+    pub settings_token_name: AssetName,
+    pub utxo_ref: UtxoRef,
+    #[serde(flatten)]
+    pub coin_control: CoinControl,
 }
 
 #[derive(Serialize)]
@@ -74,36 +171,55 @@ struct DeployParamsExt<'a> {
 }
 pub fn publish_settings(
     config: Config<WorkerConfig>,
-    params: Params<DeployParams>,
+    params: Params<serde_json::Value>,
 ) -> WorkerResult<Json<TxEnvelope>> {
+    let ids = CallIds::from_params("publish-settings", &params.0)?;
+    let params: DeployParams = parse_params(params.0)?;
+    // Settings are deployed from `githoney_addr`, so use the worker's reserved collateral
+    let params = DeployParams {
+        coin_control: githoney_coin_control(&config, &params.coin_control),
+        ..params
+    };
     let protocol_url =
         url::Url::parse(&format!("{}/deploy-settings", &config.tx_builder_base_url)).unwrap();
 
     let body = Some(serde_json::to_vec(&DeployParamsExt {
-        _base: &params.0,
+        _base: &params,
         githoney_payment_credential: &config.githoney_payment_cred,
         githoney_script: &config.githoney_script_bytes,
         githoney_staking_credential: &config.githoney_staking_cred,
         githoneyaddr: &config.githoney_addr,
     })?);
 
-    do_tx_building_request(protocol_url, body)
+    do_tx_building_request(&config, &ids, protocol_url, body)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddParams {
-    pub bountyref: String,
-    pub rewardamount: String,
-    pub settingsref: String,
-    pub since: String,
-    pub sponsor: String,
-    pub until: String,
+    // Either the indexed bounty id or an explicit `txhash#index` bounty UTxO
+    #[serde(default, skip_serializing)]
+    pub bounty_id: Option<BountyId>,
+    #[serde(default, skip_serializing)]
+    pub bountyref: Option<UtxoRef>,
+    // Lovelace amount
+    pub rewardamount: u64,
+    // Slots, ISO-8601 timestamps or durations such as "2h"; default to the current tip
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub since: Option<String>,
+    pub sponsor: Address,
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub until: Option<String>,
+    #[serde(flatten)]
+    pub coin_control: CoinControl,
 }
 
 #[derive(Serialize)]
 pub struct AddParamsExt<'a> {
     #[serde(flatten)]
     _base: &'a AddParams,
+    bountyref: &'a String,
+    since: u64,
+    until: u64,
     script: &'a String,
     rewardassetname: &'a String,
     rewardpolicyid: &'a String,
@@ -112,19 +228,240 @@ pub struct AddParamsExt<'a> {
 
 pub fn add_funds(
     config: Config<WorkerConfig>,
-    params: Params<AddParams>,
+    params: Params<serde_json::Value>,
 ) -> WorkerResult<Json<TxEnvelope>> {
+    let ids = CallIds::from_params("add-funds", &params.0)?;
+    let params: AddParams = parse_params(params.0)?;
     let protocol_url =
         url::Url::parse(&format!("{}/add-funds", &config.tx_builder_base_url)).unwrap();
 
+    let explicit_ref = params.bountyref.as_ref().map(UtxoRef::to_string);
+    let bountyref = resolve_bounty_ref(
+        params.bounty_id.as_ref().map(BountyId::as_str),
+        explicit_ref.as_deref(),
+    )?;
+
+    let slots = network_slots(&config.network)?;
+    let (since, until) = resolve_validity(
+        &slots,
+        tip_posix_ms(&slots),
+        params.since.as_deref(),
+        params.until.as_deref(),
+    )
+    .map_err(BountyError::from)?;
+
     let body = Some(serde_json::to_vec(&AddParamsExt {
-        _base: &params.0,
+        _base: &params,
+        bountyref: &bountyref,
+        since,
+        until,
         script: &config.githoney_script_address,
         rewardassetname: &"".to_string(),
         rewardpolicyid: &"".to_string(),
         settingsref: &config.validator_ref,
     })?);
 
-    do_tx_building_request(protocol_url, body)
+    do_tx_building_request(&config, &ids, protocol_url, body)
 }
 
+// Largest multi-bounty add in `protocol/main.tx3` (`add3`)
+const MAX_ADD_BATCH_SIZE: usize = 3;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchTopUp {
+    // Either the indexed bounty id or an explicit `txhash#index` bounty UTxO
+    #[serde(default, skip_serializing)]
+    pub bounty_id: Option<BountyId>,
+    #[serde(default, skip_serializing)]
+    pub bountyref: Option<UtxoRef>,
+    // Amount of the reward asset, lovelace when no asset is given
+    pub rewardamount: u64,
+    #[serde(default, skip_serializing)]
+    pub rewardpolicyid: Option<HexString>,
+    #[serde(default, skip_serializing)]
+    pub rewardassetname: Option<AssetName>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddFundsBatchParams {
+    pub bounties: Vec<BatchTopUp>,
+    // Slots, ISO-8601 timestamps or durations such as "2h"; default to the current tip
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub since: Option<String>,
+    pub sponsor: Address,
+    #[serde(default, skip_serializing, deserialize_with = "de_time_input")]
+    pub until: Option<String>,
+    // Shared by every top-up
+    #[serde(flatten)]
+    pub coin_control: CoinControl,
+}
+
+#[derive(Serialize)]
+pub struct BatchTopUpExt<'a> {
+    #[serde(flatten)]
+    _base: &'a BatchTopUp,
+    bountyref: String,
+    rewardassetname: &'a str,
+    rewardpolicyid: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct AddFundsBatchParamsExt<'a> {
+    #[serde(flatten)]
+    coin_control: &'a CoinControl,
+    bounties: Vec<BatchTopUpExt<'a>>,
+    since: u64,
+    sponsor: &'a Address,
+    until: u64,
+    script: &'a String,
+    settingsref: &'a String,
+}
+
+pub fn add_funds_batch(
+    config: Config<WorkerConfig>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<TxEnvelope>> {
+    let ids = CallIds::from_params("add-funds-batch", &params.0)?;
+    let params: AddFundsBatchParams = parse_params(params.0)?;
+    if params.bounties.is_empty() || params.bounties.len() > MAX_ADD_BATCH_SIZE {
+        return Err(BountyError::InvalidField {
+            field: "bounties".to_string(),
+            reason: format!("expected between 1 and {} entries", MAX_ADD_BATCH_SIZE),
+        }
+        .into());
+    }
+    let protocol_url =
+        url::Url::parse(&format!("{}/add-funds-batch", &config.tx_builder_base_url)).unwrap();
+
+    let mut bounties = Vec::with_capacity(params.bounties.len());
+    for top_up in &params.bounties {
+        let explicit_ref = top_up.bountyref.as_ref().map(UtxoRef::to_string);
+        let bountyref = resolve_bounty_ref(
+            top_up.bounty_id.as_ref().map(BountyId::as_str),
+            explicit_ref.as_deref(),
+        )?;
+        bounties.push(BatchTopUpExt {
+            _base: top_up,
+            bountyref,
+            rewardassetname: top_up.rewardassetname.as_ref().map_or("", AssetName::as_str),
+            rewardpolicyid: top_up.rewardpolicyid.as_ref().map_or("", HexString::as_str),
+        });
+    }
+
+    let slots = network_slots(&config.network)?;
+    let (since, until) = resolve_validity(
+        &slots,
+        tip_posix_ms(&slots),
+        params.since.as_deref(),
+        params.until.as_deref(),
+    )
+    .map_err(BountyError::from)?;
+
+    let body = Some(serde_json::to_vec(&AddFundsBatchParamsExt {
+        coin_control: &params.coin_control,
+        bounties,
+        since,
+        sponsor: &params.sponsor,
+        until,
+        script: &config.githoney_script_address,
+        settingsref: &config.validator_ref,
+    })?);
+
+    do_tx_building_request(&config, &ids, protocol_url, body)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CloseParams {
+    pub bounty_id: String,
+    pub bountyref: String,
+    pub admin: String,
+    // Set for assigned bounties, whose contributor gets its deposit back
+    pub contributor: Option<ContributorRefund>,
+    pub maintainer: String,
+    pub minting_policy_id: String,
+    pub settingsref: String,
+    pub since: u64,
+    pub until: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContributorRefund {
+    pub address: String,
+    pub deposit: u64,
+}
+
+// The refund owed to the contributor of an assigned bounty, if any
+fn contributor_refund(config: &WorkerConfig, record: &BountyRecord) -> WorkerResult<Option<ContributorRefund>> {
+    let Some(payment_key) = &record.contributor_payment_key else {
+        return Ok(None);
+    };
+
+    let address = key_address(&config.network, payment_key, record.contributor_stake_key.as_deref())
+        .ok_or_else(|| {
+            balius_sdk::Error::Internal(format!(
+                "Invalid contributor keys for bounty {}",
+                record.bounty_id
+            ))
+        })?;
+    let deposit = record.contributor_deposit.ok_or_else(|| {
+        balius_sdk::Error::Internal(format!(
+            "Contributor deposit of bounty {} is unknown",
+            record.bounty_id
+        ))
+    })?;
+    Ok(Some(ContributorRefund { address, deposit }))
+}
+
+// Builds the unsigned admin close transaction for a live bounty, refunding
+// its contributor if it has one. Close takes its collateral from the admin,
+// who signs it, so the worker's reservation at `githoney_addr` is not pinned.
+pub fn build_close_tx(
+    config: &WorkerConfig,
+    record: &BountyRecord,
+    current_slot: u64,
+) -> WorkerResult<TxEnvelope> {
+    let protocol_url =
+        url::Url::parse(&format!("{}/close-bounty", &config.tx_builder_base_url)).unwrap();
+
+    let bountyref = record
+        .utxo_ref
+        .clone()
+        .ok_or_else(|| BountyError::NotLive(record.bounty_id.clone()))?;
+    // One id per bounty UTxO, however many blocks retry the close
+    let ids = CallIds::from_params("close-bounty", &json!({ "bountyref": bountyref }))?;
+
+    let maintainer = key_address(
+        &config.network,
+        &record.maintainer_payment_key,
+        record.maintainer_stake_key.as_deref(),
+    )
+    .ok_or_else(|| {
+        balius_sdk::Error::Internal(format!(
+            "Invalid maintainer keys for bounty {}",
+            record.bounty_id
+        ))
+    })?;
+
+    let slots = network_slots(&config.network)?;
+    let (since, until) = resolve_validity(
+        &slots,
+        Some(slots.slot_to_posix_ms(current_slot)),
+        None,
+        None,
+    )
+    .map_err(BountyError::from)?;
+
+    let body = Some(serde_json::to_vec(&CloseParams {
+        bounty_id: record.bounty_id.clone(),
+        bountyref,
+        admin: config.admin_address.clone(),
+        contributor: contributor_refund(config, record)?,
+        maintainer,
+        minting_policy_id: config.minting_policy_id.clone(),
+        settingsref: config.validator_ref.clone(),
+        since,
+        until,
+    })?);
+
+    Ok(do_tx_building_request(config, &ids, protocol_url, body)?.0)
+}
@@ -0,0 +1,136 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::http::{HttpRequest, HttpResponse};
+use balius_sdk::wit::balius::app::kv;
+use balius_sdk::WorkerResult;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use serde_json::json;
+
+use crate::bounty_index::chain_tip_slot;
+use crate::types::OutboundConfig;
+use crate::utils::log_event;
+
+const BREAKER_KEY_PREFIX: &str = "breaker:";
+
+///// CIRCUIT BREAKERS /////
+// Per-destination failure tracking, keyed by host
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until_slot: Option<u64>,
+}
+
+fn breaker_key(destination: &str) -> String {
+    format!("{}{}", BREAKER_KEY_PREFIX, destination)
+}
+
+fn load_breaker(destination: &str) -> Breaker {
+    kv::get_value(&breaker_key(destination))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_breaker(destination: &str, breaker: &Breaker) {
+    let stored = serde_json::to_vec(breaker)
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|value| {
+            kv::set_value(&breaker_key(destination), &value).map_err(|e| format!("{:?}", e))
+        });
+
+    if let Err(e) = stored {
+        log_event(
+            worker::logging::Level::Error,
+            "outbound",
+            json!({
+                "message": "Failed to store circuit breaker",
+                "destination": destination,
+                "error": e.to_string(),
+            }),
+        );
+    }
+}
+
+fn record_success(destination: &str) {
+    let breaker = load_breaker(destination);
+    if breaker.consecutive_failures > 0 || breaker.open_until_slot.is_some() {
+        save_breaker(destination, &Breaker::default());
+    }
+}
+
+fn record_failure(policy: &OutboundConfig, destination: &str, tip_slot: u64) {
+    let mut breaker = load_breaker(destination);
+    breaker.consecutive_failures += 1;
+
+    if breaker.consecutive_failures >= policy.breaker_threshold {
+        breaker.open_until_slot = Some(tip_slot + policy.breaker_cooldown_slots);
+        log_event(
+            worker::logging::Level::Warn,
+            "outbound",
+            json!({
+                "message": "Opening circuit",
+                "destination": destination,
+                "consecutive_failures": breaker.consecutive_failures,
+            }),
+        );
+    }
+
+    save_breaker(destination, &breaker);
+}
+
+///// RETRIES /////
+// 5xx and 429 are worth retrying; other statuses mean the request itself is wrong
+fn is_retryable(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+// Sends the request built by `build`, retrying transport errors and retryable
+// statuses up to `max_attempts` times. Calls to a destination whose circuit
+// is open fail immediately until the cooldown has passed.
+pub fn send_with_retries(
+    policy: &OutboundConfig,
+    url: &Url,
+    build: impl Fn() -> WorkerResult<HttpRequest>,
+) -> WorkerResult<HttpResponse> {
+    let destination = url.host_str().unwrap_or_default().to_string();
+    let tip_slot = chain_tip_slot().unwrap_or_default();
+
+    if let Some(open_until) = load_breaker(&destination).open_until_slot {
+        if tip_slot < open_until {
+            return Err(balius_sdk::Error::Internal(format!(
+                "Circuit open for {} until slot {}",
+                destination, open_until
+            )));
+        }
+    }
+
+    let mut last_error = String::new();
+    for attempt in 1..=policy.max_attempts.max(1) {
+        match build()?.send() {
+            Ok(response) if response.is_ok() || !is_retryable(response.status) => {
+                record_success(&destination);
+                return Ok(response);
+            }
+            Ok(response) => last_error = format!("status {}", response.status),
+            Err(e) => last_error = format!("{:?}", e),
+        }
+
+        log_event(
+            worker::logging::Level::Warn,
+            "outbound",
+            json!({
+                "message": "Attempt failed",
+                "destination": destination,
+                "attempt": attempt,
+                "error": last_error,
+            }),
+        );
+    }
+
+    record_failure(policy, &destination, tip_slot);
+    Err(balius_sdk::Error::Internal(format!(
+        "Request to {} failed after {} attempts: {}",
+        destination, policy.max_attempts, last_error
+    )))
+}
@@ -0,0 +1,63 @@
+use balius_sdk::{wit::balius::app::ledger, Config, Json, Params, WorkerResult};
+use pallas_traverse::{Era, MultiEraOutput, MultiEraTx};
+use serde::{Deserialize, Serialize};
+
+use githoney_common::preview::TxPreview;
+
+use crate::time::network_slots;
+use crate::types::{HexString, WorkerConfig};
+use crate::utils::parse_params;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PreviewParams {
+    // Hex-encoded transaction CBOR, as returned in `TxEnvelope.tx`
+    pub tx: HexString,
+}
+
+// `txhash#index` of the inputs of `tx` locked at the Githoney script
+fn script_inputs(config: &WorkerConfig, tx: &MultiEraTx) -> WorkerResult<Vec<String>> {
+    let script = pallas_addresses::Address::from_bech32(&config.githoney_script_address)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid script address: {}", e)))?
+        .to_vec();
+
+    let refs: Vec<ledger::TxoRef> = tx
+        .inputs()
+        .iter()
+        .map(|input| ledger::TxoRef {
+            tx_hash: input.hash().to_vec(),
+            tx_index: input.index() as u32,
+        })
+        .collect();
+    let utxos = ledger::read_utxos(&refs)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to read spent UTxOs: {:?}", e)))?;
+
+    Ok(utxos
+        .iter()
+        .filter(|utxo| {
+            MultiEraOutput::decode(Era::Conway, &utxo.body)
+                .ok()
+                .and_then(|output| output.address().ok())
+                .is_some_and(|address| address.to_vec() == script)
+        })
+        .map(|utxo| format!("{}#{}", hex::encode(&utxo.ref_.tx_hash), utxo.ref_.tx_index))
+        .collect())
+}
+
+// Decodes a built transaction into a reviewable summary, like the server's `/preview`
+pub fn preview_tx(
+    config: Config<WorkerConfig>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<TxPreview>> {
+    let params: PreviewParams = parse_params(params.0)?;
+    let slots = network_slots(&config.network)?;
+
+    let bytes = hex::decode(params.tx.as_str())
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid field `tx`: {}", e)))?;
+    let tx = MultiEraTx::decode(&bytes)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid transaction: {}", e)))?;
+
+    // Unknown inputs only leave their spend redeemers unnamed
+    let script_inputs = script_inputs(&config, &tx).unwrap_or_default();
+
+    Ok(Json(githoney_common::preview::preview_tx(&tx, &slots, &script_inputs)))
+}
@@ -0,0 +1,442 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::txbuilder::PParams;
+use balius_sdk::wit::balius::app::{kv, ledger};
+use balius_sdk::{Config, Json, Params, Tx, WorkerResult};
+use pallas_codec::minicbor;
+use pallas_codec::utils::{Bytes, CborWrap, Int, KeyValuePairs, PositiveCoin};
+use pallas_primitives::alonzo::{BigInt, Constr, PlutusData};
+use pallas_primitives::conway::{
+    MintedDatumOption, PostAlonzoTransactionOutput, PseudoDatumOption, TransactionOutput, Value,
+};
+use pallas_traverse::{Era, MultiEraOutput, MultiEraTx};
+use serde::{Deserialize, Serialize};
+
+use githoney_common::datum::{decode_settings_datum, SettingsDatum};
+use serde_json::json;
+use crate::errors::BountyError;
+use crate::offchain_handlers::{build_create_bounty, CreateWithLovelaceParams};
+use crate::time::{network_slots, resolve_deadline, tip_posix_ms};
+use crate::types::{Address, CoinControl, KeyHash, WorkerConfig};
+use crate::utils::{log_event, parse_params, parse_utxo_ref, CallIds};
+
+const SETTINGS_CACHE_KEY: &str = "settings-datum";
+// The settings UTxO a transaction has spent, see `observe_settings_spend`
+const SETTINGS_SPENT_KEY: &str = "settings-spent";
+
+// `bounty_reward_fee` is expressed in basis points of the reward amount, so
+// a SettingsDatum above this denominator is refused rather than quoted
+const REWARD_FEE_DENOMINATOR: u64 = 10_000;
+
+// Bytes the ledger adds to an output's serialized size for its min-ADA:
+// (UTXO_OVERHEAD_BYTES + serialized size) * coinsPerUTxOByte
+const UTXO_OVERHEAD_BYTES: u64 = 160;
+
+// Key hashes are always 28 bytes and asset names at most 32, so placeholders
+// of those sizes give the largest bounty output a createWithLovelace can produce
+const PLACEHOLDER_KEY_HASH: [u8; 28] = [0; 28];
+const PLACEHOLDER_BOUNTY_ID: [u8; 32] = [0; 32];
+
+#[derive(Serialize, Deserialize)]
+struct CachedSettings {
+    validator_ref: String,
+    datum: SettingsDatum,
+}
+
+fn read_settings_utxo(validator_ref: &str) -> WorkerResult<SettingsDatum> {
+    let (tx_hash, tx_index) = parse_utxo_ref(validator_ref).ok_or_else(|| {
+        balius_sdk::Error::Internal(format!("Invalid validator_ref: {}", validator_ref))
+    })?;
+
+    let utxos = ledger::read_utxos(&[ledger::TxoRef { tx_hash, tx_index }])
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to read settings UTxO: {:?}", e)))?;
+
+    let utxo = utxos.first().ok_or_else(|| {
+        balius_sdk::Error::Internal(format!("Settings UTxO not found: {}", validator_ref))
+    })?;
+
+    let output = MultiEraOutput::decode(Era::Conway, &utxo.body).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to decode settings UTxO: {}", e))
+    })?;
+
+    let datum = match output.datum() {
+        Some(MintedDatumOption::Data(data)) => decode_settings_datum(data.0.raw_cbor()),
+        _ => None,
+    };
+
+    let datum = datum.ok_or_else(|| {
+        balius_sdk::Error::Internal(format!(
+            "Settings UTxO {} has no valid SettingsDatum",
+            validator_ref
+        ))
+    })?;
+
+    if datum.bounty_reward_fee > REWARD_FEE_DENOMINATOR {
+        return Err(balius_sdk::Error::Internal(format!(
+            "Settings UTxO {} has a bounty_reward_fee of {}, above {} basis points",
+            validator_ref, datum.bounty_reward_fee, REWARD_FEE_DENOMINATOR
+        )));
+    }
+
+    Ok(datum)
+}
+
+#[derive(Serialize, Deserialize)]
+struct SpentSettings {
+    validator_ref: String,
+    tx_hash: String,
+}
+
+// Records that `tx` spent the settings UTxO. A UTxO never changes, so the
+// cached datum stays valid until then; after it `load_settings` refuses to
+// serve fees from a SettingsDatum that is no longer on chain.
+pub fn observe_settings_spend(config: &WorkerConfig, tx: &Tx) -> WorkerResult<()> {
+    let Some((tx_hash, tx_index)) = parse_utxo_ref(&config.validator_ref) else {
+        return Ok(());
+    };
+    let spends_settings = tx
+        .tx
+        .inputs
+        .iter()
+        .any(|input| input.tx_hash.as_ref() == tx_hash.as_slice() && input.output_index == tx_index);
+    if !spends_settings {
+        return Ok(());
+    }
+
+    let spent = SpentSettings {
+        validator_ref: config.validator_ref.clone(),
+        tx_hash: hex::encode(&tx.hash),
+    };
+
+    log_event(
+        worker::logging::Level::Warn,
+        "settings",
+        json!({
+            "message": "Settings UTxO spent, validator_ref needs updating",
+            "settings_ref": spent.validator_ref,
+            "tx_hash": spent.tx_hash,
+        }),
+    );
+
+    kv::set_value(SETTINGS_SPENT_KEY, &serde_json::to_vec(&spent)?).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to record spent settings: {:?}", e))
+    })
+}
+
+// Returns the SettingsDatum at `validator_ref`, reading it from the ledger only
+// when the cache is empty or was filled for a different settings UTxO.
+pub fn load_settings(config: &WorkerConfig) -> WorkerResult<SettingsDatum> {
+    let spent: Option<SpentSettings> = kv::get_value(SETTINGS_SPENT_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+
+    if let Some(spent) = spent.filter(|spent| spent.validator_ref == config.validator_ref) {
+        return Err(balius_sdk::Error::Internal(format!(
+            "Settings UTxO {} was spent by {}, update validator_ref",
+            spent.validator_ref, spent.tx_hash
+        )));
+    }
+
+    let cached: Option<CachedSettings> = kv::get_value(SETTINGS_CACHE_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+
+    if let Some(cached) = cached.filter(|cached| cached.validator_ref == config.validator_ref) {
+        return Ok(cached.datum);
+    }
+
+    let datum = read_settings_utxo(&config.validator_ref)?;
+
+    log_event(
+        worker::logging::Level::Info,
+        "settings",
+        json!({
+            "message": "Cached settings",
+            "settings_ref": config.validator_ref,
+            "bounty_creation_fee": datum.bounty_creation_fee,
+            "bounty_reward_fee": datum.bounty_reward_fee,
+        }),
+    );
+
+    let cache = CachedSettings {
+        validator_ref: config.validator_ref.clone(),
+        datum: datum.clone(),
+    };
+    kv::set_value(SETTINGS_CACHE_KEY, &serde_json::to_vec(&cache)?)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to cache settings: {:?}", e)))?;
+
+    Ok(datum)
+}
+
+fn overflow(field: &str) -> BountyError {
+    BountyError::InvalidField {
+        field: field.to_string(),
+        reason: "amount is too large".to_string(),
+    }
+}
+
+// Widened to u128 so basis points of any u64 reward fit before dividing
+pub fn reward_fee(settings: &SettingsDatum, reward_amount: u64) -> Result<u64, BountyError> {
+    let fee = u128::from(reward_amount) * u128::from(settings.bounty_reward_fee)
+        / u128::from(REWARD_FEE_DENOMINATOR);
+    u64::try_from(fee).map_err(|_| overflow("reward_amount"))
+}
+
+fn protocol_params() -> WorkerResult<PParams> {
+    let bytes = ledger::read_params()
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to read protocol params: {:?}", e)))?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to parse protocol params: {}", e))
+    })
+}
+
+fn constr(index: u64, fields: Vec<PlutusData>) -> PlutusData {
+    PlutusData::Constr(Constr {
+        tag: 121 + index,
+        any_constructor: None,
+        fields,
+    })
+}
+
+fn int(value: u64) -> PlutusData {
+    PlutusData::BigInt(BigInt::Int(Int(minicbor::data::Int::from(value))))
+}
+
+fn key_credential(key: &[u8]) -> PlutusData {
+    constr(0, vec![PlutusData::BoundedBytes(key.to_vec().into())])
+}
+
+// GithoneyDatum of a new bounty holding `lovelace`, with placeholder maintainer keys
+fn bounty_datum(
+    admin_payment_key: &[u8],
+    lovelace: u64,
+    bounty_reward_fee: u64,
+    deadline: u64,
+) -> PlutusData {
+    let maintainer_address = constr(
+        0,
+        vec![
+            key_credential(&PLACEHOLDER_KEY_HASH),
+            constr(0, vec![constr(0, vec![key_credential(&PLACEHOLDER_KEY_HASH)])]),
+        ],
+    );
+    let initial_value = PlutusData::Map(KeyValuePairs::Def(vec![(
+        PlutusData::BoundedBytes(vec![].into()),
+        PlutusData::Map(KeyValuePairs::Def(vec![(
+            PlutusData::BoundedBytes(vec![].into()),
+            int(lovelace),
+        )])),
+    )]));
+
+    constr(
+        0,
+        vec![
+            key_credential(admin_payment_key),
+            maintainer_address,
+            constr(1, vec![]),
+            int(bounty_reward_fee),
+            int(deadline),
+            constr(0, vec![]),
+            initial_value,
+        ],
+    )
+}
+
+// The bounty output createWithLovelace pays to the script (see
+// `protocol/main.tx3`), with placeholder keys and bounty id
+fn bounty_output(
+    config: &WorkerConfig,
+    lovelace: u64,
+    bounty_reward_fee: u64,
+    deadline: u64,
+) -> WorkerResult<TransactionOutput> {
+    let address = pallas_addresses::Address::from_bech32(&config.githoney_script_address)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid githoney_script_address: {}", e)))?;
+    let policy_id = config.minting_policy_id.parse().map_err(|_| {
+        balius_sdk::Error::Internal(format!("Invalid minting_policy_id: {}", config.minting_policy_id))
+    })?;
+    let admin_payment_key = hex::decode(&config.admin_payment_cred).map_err(|_| {
+        balius_sdk::Error::Internal(format!("Invalid admin_payment_cred: {}", config.admin_payment_cred))
+    })?;
+
+    let nft = vec![(
+        policy_id,
+        vec![(Bytes::from(PLACEHOLDER_BOUNTY_ID.to_vec()), PositiveCoin::try_from(1).unwrap())]
+            .try_into()
+            .unwrap(),
+    )]
+    .try_into()
+    .unwrap();
+    let datum = bounty_datum(&admin_payment_key, lovelace, bounty_reward_fee, deadline);
+
+    Ok(TransactionOutput::PostAlonzo(PostAlonzoTransactionOutput {
+        address: address.to_vec().into(),
+        value: Value::Multiasset(lovelace, nft),
+        datum_option: Some(PseudoDatumOption::Data(CborWrap(datum))),
+        script_ref: None,
+    }))
+}
+
+// Lovelace deposit that alone covers the min-ADA of the bounty output holding
+// `reward_amount` on top of it. The deposit is part of the output it pays for,
+// so it is raised until the output's size stops growing.
+pub fn bounty_min_ada(
+    config: &WorkerConfig,
+    pparams: &PParams,
+    settings: &SettingsDatum,
+    reward_amount: u64,
+) -> WorkerResult<u64> {
+    let slots = network_slots(&config.network)?;
+    let deadline = resolve_deadline(tip_posix_ms(&slots), None).map_err(BountyError::from)?;
+
+    let mut min_ada = 0;
+    loop {
+        let lovelace = reward_amount
+            .checked_add(min_ada)
+            .ok_or_else(|| overflow("reward_amount"))?;
+        let output = bounty_output(config, lovelace, settings.bounty_reward_fee, deadline)?;
+        let size = minicbor::to_vec(&output)
+            .map_err(|e| balius_sdk::Error::Internal(format!("Failed to encode bounty output: {}", e)))?
+            .len() as u64;
+        let required = (UTXO_OVERHEAD_BYTES + size) * pparams.coins_per_utxo_byte;
+        if required <= min_ada {
+            return Ok(min_ada);
+        }
+        min_ada = required;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct QuoteBountyParams {
+    // Reward in lovelace
+    pub reward_amount: u64,
+    // With the maintainer's address and keys the createWithLovelace
+    // transaction is built, and its fee included in the quote
+    #[serde(default)]
+    pub maintainer: Option<Address>,
+    #[serde(default)]
+    pub maintainer_payment_key: Option<KeyHash>,
+    #[serde(default)]
+    pub maintainer_stake_key: Option<KeyHash>,
+    #[serde(flatten)]
+    pub coin_control: CoinControl,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BountyQuote {
+    pub reward_amount: u64,
+    pub bounty_creation_fee: u64,
+    // Basis points of the reward, taken when the bounty is paid out
+    pub bounty_reward_fee: u64,
+    pub reward_fee: u64,
+    pub min_ada: u64,
+    // Fee of the built transaction; None without the maintainer
+    pub network_fee: Option<u64>,
+    // Lovelace the maintainer needs to create the bounty; None without the network fee
+    pub total: Option<u64>,
+}
+
+// Fee of the createWithLovelace transaction the tx builder returns for the quote
+fn create_bounty_fee(
+    config: &WorkerConfig,
+    ids: &CallIds,
+    params: &CreateWithLovelaceParams,
+) -> WorkerResult<u64> {
+    let Json(envelope) = build_create_bounty(
+        config,
+        ids,
+        params,
+        &hex::encode(PLACEHOLDER_BOUNTY_ID),
+    )?;
+    let cbor = hex::decode(&envelope.tx).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Tx builder returned invalid hex: {}", e))
+    })?;
+    let tx = MultiEraTx::decode(&cbor).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Tx builder returned an invalid transaction: {}", e))
+    })?;
+    tx.fee().ok_or_else(|| {
+        balius_sdk::Error::Internal("Tx builder returned a transaction without a fee".to_string())
+    })
+}
+
+pub fn quote_bounty(
+    config: Config<WorkerConfig>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<BountyQuote>> {
+    let ids = CallIds::from_params("quote-bounty", &params.0)?;
+    let params: QuoteBountyParams = parse_params(params.0)?;
+    let settings = load_settings(&config)?;
+    let min_ada = bounty_min_ada(&config, &protocol_params()?, &settings, params.reward_amount)?;
+
+    let network_fee = match (
+        params.maintainer,
+        params.maintainer_payment_key,
+        params.maintainer_stake_key,
+    ) {
+        (Some(maintainer), Some(maintainer_payment_key), Some(maintainer_stake_key)) => {
+            Some(create_bounty_fee(
+                &config,
+                &ids,
+                &CreateWithLovelaceParams {
+                    bounty_id: None,
+                    repo: None,
+                    issue: None,
+                    bounty_creation_fee: None,
+                    bounty_rewards_fee: None,
+                    maintainer,
+                    maintainer_payment_key,
+                    maintainer_stake_key,
+                    min_ada,
+                    reward_amount: params.reward_amount,
+                    since: None,
+                    time_limit: None,
+                    until: None,
+                    coin_control: params.coin_control,
+                },
+            )?)
+        }
+        _ => None,
+    };
+
+    let total = network_fee
+        .map(|network_fee| {
+            params
+                .reward_amount
+                .checked_add(settings.bounty_creation_fee)
+                .and_then(|total| total.checked_add(min_ada))
+                .and_then(|total| total.checked_add(network_fee))
+                .ok_or_else(|| overflow("reward_amount"))
+        })
+        .transpose()?;
+
+    Ok(Json(BountyQuote {
+        reward_amount: params.reward_amount,
+        bounty_creation_fee: settings.bounty_creation_fee,
+        bounty_reward_fee: settings.bounty_reward_fee,
+        reward_fee: reward_fee(&settings, params.reward_amount)?,
+        min_ada,
+        network_fee,
+        total,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use githoney_common::datum::decode_githoney_datum;
+
+    #[test]
+    fn sized_bounty_datum_has_the_githoney_datum_shape() {
+        let admin = [7u8; 28];
+        let datum = bounty_datum(&admin, 12_000_000, 250, 1_700_000_000_000);
+        let decoded = decode_githoney_datum(&minicbor::to_vec(&datum).unwrap()).unwrap();
+
+        assert_eq!(decoded.admin_payment_key, hex::encode(admin));
+        assert_eq!(decoded.maintainer_payment_key, hex::encode(PLACEHOLDER_KEY_HASH));
+        assert_eq!(decoded.maintainer_stake_key, Some(hex::encode(PLACEHOLDER_KEY_HASH)));
+        assert_eq!(decoded.contributor_payment_key, None);
+        assert_eq!(decoded.bounty_reward_fee, 250);
+        assert_eq!(decoded.deadline, 1_700_000_000_000);
+        assert!(!decoded.merged);
+    }
+}
@@ -1,8 +1,12 @@
 use balius_sdk::{Config, Json, Params, WorkerResult};
+use balius_sdk::wit::balius::app as worker;
 use balius_sdk::wit::balius::app::sign;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
+use crate::chainsync::track_transaction;
 use crate::types::{WorkerConfig};
+use crate::utils::{do_tx_building_request, log_event, parse_params, CallIds};
 
 #[derive(Serialize, Deserialize)]
 pub struct SubmitTxParams {
@@ -15,6 +19,12 @@ pub struct SubmitResponse {
     pub tx_hash: Option<String>,
 }
 
+// The part of the tx builder's `/submit` response the worker needs
+#[derive(Deserialize)]
+struct Submitted {
+    hash: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SignPayloadParams {
     pub key_name: String,
@@ -46,3 +56,39 @@ pub fn sign_payload(
         public_key: config.payment_key_public.clone(),
     }))
 }
+
+// Submits a signed transaction through the tx builder, which only accepts
+// transactions it built for this worker and keeps their outputs available to
+// follow-up builds. The transaction is then tracked until it confirms.
+pub fn submit_tx(
+    config: Config<WorkerConfig>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<SubmitResponse>> {
+    let ids = CallIds::from_params("submit-tx", &params.0)?;
+    let params: SubmitTxParams = parse_params(params.0)?;
+    let submit_url = url::Url::parse(&format!("{}/submit", &config.tx_builder_base_url)).unwrap();
+    let body = Some(serde_json::to_vec(&json!({ "tx": params.tx_cbor }))?);
+
+    let Json(submitted): Json<Result<Submitted, String>> =
+        do_tx_building_request(&config, &ids, submit_url, body)?;
+    match submitted {
+        Ok(submitted) => {
+            track_transaction(&submitted.hash)?;
+            Ok(Json(SubmitResponse {
+                success: true,
+                tx_hash: Some(submitted.hash),
+            }))
+        }
+        Err(e) => {
+            log_event(
+                worker::logging::Level::Warn,
+                "submit",
+                json!({ "request_id": ids.request_id, "error": e }),
+            );
+            Ok(Json(SubmitResponse {
+                success: false,
+                tx_hash: None,
+            }))
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::wit::balius::app::kv;
+use balius_sdk::{Config, Json, Params, WorkerResult};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use serde_json::json;
+
+use crate::bounty_index::BountyRecord;
+use crate::errors::BountyError;
+use crate::types::{BountyId, KeyHash, WorkerConfig};
+use crate::utils::{log_event, parse_params};
+use crate::webhooks::{drop_subscription_deliveries, EventType};
+
+const SUBSCRIPTIONS_KEY: &str = "webhook-subscriptions";
+const SUBSCRIPTION_SEQ_KEY: &str = "subscription-seq";
+
+// A webhook consumer registered through `subscribe`. Empty filters match
+// everything; bounty and maintainer filters never match events without a bounty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    // Deliveries are signed with HMAC-SHA256 of this secret, like `webhook_secret`
+    pub secret: String,
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    #[serde(default)]
+    pub bounty_ids: Vec<BountyId>,
+    // Maintainer payment key hashes
+    #[serde(default)]
+    pub maintainers: Vec<KeyHash>,
+}
+
+impl Subscription {
+    pub fn matches(&self, event_type: EventType, bounty: Option<&BountyRecord>) -> bool {
+        let type_matches = self.event_types.is_empty() || self.event_types.contains(&event_type);
+        let bounty_matches = self.bounty_ids.is_empty()
+            || bounty.is_some_and(|record| {
+                self.bounty_ids.iter().any(|id| id.as_str() == record.bounty_id)
+            });
+        let maintainer_matches = self.maintainers.is_empty()
+            || bounty.is_some_and(|record| {
+                self.maintainers
+                    .iter()
+                    .any(|key| key.as_str() == record.maintainer_payment_key)
+            });
+
+        type_matches && bounty_matches && maintainer_matches
+    }
+}
+
+pub fn load_subscriptions() -> Vec<Subscription> {
+    kv::get_value(SUBSCRIPTIONS_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_subscriptions(subscriptions: &[Subscription]) -> WorkerResult<()> {
+    kv::set_value(SUBSCRIPTIONS_KEY, &serde_json::to_vec(subscriptions)?).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to store webhook subscriptions: {:?}", e))
+    })
+}
+
+fn next_subscription_id() -> WorkerResult<String> {
+    let seq = kv::get_value(SUBSCRIPTION_SEQ_KEY)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|seq| seq.parse::<u64>().ok())
+        .unwrap_or(0)
+        + 1;
+    kv::set_value(SUBSCRIPTION_SEQ_KEY, seq.to_string().as_bytes()).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to store subscription sequence: {:?}", e))
+    })?;
+    Ok(format!("sub-{}", seq))
+}
+
+///// HANDLERS /////
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubscribeParams {
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    #[serde(default)]
+    pub bounty_ids: Vec<BountyId>,
+    #[serde(default)]
+    pub maintainers: Vec<KeyHash>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubscribeResponse {
+    pub id: String,
+}
+
+pub fn subscribe(
+    _config: Config<WorkerConfig>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<SubscribeResponse>> {
+    let params: SubscribeParams = parse_params(params.0)?;
+
+    let url = Url::parse(&params.url).map_err(|e| BountyError::InvalidField {
+        field: "url".to_string(),
+        reason: e.to_string(),
+    })?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(BountyError::InvalidField {
+            field: "url".to_string(),
+            reason: format!("unsupported scheme {:?}", url.scheme()),
+        }
+        .into());
+    }
+    if params.secret.is_empty() {
+        return Err(BountyError::InvalidField {
+            field: "secret".to_string(),
+            reason: "must not be empty".to_string(),
+        }
+        .into());
+    }
+
+    let subscription = Subscription {
+        id: next_subscription_id()?,
+        url: url.to_string(),
+        secret: params.secret,
+        event_types: params.event_types,
+        bounty_ids: params.bounty_ids,
+        maintainers: params.maintainers,
+    };
+
+    let mut subscriptions = load_subscriptions();
+    subscriptions.push(subscription.clone());
+    save_subscriptions(&subscriptions)?;
+
+    log_event(
+        worker::logging::Level::Info,
+        "webhook",
+        json!({
+            "message": "Added subscription",
+            "subscription_id": subscription.id,
+            "url": subscription.url,
+        }),
+    );
+
+    Ok(Json(SubscribeResponse {
+        id: subscription.id,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnsubscribeParams {
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnsubscribeResponse {
+    pub id: String,
+    // Queued deliveries dropped with the subscription
+    pub dropped: usize,
+}
+
+pub fn unsubscribe(
+    _config: Config<WorkerConfig>,
+    params: Params<serde_json::Value>,
+) -> WorkerResult<Json<UnsubscribeResponse>> {
+    let params: UnsubscribeParams = parse_params(params.0)?;
+
+    let mut subscriptions = load_subscriptions();
+    let before = subscriptions.len();
+    subscriptions.retain(|subscription| subscription.id != params.id);
+    if subscriptions.len() == before {
+        return Err(BountyError::SubscriptionNotFound(params.id).into());
+    }
+    save_subscriptions(&subscriptions)?;
+
+    let dropped = drop_subscription_deliveries(&params.id)?;
+
+    log_event(
+        worker::logging::Level::Info,
+        "webhook",
+        json!({
+            "message": "Removed subscription",
+            "subscription_id": params.id,
+            "dropped_deliveries": dropped,
+        }),
+    );
+
+    Ok(Json(UnsubscribeResponse {
+        id: params.id,
+        dropped,
+    }))
+}
@@ -0,0 +1,86 @@
+use balius_sdk::wit::balius::app::kv;
+use balius_sdk::WorkerResult;
+use serde::{Deserialize, Serialize};
+
+pub use githoney_common::time::{
+    de_time_input, resolve_deadline, resolve_validity, SlotConfig, TimeError,
+};
+
+use crate::bounty_index::chain_tip_slot;
+use crate::chainsync::latest_block;
+use crate::errors::BountyError;
+use crate::types::WorkerConfig;
+
+// Highest network tip slot seen from Blockfrost, see `NetworkTip`
+const NETWORK_TIP_SLOT_KEY: &str = "network-tip-slot";
+
+pub fn network_slots(network: &str) -> WorkerResult<SlotConfig> {
+    SlotConfig::for_network(network).ok_or_else(|| {
+        balius_sdk::Error::Internal(format!("Unknown network in config: {}", network))
+    })
+}
+
+// The network tip slot from Blockfrost and the indexed tip it was checked at
+#[derive(Serialize, Deserialize)]
+struct NetworkTip {
+    slot: u64,
+    checked_at: u64,
+}
+
+fn known_network_tip() -> Option<NetworkTip> {
+    kv::get_value(NETWORK_TIP_SLOT_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+fn network_tip_slot(config: &WorkerConfig, tip: u64) -> WorkerResult<u64> {
+    let slot = latest_block(config)?["slot"].as_u64().ok_or_else(|| {
+        balius_sdk::Error::Internal("Blockfrost latest block has no slot".to_string())
+    })?;
+    let known = NetworkTip {
+        slot,
+        checked_at: tip,
+    };
+    kv::set_value(NETWORK_TIP_SLOT_KEY, &serde_json::to_vec(&known)?).map_err(|e| {
+        balius_sdk::Error::Internal(format!("Failed to store network tip: {:?}", e))
+    })?;
+    Ok(slot)
+}
+
+// The indexed chain tip, provided it is within `max_tip_lag_slots` of the
+// network's. The worker has no wall clock, and while it catches up the tip
+// lies in the past, so anything timestamped with it would be rejected.
+//
+// Blockfrost is asked at most once per indexed block: until the next block
+// arrives the answer for the current tip is reused.
+pub fn recent_tip_slot(config: &WorkerConfig) -> WorkerResult<u64> {
+    let tip = chain_tip_slot()
+        .ok_or_else(|| BountyError::TipNotReady("no block has been seen yet".to_string()))?;
+    let behind = |network: u64| tip.saturating_add(config.max_tip_lag_slots) < network;
+
+    // The network tip only moves forward, so a tip behind an earlier
+    // observation is still catching up without asking Blockfrost again
+    let network = match known_network_tip() {
+        Some(known) if behind(known.slot) || known.checked_at == tip => known.slot,
+        _ => network_tip_slot(config, tip)?,
+    };
+    if behind(network) {
+        return Err(BountyError::TipNotReady(format!(
+            "indexed tip {} is {} slots behind the network, still catching up",
+            tip,
+            network - tip
+        ))
+        .into());
+    }
+    Ok(tip)
+}
+
+// Unix time of a recent chain tip in seconds, for signature timestamps
+pub fn tip_unix_secs(config: &WorkerConfig) -> WorkerResult<u64> {
+    Ok(network_slots(&config.network)?.slot_to_posix_ms(recent_tip_slot(config)?) / 1000)
+}
+
+// The chain tip as the current time for `resolve_validity` and `resolve_deadline`
+pub fn tip_posix_ms(slots: &SlotConfig) -> Option<u64> {
+    chain_tip_slot().map(|tip| slots.slot_to_posix_ms(tip))
+}
@@ -2,21 +2,80 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct WorkerConfig {
+    // Cardano network name: mainnet, preprod or preview
+    #[serde(default = "default_network")]
+    pub network: String,
     // Blockfrost config
     pub project_id: String,
     pub payment_key_public: String,
+    // Receives tx-confirmed and expired events; other consumers register with `subscribe`.
+    // Point it at the tx builder's `/tx-confirmed` so it drops confirmed pending outputs.
     pub webhook_url: String,
+    // Signs webhook deliveries with HMAC-SHA256; without it they are signed with `payment-key`
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
     pub monitoring_address: String,
     // Githoney specific config
     pub admin_payment_cred: String,
+    pub admin_address: String,
     pub githoney_script_address: String,
     pub githoney_script_bytes: String,
     pub githoney_addr: String,
     pub githoney_payment_cred: String,
     pub githoney_staking_cred: String,
     pub tx_builder_base_url: String,
+    // Shared secret for signing tx-builder requests (`worker.secret` in the server's AUTH_CONFIG)
+    #[serde(default)]
+    pub tx_builder_secret: Option<String>,
     pub validator_ref: String,
     pub minting_policy_id: String,
+    // Retry and circuit breaker settings for outbound HTTP
+    #[serde(default)]
+    pub outbound: OutboundConfig,
+    // Slots the indexed tip may trail the network's before the worker stops
+    // signing and building, as it is still catching up
+    #[serde(default = "default_max_tip_lag_slots")]
+    pub max_tip_lag_slots: u64,
+}
+
+fn default_network() -> String {
+    "preprod".to_string()
+}
+
+fn default_max_tip_lag_slots() -> u64 {
+    300
+}
+
+// The worker has no timer, so retries within a call are immediate and
+// backoff between calls is measured in slots of a recent chain tip.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OutboundConfig {
+    // Attempts per tx builder call before giving up on it. Webhook
+    // deliveries make one per flush and rely on the outbox backoff.
+    pub max_attempts: u32,
+    // Consecutive failed calls that open a destination's circuit
+    pub breaker_threshold: u32,
+    // Slots an open circuit rejects calls before letting one through again
+    pub breaker_cooldown_slots: u64,
+    // Outbox backoff: base * 2^attempts slots, capped at max
+    pub outbox_base_backoff_slots: u64,
+    pub outbox_max_backoff_slots: u64,
+    // Deliveries are marked failed after this many attempts, until replayed
+    pub outbox_max_attempts: u32,
+}
+
+impl Default for OutboundConfig {
+    fn default() -> Self {
+        OutboundConfig {
+            max_attempts: 3,
+            breaker_threshold: 5,
+            breaker_cooldown_slots: 120,
+            outbox_base_backoff_slots: 20,
+            outbox_max_backoff_slots: 3600,
+            outbox_max_attempts: 20,
+        }
+    }
 }
 
 ///// OFFCHAIN PROTOCOL TYPES /////
@@ -27,3 +86,22 @@ pub struct TxEnvelope {
     #[serde(rename = "tx")]
     pub tx: String,
 }
+
+// Validated request argument types, shared with the tx builder
+pub use githoney_common::types::{Address, AssetName, BountyId, HexString, KeyHash, PolicyId, UtxoRef};
+
+// Optional coin control for build requests, passed through to the tx builder,
+// which withholds transactions that do not honour it
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CoinControl {
+    // UTxOs the transaction must spend
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<UtxoRef>,
+    // UTxOs the transaction must not spend, e.g. ones holding NFTs or stake
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_inputs: Vec<UtxoRef>,
+    // UTxOs the transaction may use as collateral; any when empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub collateral: Vec<UtxoRef>,
+}
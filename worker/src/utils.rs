@@ -1,19 +1,204 @@
+use balius_sdk::wit::balius::app as worker;
+use balius_sdk::wit::balius::app::kv;
 use balius_sdk::{http::HttpRequest, Json, WorkerResult};
+use pallas_addresses::{
+    Address, Network, ShelleyAddress, ShelleyDelegationPart, ShelleyPaymentPart,
+};
+use hmac::{Hmac, Mac};
+use pallas_crypto::hash::Hash;
+use sha2::{Digest, Sha256};
+use serde_json::{json, Value};
 use url::Url;
 
-use crate::types::TxEnvelope;
+use crate::errors::BountyError;
+use crate::outbound::send_with_retries;
+use crate::time::tip_unix_secs;
+use crate::types::WorkerConfig;
 
-pub fn do_tx_building_request(url: Url, body: Option<Vec<u8>>) -> WorkerResult<Json<TxEnvelope>> {
-    let mut request = HttpRequest::post(url).header("Content-Type", "application/json");
-    request.body = body;
+// Longest caller-provided `request_id` or `idempotency_key` forwarded to the tx builder
+const MAX_CALLER_ID_LEN: usize = 128;
+// Last nonce a signed tx builder request was sent with
+const REQUEST_NONCE_KEY: &str = "tx-builder-nonce";
 
-    let response = request
-        .send()
-        .map_err(|e| balius_sdk::Error::Internal(format!("Protocol request error: {:?}", e)))?;
+// A caller-provided id param, checked to be usable as a header value
+fn caller_id(params: &Value, field: &str) -> Result<Option<String>, BountyError> {
+    match params.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(id))
+            if !id.is_empty()
+                && id.len() <= MAX_CALLER_ID_LEN
+                && id.bytes().all(|byte| byte.is_ascii_graphic()) =>
+        {
+            Ok(Some(id.clone()))
+        }
+        Some(_) => Err(BountyError::InvalidField {
+            field: field.to_string(),
+            reason: format!("expected 1 to {} visible ASCII characters", MAX_CALLER_ID_LEN),
+        }),
+    }
+}
+
+fn digest_id(parts: &[&[u8]]) -> String {
+    let digest = parts
+        .iter()
+        .fold(Sha256::new(), |hasher, part| hasher.chain_update(part).chain_update(b"."))
+        .finalize();
+    format!("worker-{}", hex::encode(&digest[..8]))
+}
+
+// Ids sent with the tx builder requests made for one call into the worker
+pub struct CallIds {
+    // `X-Request-Id`. Callers pass their own as the `request_id` param so
+    // their logs join with the worker's and the server's; without one it is
+    // derived from the call, so a retried call keeps it.
+    pub request_id: String,
+    // `Idempotency-Key`, from the `idempotency_key` param. Without one it is
+    // derived from the route and body actually sent, see `do_tx_building_request`.
+    pub idempotency_key: Option<String>,
+}
+
+impl CallIds {
+    pub fn from_params(method: &str, params: &Value) -> Result<CallIds, BountyError> {
+        let request_id = match caller_id(params, "request_id")? {
+            Some(request_id) => request_id,
+            None => digest_id(&[method.as_bytes(), params.to_string().as_bytes()]),
+        };
+        Ok(CallIds {
+            request_id,
+            idempotency_key: caller_id(params, "idempotency_key")?,
+        })
+    }
+}
+
+// Logs one JSON object per line so entries can be joined with the server logs on `request_id`
+pub fn log_event(level: worker::logging::Level, target: &str, event: serde_json::Value) {
+    worker::logging::log(level, target, &event.to_string());
+}
+
+// Signs `timestamp.nonce.body` so the tx builder can authenticate the worker
+fn sign_request(secret: &str, timestamp: u64, nonce: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}.{}.", timestamp, nonce).as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// The tx builder refuses a nonce it has seen within its skew window, so every
+// attempt takes the next value of a counter kept in KV
+fn next_nonce() -> WorkerResult<u64> {
+    let nonce = kv::get_value(REQUEST_NONCE_KEY)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<u64>(&bytes).ok())
+        .unwrap_or_default()
+        + 1;
+    kv::set_value(REQUEST_NONCE_KEY, &serde_json::to_vec(&nonce)?)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to store request nonce: {:?}", e)))?;
+    Ok(nonce)
+}
 
-    let parsed: TxEnvelope = response.json().map_err(|e| {
-        balius_sdk::Error::Internal(format!("Protocol response parse error: {:?}", e))
+// Posts to a tx builder route; `T` is the TxEnvelope it responds with
+//
+// Every attempt carries the same `Idempotency-Key`, so a retry after a lost
+// response replays the transaction the server already built instead of
+// building another one from different UTxOs. The derived key covers the body,
+// so a call rebuilt from a later tip is a new request rather than a conflict.
+pub fn do_tx_building_request<T: serde::de::DeserializeOwned>(
+    config: &WorkerConfig,
+    ids: &CallIds,
+    url: Url,
+    body: Option<Vec<u8>>,
+) -> WorkerResult<Json<T>> {
+    let request_id = ids.request_id.as_str();
+    let idempotency_key = match &ids.idempotency_key {
+        Some(key) => key.clone(),
+        None => digest_id(&[url.path().as_bytes(), body.as_deref().unwrap_or_default()]),
+    };
+    log_event(
+        worker::logging::Level::Info,
+        "tx-builder",
+        json!({
+            "request_id": request_id,
+            "idempotency_key": idempotency_key,
+            "url": url.as_str(),
+            "message": "Sending request"
+        }),
+    );
+
+    // Retries share the timestamp but are signed with a fresh nonce each
+    let signing = match &config.tx_builder_secret {
+        Some(secret) => Some((secret, tip_unix_secs(config)?)),
+        None => None,
+    };
+
+    let response = send_with_retries(&config.outbound, &url, || {
+        let mut request = HttpRequest::post(url.clone())
+            .header("Content-Type", "application/json")
+            .header("X-Request-Id", request_id)
+            .header("Idempotency-Key", idempotency_key.as_str());
+        if let Some((secret, timestamp)) = signing {
+            let nonce = next_nonce()?;
+            let signature = sign_request(secret, timestamp, nonce, body.as_deref().unwrap_or_default());
+            request = request
+                .header("X-Worker-Timestamp", timestamp.to_string().as_str())
+                .header("X-Worker-Nonce", nonce.to_string().as_str())
+                .header("X-Worker-Signature", signature.as_str());
+        }
+        request.body = body.clone();
+        Ok(request)
+    })
+    .map_err(|e| {
+        log_event(
+            worker::logging::Level::Error,
+            "tx-builder",
+            json!({ "request_id": request_id, "error": format!("{:?}", e) }),
+        );
+        balius_sdk::Error::Internal(format!("Protocol request error: {:?}", e))
+    })?;
+
+    log_event(
+        worker::logging::Level::Info,
+        "tx-builder",
+        json!({ "request_id": request_id, "status": response.status, "message": "Received response" }),
+    );
+
+    let parsed: T = response.json().map_err(|e| {
+        balius_sdk::Error::Internal(format!(
+            "Protocol response parse error for request {}: {:?}",
+            request_id, e
+        ))
     })?;
 
     Ok(Json(parsed))
 }
+
+// Splits a `txhash#index` UTxO reference into its raw parts
+pub fn parse_utxo_ref(utxo_ref: &str) -> Option<(Vec<u8>, u32)> {
+    let (hash, index) = utxo_ref.split_once('#')?;
+    Some((hex::decode(hash).ok()?, index.parse().ok()?))
+}
+
+// Bech32 base address for a payment key hash and optional stake key hash
+pub fn key_address(network: &str, payment_key: &str, stake_key: Option<&str>) -> Option<String> {
+    let payment = ShelleyPaymentPart::key_hash(payment_key.parse::<Hash<28>>().ok()?);
+    let delegation = match stake_key {
+        Some(stake_key) => ShelleyDelegationPart::key_hash(stake_key.parse::<Hash<28>>().ok()?),
+        None => ShelleyDelegationPart::Null,
+    };
+
+    let network = match network {
+        "mainnet" => Network::Mainnet,
+        _ => Network::Testnet,
+    };
+
+    Address::Shelley(ShelleyAddress::new(network, payment, delegation))
+        .to_bech32()
+        .ok()
+}
+
+// Deserializes request params, reporting the path of the first invalid field
+pub fn parse_params<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> Result<T, BountyError> {
+    serde_path_to_error::deserialize(value).map_err(|e| BountyError::InvalidField {
+        field: e.path().to_string(),
+        reason: e.inner().to_string(),
+    })
+}
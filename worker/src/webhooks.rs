@@ -0,0 +1,389 @@
+use balius_sdk::wit::balius::app as worker;
+
+use balius_sdk::http::HttpRequest;
+use balius_sdk::wit::balius::app::{kv, sign};
+use balius_sdk::{Config, Json, Params, WorkerResult};
+use hmac::{Hmac, Mac};
+use pallas_crypto::hash::Hasher;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use url::Url;
+use serde_json::json;
+
+use crate::bounty_index::{chain_tip_slot, BountyRecord};
+use crate::outbound::send_with_retries;
+use crate::subscriptions::{load_subscriptions, Subscription};
+use crate::time::{network_slots, recent_tip_slot};
+use crate::types::{OutboundConfig, WorkerConfig};
+use crate::utils::log_event;
+
+const OUTBOX_KEY: &str = "webhook-outbox";
+const DELIVERED_KEY: &str = "webhook-delivered";
+// Delivered events kept around so operators can replay them
+const MAX_DELIVERED: usize = 200;
+
+const SIGNING_KEY: &str = "payment-key";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventType {
+    BountyCreated,
+    FundsAdded,
+    Assigned,
+    Merged,
+    Claimed,
+    Closed,
+    Expired,
+    TxConfirmed,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::BountyCreated => "bounty-created",
+            EventType::FundsAdded => "funds-added",
+            EventType::Assigned => "assigned",
+            EventType::Merged => "merged",
+            EventType::Claimed => "claimed",
+            EventType::Closed => "closed",
+            EventType::Expired => "expired",
+            EventType::TxConfirmed => "tx-confirmed",
+        }
+    }
+}
+
+// Events still sent to the configured `webhook_url`
+const CONFIG_WEBHOOK_EVENTS: &[EventType] = &[EventType::TxConfirmed, EventType::Expired];
+
+// Envelope of every webhook delivery
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookEvent {
+    // Derived from the type and data, so re-emitting an event keeps its id
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub slot: u64,
+    pub data: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OutboxEntry {
+    pub url: String,
+    // None for deliveries to the configured `webhook_url`
+    #[serde(default)]
+    pub subscription_id: Option<String>,
+    pub event: WebhookEvent,
+    pub attempts: u32,
+    pub next_attempt_slot: u64,
+    pub last_error: Option<String>,
+    // Out of attempts; kept until an operator replays it
+    pub failed: bool,
+}
+
+fn event_id(event_type: &str, data: &serde_json::Value) -> String {
+    let preimage = format!("{}\n{}", event_type, data);
+    hex::encode(Hasher::<256>::hash(preimage.as_bytes()).as_ref())
+}
+
+fn load_entries(key: &str) -> Vec<OutboxEntry> {
+    kv::get_value(key)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_entries(key: &str, entries: &[OutboxEntry]) -> WorkerResult<()> {
+    kv::set_value(key, &serde_json::to_vec(entries)?)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Failed to store {}: {:?}", key, e)))
+}
+
+fn same_delivery(a: &OutboxEntry, b: &OutboxEntry) -> bool {
+    a.event.id == b.event.id && a.url == b.url && a.subscription_id == b.subscription_id
+}
+
+fn record_delivered(entry: OutboxEntry) -> WorkerResult<()> {
+    let mut delivered = load_entries(DELIVERED_KEY);
+    delivered.retain(|existing| !same_delivery(existing, &entry));
+    delivered.push(entry);
+    if delivered.len() > MAX_DELIVERED {
+        delivered.drain(..delivered.len() - MAX_DELIVERED);
+    }
+    save_entries(DELIVERED_KEY, &delivered)
+}
+
+///// SIGNING /////
+// `X-Githoney-Signature: t=<unix secs>,v1=<hex HMAC-SHA256 of "t.body">` with
+// the subscription's secret or `webhook_secret`, or `t=<unix secs>,ed25519=<hex
+// signature>` made with the worker's `payment-key` (public key in
+// `payment_key_public`) when neither is set.
+fn signature_header(secret: Option<&str>, timestamp: u64, body: &[u8]) -> WorkerResult<String> {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+
+    match secret {
+        Some(secret) => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+            mac.update(&message);
+            Ok(format!(
+                "t={},v1={}",
+                timestamp,
+                hex::encode(mac.finalize().into_bytes())
+            ))
+        }
+        None => {
+            let signature = sign::sign_payload(SIGNING_KEY, &message)
+                .map_err(|e| balius_sdk::Error::Internal(format!("Sign error: {:?}", e)))?;
+            Ok(format!("t={},ed25519={}", timestamp, hex::encode(signature)))
+        }
+    }
+}
+
+// A delivery counts only once the receiver answers 2xx
+fn deliver(
+    config: &WorkerConfig,
+    entry: &OutboxEntry,
+    secret: Option<&str>,
+    timestamp: u64,
+) -> WorkerResult<()> {
+    let url = Url::parse(&entry.url)
+        .map_err(|e| balius_sdk::Error::Internal(format!("Invalid webhook URL: {}", e)))?;
+    let body = serde_json::to_vec(&entry.event)?;
+    let signature = signature_header(secret, timestamp, &body)?;
+
+    // One attempt per flush: retrying here would be immediate, so failures
+    // wait out the outbox backoff instead
+    let policy = OutboundConfig {
+        max_attempts: 1,
+        ..config.outbound.clone()
+    };
+    let response = send_with_retries(&policy, &url, || {
+        let mut request = HttpRequest::post(url.clone())
+            .header("Content-Type", "application/json")
+            .header("X-Githoney-Event-Id", entry.event.id.as_str())
+            .header("X-Githoney-Event-Type", entry.event.event_type.as_str())
+            .header("X-Githoney-Signature", signature.as_str());
+        request.body = Some(body.clone());
+        Ok(request)
+    })?;
+
+    if !response.is_ok() {
+        return Err(balius_sdk::Error::Internal(format!(
+            "Webhook request failed with status {}",
+            response.status
+        )));
+    }
+
+    Ok(())
+}
+
+fn backoff_slots(config: &WorkerConfig, attempts: u32) -> u64 {
+    config
+        .outbound
+        .outbox_base_backoff_slots
+        .saturating_mul(1u64 << attempts.min(32))
+        .min(config.outbound.outbox_max_backoff_slots)
+}
+
+// Attempts every due, non-failed delivery and stores what is left.
+//
+// Backoff is counted against a recent tip, whose slots follow the network's
+// clock; the tip of a worker that is catching up races through old slots.
+pub fn flush_outbox(config: &WorkerConfig) -> WorkerResult<()> {
+    let outbox = load_entries(OUTBOX_KEY);
+    let due = |entry: &OutboxEntry, slot: u64| !entry.failed && entry.next_attempt_slot <= slot;
+    if !outbox
+        .iter()
+        .any(|entry| due(entry, chain_tip_slot().unwrap_or_default()))
+    {
+        return Ok(());
+    }
+
+    // Receivers reject old signature timestamps, so wait for a recent tip
+    // instead of spending attempts on deliveries that cannot succeed
+    let (slot, timestamp) = match recent_tip_slot(config) {
+        Ok(slot) => (slot, network_slots(&config.network)?.slot_to_posix_ms(slot) / 1000),
+        Err(e) => {
+            log_event(
+                worker::logging::Level::Debug,
+                "webhook",
+                json!({ "message": "Deferring webhook deliveries", "error": format!("{:?}", e) }),
+            );
+            return Ok(());
+        }
+    };
+
+    let subscriptions = load_subscriptions();
+    let mut remaining = Vec::with_capacity(outbox.len());
+    for mut entry in outbox {
+        if !due(&entry, slot) {
+            remaining.push(entry);
+            continue;
+        }
+
+        let secret = match &entry.subscription_id {
+            Some(id) => match subscriptions.iter().find(|subscription| &subscription.id == id) {
+                Some(subscription) => Some(subscription.secret.as_str()),
+                // Unsubscribed since the event was queued
+                None => continue,
+            },
+            None => config.webhook_secret.as_deref(),
+        };
+
+        entry.attempts += 1;
+        match deliver(config, &entry, secret, timestamp) {
+            Ok(()) => {
+                entry.last_error = None;
+                record_delivered(entry)?;
+            }
+            Err(e) => {
+                log_event(
+                    worker::logging::Level::Warn,
+                    "webhook",
+                    json!({
+                        "message": "Webhook delivery failed",
+                        "event_id": entry.event.id,
+                        "url": entry.url,
+                        "attempt": entry.attempts,
+                        "error": format!("{:?}", e),
+                    }),
+                );
+                entry.last_error = Some(format!("{:?}", e));
+                entry.failed = entry.attempts >= config.outbound.outbox_max_attempts;
+                entry.next_attempt_slot = slot + backoff_slots(config, entry.attempts);
+                remaining.push(entry);
+            }
+        }
+    }
+
+    save_entries(OUTBOX_KEY, &remaining)
+}
+
+// Queues the event for the configured `webhook_url` and every matching
+// subscription, then tries to deliver it right away. `bounty` is the bounty
+// the event is about, used by the bounty id and maintainer filters.
+pub fn emit<T: Serialize>(
+    config: &WorkerConfig,
+    event_type: EventType,
+    bounty: Option<&BountyRecord>,
+    data: &T,
+) -> WorkerResult<()> {
+    let data = serde_json::to_value(data)?;
+    let slot = chain_tip_slot().unwrap_or_default();
+    let event = WebhookEvent {
+        id: event_id(event_type.as_str(), &data),
+        event_type: event_type.as_str().to_string(),
+        slot,
+        data,
+    };
+
+    let mut destinations = Vec::new();
+    if CONFIG_WEBHOOK_EVENTS.contains(&event_type) && !config.webhook_url.is_empty() {
+        destinations.push((config.webhook_url.clone(), None));
+    }
+    for Subscription { id, url, .. } in load_subscriptions()
+        .into_iter()
+        .filter(|subscription| subscription.matches(event_type, bounty))
+    {
+        destinations.push((url, Some(id)));
+    }
+    if destinations.is_empty() {
+        return Ok(());
+    }
+
+    let mut outbox = load_entries(OUTBOX_KEY);
+    for (url, subscription_id) in destinations {
+        let entry = OutboxEntry {
+            url,
+            subscription_id,
+            event: event.clone(),
+            attempts: 0,
+            next_attempt_slot: slot,
+            last_error: None,
+            failed: false,
+        };
+        if !outbox.iter().any(|queued| same_delivery(queued, &entry)) {
+            outbox.push(entry);
+        }
+    }
+    save_entries(OUTBOX_KEY, &outbox)?;
+
+    flush_outbox(config)
+}
+
+// Removes the queued and delivered entries of a removed subscription
+pub fn drop_subscription_deliveries(subscription_id: &str) -> WorkerResult<usize> {
+    let belongs = |entry: &OutboxEntry| entry.subscription_id.as_deref() == Some(subscription_id);
+
+    let mut outbox = load_entries(OUTBOX_KEY);
+    let queued = outbox.len();
+    outbox.retain(|entry| !belongs(entry));
+    let dropped = queued - outbox.len();
+    save_entries(OUTBOX_KEY, &outbox)?;
+
+    let mut delivered = load_entries(DELIVERED_KEY);
+    delivered.retain(|entry| !belongs(entry));
+    save_entries(DELIVERED_KEY, &delivered)?;
+
+    Ok(dropped)
+}
+
+///// OPERATOR HANDLERS /////
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ReplayWebhooksParams {
+    // Events to replay; all failed deliveries when omitted
+    #[serde(default)]
+    pub event_ids: Option<Vec<String>>,
+    // Also resend matching events that were already delivered
+    #[serde(default)]
+    pub include_delivered: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReplayWebhooksResponse {
+    pub requeued: usize,
+    pub pending: usize,
+    pub failed: usize,
+}
+
+pub fn replay_webhooks(
+    config: Config<WorkerConfig>,
+    params: Params<ReplayWebhooksParams>,
+) -> WorkerResult<Json<ReplayWebhooksResponse>> {
+    let selected = |entry: &OutboxEntry| match &params.event_ids {
+        Some(ids) => ids.contains(&entry.event.id),
+        None => true,
+    };
+    let slot = chain_tip_slot().unwrap_or_default();
+
+    let mut outbox = load_entries(OUTBOX_KEY);
+    let mut requeued = 0;
+    for entry in outbox.iter_mut().filter(|entry| entry.failed && selected(entry)) {
+        entry.failed = false;
+        entry.attempts = 0;
+        entry.next_attempt_slot = slot;
+        requeued += 1;
+    }
+
+    if params.include_delivered {
+        for mut entry in load_entries(DELIVERED_KEY).into_iter().filter(selected) {
+            let queued = outbox.iter().any(|queued| same_delivery(queued, &entry));
+            if queued {
+                continue;
+            }
+            entry.attempts = 0;
+            entry.next_attempt_slot = slot;
+            outbox.push(entry);
+            requeued += 1;
+        }
+    }
+
+    save_entries(OUTBOX_KEY, &outbox)?;
+    flush_outbox(&config)?;
+
+    let outbox = load_entries(OUTBOX_KEY);
+    Ok(Json(ReplayWebhooksResponse {
+        requeued,
+        pending: outbox.iter().filter(|entry| !entry.failed).count(),
+        failed: outbox.iter().filter(|entry| entry.failed).count(),
+    }))
+}